@@ -1,7 +1,9 @@
 use tonic::Request;
 
 use block_streamer::block_streamer_client::BlockStreamerClient;
-use block_streamer::{start_stream_request::Rule, ActionAnyRule, StartStreamRequest, Status};
+use block_streamer::{
+    start_stream_request::Rule, ActionAnyRule, ChainId, StartStreamRequest, Status,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -14,6 +16,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             function_name: "test".to_string(),
             version: 0,
             redis_stream: "morgs.near/test:block_stream".to_string(),
+            chain_id: ChainId::Mainnet.into(),
             rule: Some(Rule::ActionAnyRule(ActionAnyRule {
                 affected_account_id: "social.near".to_string(),
                 status: Status::Success.into(),
@@ -16,7 +16,8 @@ use crate::lake_s3_client::SharedLakeS3Client;
 use crate::metrics;
 use crate::receiver_blocks::ReceiverBlocksProcessor;
 use crate::redis::RedisClient;
-use crate::rules::types::ChainId;
+use crate::rules::outcomes_reducer::TransactionHashes;
+use crate::rules::types::{ChainId, IndexerRuleMatch};
 
 /// The number of blocks to prefetch within `near-lake-framework`. The internal default is 100, but
 /// we need this configurable for testing purposes.
@@ -322,7 +323,8 @@ impl BlockStream {
         account_id = indexer.account_id.as_str(),
         function_name = indexer.function_name,
         start_block_height = start_block_height,
-        redis_stream = redis_stream
+        redis_stream = redis_stream,
+        chain_id = %chain_id
     )
 )]
 pub(crate) async fn start_block_stream(
@@ -344,9 +346,11 @@ pub(crate) async fn start_block_stream(
     let last_bitmap_indexer_block = process_bitmap_indexer_blocks(
         start_block_height,
         reciever_blocks_processor,
+        &lake_s3_client,
         redis.clone(),
         indexer,
         redis_stream.clone(),
+        chain_id,
     )
     .await
     .context("Failed while fetching and streaming bitmap indexer blocks")?;
@@ -371,30 +375,92 @@ pub(crate) async fn start_block_stream(
     Ok(())
 }
 
+/// Fetches a single block from Near Lake and returns the matches for the rule within it.
+/// Used to narrow down the candidate heights returned by the bitmap indexer, which only
+/// pre-filters by receiver.
+async fn fetch_lake_block_matches(
+    block_height: near_indexer_primitives::types::BlockHeight,
+    lake_s3_client: &SharedLakeS3Client,
+    indexer: &IndexerConfig,
+    chain_id: &ChainId,
+) -> anyhow::Result<Vec<IndexerRuleMatch>> {
+    let streamer_message = near_lake_framework::s3_fetchers::fetch_streamer_message(
+        lake_s3_client,
+        &chain_id.get_lake_bucket(),
+        block_height,
+    )
+    .await
+    .map_err(|err| anyhow::anyhow!("Failed to fetch block {block_height}: {err:?}"))?;
+
+    Ok(crate::rules::reduce_indexer_rule_matches(
+        &indexer.rule,
+        &streamer_message,
+        chain_id.clone(),
+    ))
+}
+
+/// Derives the receiver pattern used to query the bitmap indexer. Returns `None` when the rule
+/// could match receipts outside of any specific set of accounts.
+fn get_bitmap_contract_pattern(rule: &Rule) -> Option<String> {
+    match rule {
+        Rule::ActionAny {
+            affected_account_id,
+            ..
+        }
+        | Rule::ActionFunctionCall {
+            affected_account_id,
+            ..
+        }
+        | Rule::ActionFunctionCallArgs {
+            affected_account_id,
+            ..
+        }
+        | Rule::ActionKind {
+            affected_account_id,
+            ..
+        } => Some(affected_account_id.to_owned()),
+        Rule::Event {
+            contract_account_id,
+            ..
+        } => Some(contract_account_id.to_owned()),
+        Rule::Any { rules } => rules
+            .iter()
+            .map(get_bitmap_contract_pattern)
+            .collect::<Option<Vec<String>>>()
+            .map(|patterns| patterns.join(",")),
+        Rule::All { rules } => rules.iter().find_map(get_bitmap_contract_pattern),
+        Rule::Not { .. } => None,
+        // Bitmaps only index receipts, so state changes from other causes would be missed
+        Rule::StateChange { .. } => None,
+        // Bitmaps only index receivers, not signers
+        Rule::TransactionSigner { .. } => None,
+    }
+}
+
 async fn process_bitmap_indexer_blocks(
     start_block_height: near_indexer_primitives::types::BlockHeight,
     reciever_blocks_processor: Arc<ReceiverBlocksProcessor>,
+    lake_s3_client: &SharedLakeS3Client,
     redis: Arc<RedisClient>,
     indexer: &IndexerConfig,
     redis_stream: String,
+    chain_id: &ChainId,
 ) -> anyhow::Result<u64> {
-    let contract_pattern: String = match &indexer.rule {
-        Rule::ActionAny {
-            affected_account_id,
-            ..
-        } => affected_account_id.to_owned(),
-        Rule::ActionFunctionCall { .. } => {
-            tracing::error!("ActionFunctionCall matching rule not yet supported for bitmap processing, function");
-            return Ok(start_block_height);
-        }
-        Rule::Event { .. } => {
-            tracing::error!(
-                "Event matching rule not yet supported for bitmap processing, function"
+    let contract_pattern = match get_bitmap_contract_pattern(&indexer.rule) {
+        Some(contract_pattern) => contract_pattern,
+        None => {
+            tracing::debug!(
+                "Skipping fetching block heights from bitmap indexer as rule cannot be narrowed down to specific accounts"
             );
+
             return Ok(start_block_height);
         }
     };
 
+    // Bitmaps only index receivers, so anything more specific than `ActionAny` must be verified
+    // against the block itself before publishing
+    let requires_block_match = !matches!(indexer.rule, Rule::ActionAny { .. });
+
     tracing::debug!(
         "Fetching block heights starting from {} from Bitmap Indexer",
         start_block_height,
@@ -412,12 +478,15 @@ async fn process_bitmap_indexer_blocks(
         return Ok(start_block_height);
     }
 
-    let matching_block_heights = reciever_blocks_processor
-        .stream_matching_block_heights(start_block_height, contract_pattern);
+    let matching_block_heights = reciever_blocks_processor.stream_matching_block_heights(
+        start_block_height,
+        contract_pattern,
+        chain_id.clone(),
+    );
 
     tokio::pin!(matching_block_heights);
 
-    let mut last_published_block_height: u64 = start_block_height;
+    let mut last_processed_block_height: u64 = start_block_height;
 
     let indexer_name = indexer.get_full_name();
 
@@ -427,14 +496,40 @@ async fn process_bitmap_indexer_blocks(
                 metrics::RECEIVER_BLOCKS_FAILURE
                     .with_label_values(&[&indexer_name])
                     .set(0);
-                redis
-                    .publish_block(indexer, redis_stream.clone(), block_height, MAX_STREAM_SIZE)
-                    .await?;
+
+                if requires_block_match {
+                    let matches =
+                        fetch_lake_block_matches(block_height, lake_s3_client, indexer, chain_id)
+                            .await?;
+
+                    if !matches.is_empty() {
+                        redis
+                            .publish_block(
+                                indexer,
+                                redis_stream.clone(),
+                                block_height,
+                                &matches,
+                                MAX_STREAM_SIZE,
+                            )
+                            .await?;
+                    }
+                } else {
+                    // Published without fetching the block, so the runner is left to filter it
+                    redis
+                        .publish_block_with_unknown_matches(
+                            indexer,
+                            redis_stream.clone(),
+                            block_height,
+                            MAX_STREAM_SIZE,
+                        )
+                        .await?;
+                }
+
                 redis
                     .set_last_processed_block(indexer, block_height)
                     .await?;
 
-                last_published_block_height = block_height;
+                last_processed_block_height = block_height;
             }
             Err(err) => {
                 metrics::RECEIVER_BLOCKS_FAILURE
@@ -449,7 +544,7 @@ async fn process_bitmap_indexer_blocks(
         }
     }
 
-    Ok(last_published_block_height)
+    Ok(last_processed_block_height)
 }
 
 async fn process_near_lake_blocks(
@@ -474,6 +569,7 @@ async fn process_near_lake_blocks(
     .context("Failed to build lake config")?;
 
     let mut last_indexed_block = start_block_height;
+    let mut transaction_hashes = TransactionHashes::default();
 
     let (sender, mut stream) = near_lake_framework::streamer(lake_config);
 
@@ -485,9 +581,10 @@ async fn process_near_lake_blocks(
             .set_last_processed_block(indexer, block_height)
             .await?;
 
-        let matches = crate::rules::reduce_indexer_rule_matches(
+        let matches = crate::rules::reduce_consecutive_indexer_rule_matches(
             &indexer.rule,
             &streamer_message,
+            &mut transaction_hashes,
             chain_id.clone(),
         );
 
@@ -499,7 +596,13 @@ async fn process_near_lake_blocks(
             }
 
             redis
-                .publish_block(indexer, redis_stream.clone(), block_height, MAX_STREAM_SIZE)
+                .publish_block(
+                    indexer,
+                    redis_stream.clone(),
+                    block_height,
+                    &matches,
+                    MAX_STREAM_SIZE,
+                )
                 .await?;
         }
     }
@@ -576,15 +679,26 @@ mod tests {
 
         let mut mock_redis = crate::redis::RedisClient::default();
         mock_redis
-            .expect_publish_block()
+            .expect_publish_block_with_unknown_matches()
             .with(
                 predicate::always(),
                 predicate::eq("stream key".to_string()),
-                predicate::in_iter([107503702, 107503703, 107503705]),
+                predicate::in_iter([107503702, 107503703]),
                 predicate::always(),
             )
             .returning(|_, _, _, _| Ok(()))
-            .times(3);
+            .times(2);
+        mock_redis
+            .expect_publish_block()
+            .with(
+                predicate::always(),
+                predicate::eq("stream key".to_string()),
+                predicate::eq(107503705),
+                predicate::function(|matches: &[IndexerRuleMatch]| !matches.is_empty()),
+                predicate::always(),
+            )
+            .returning(|_, _, _, _, _| Ok(()))
+            .once();
         mock_redis
             .expect_set_last_processed_block()
             .with(
@@ -694,8 +808,9 @@ mod tests {
                 predicate::eq("stream key".to_string()),
                 predicate::in_iter([107503705]),
                 predicate::always(),
+                predicate::always(),
             )
-            .returning(|_, _, _, _| Ok(()))
+            .returning(|_, _, _, _, _| Ok(()))
             .times(1);
         mock_redis_client
             .expect_set_last_processed_block()
@@ -788,9 +903,11 @@ mod tests {
         process_bitmap_indexer_blocks(
             107503704,
             Arc::new(mock_reciever_blocks_processor),
+            &crate::lake_s3_client::SharedLakeS3Client::default(),
             Arc::new(mock_redis_client),
             &indexer_config,
             "stream key".to_string(),
+            &ChainId::Mainnet,
         )
         .await
         .unwrap();
@@ -828,9 +945,11 @@ mod tests {
         process_bitmap_indexer_blocks(
             107503704,
             Arc::new(mock_reciever_blocks_processor),
+            &crate::lake_s3_client::SharedLakeS3Client::default(),
             Arc::new(mock_redis_client),
             &indexer_config,
             "stream key".to_string(),
+            &ChainId::Mainnet,
         )
         .await
         .unwrap();
@@ -868,11 +987,255 @@ mod tests {
         process_bitmap_indexer_blocks(
             107503704,
             Arc::new(mock_reciever_blocks_processor),
+            &crate::lake_s3_client::SharedLakeS3Client::default(),
             Arc::new(mock_redis_client),
             &indexer_config,
             "stream key".to_string(),
+            &ChainId::Mainnet,
         )
         .await
         .unwrap();
     }
+
+    #[test]
+    fn derives_bitmap_contract_pattern_from_composite_rules() {
+        let function_call_rule = registry_types::Rule::ActionFunctionCall {
+            affected_account_id: "app.nearcrowd.near".to_string(),
+            status: registry_types::Status::Any,
+            function: "submit_review".to_string(),
+        };
+        let event_rule = registry_types::Rule::Event {
+            contract_account_id: "token.sweat".to_string(),
+            standard: "nep141".to_string(),
+            version: "*".to_string(),
+            event: "ft_transfer".to_string(),
+        };
+        let not_rule = registry_types::Rule::Not {
+            rule: Box::new(event_rule.clone()),
+        };
+
+        assert_eq!(
+            get_bitmap_contract_pattern(&registry_types::Rule::Any {
+                rules: vec![function_call_rule.clone(), event_rule.clone()]
+            }),
+            Some("app.nearcrowd.near,token.sweat".to_string())
+        );
+        assert_eq!(
+            get_bitmap_contract_pattern(&registry_types::Rule::All {
+                rules: vec![not_rule.clone(), event_rule.clone()]
+            }),
+            Some("token.sweat".to_string())
+        );
+        assert_eq!(
+            get_bitmap_contract_pattern(&registry_types::Rule::Any {
+                rules: vec![function_call_rule, not_rule.clone()]
+            }),
+            None
+        );
+        assert_eq!(get_bitmap_contract_pattern(&not_rule), None);
+    }
+
+    fn mock_lake_s3_client_from_data() -> crate::lake_s3_client::SharedLakeS3Client {
+        let mut mock_lake_s3_client = crate::lake_s3_client::SharedLakeS3Client::default();
+
+        mock_lake_s3_client
+            .expect_get_object_bytes()
+            .with(predicate::eq("near-lake-data-mainnet"), predicate::always())
+            .returning(|_, prefix| {
+                let path = format!("{}/data/{}", env!("CARGO_MANIFEST_DIR"), prefix);
+
+                std::fs::read(path).map_err(|e| GetObjectBytesError(Arc::new(e)))
+            });
+
+        mock_lake_s3_client
+    }
+
+    fn mock_reciever_blocks_processor_for(contract_filter: &str) -> ReceiverBlocksProcessor {
+        let mut mock_s3_client = crate::s3_client::S3Client::default();
+
+        mock_s3_client
+            .expect_get_text_file()
+            .with(
+                predicate::eq("near-lake-data-mainnet".to_string()),
+                predicate::eq("000107503704/block.json"),
+            )
+            .returning(move |_, _| Ok(crate::test_utils::generate_block_with_date("2023-12-09")));
+
+        let mut mock_graphql_client = crate::graphql::client::GraphQLClient::default();
+
+        mock_graphql_client
+            .expect_get_bitmaps_exact()
+            .with(
+                predicate::eq(vec![contract_filter.to_owned()]),
+                predicate::eq(crate::test_utils::utc_date_time_from_date_string(
+                    "2023-12-09",
+                )),
+            )
+            .returning(|_, _| {
+                Ok(vec![
+                    crate::graphql::client::get_bitmaps_exact::GetBitmapsExactDataplatformNearReceiverBlocksBitmaps {
+                        first_block_height: 107503704,
+                        bitmap: "oA==".to_string(),
+                    }
+                ])
+            });
+
+        mock_graphql_client
+            .expect_get_bitmaps_exact()
+            .returning(|_, _| Ok(vec![]));
+
+        ReceiverBlocksProcessor::new(mock_graphql_client, mock_s3_client)
+    }
+
+    #[tokio::test]
+    async fn filters_bitmap_blocks_for_function_call_rule() {
+        let mock_reciever_blocks_processor =
+            mock_reciever_blocks_processor_for("app.nearcrowd.near");
+
+        let mut mock_redis_client = crate::redis::RedisClient::default();
+        mock_redis_client
+            .expect_publish_block()
+            .with(
+                predicate::always(),
+                predicate::eq("stream key".to_string()),
+                predicate::eq(107503705),
+                predicate::function(|matches: &[IndexerRuleMatch]| !matches.is_empty()),
+                predicate::always(),
+            )
+            .returning(|_, _, _, _, _| Ok(()))
+            .once();
+        mock_redis_client
+            .expect_set_last_processed_block()
+            .with(
+                predicate::always(),
+                predicate::in_iter([107503704, 107503705]),
+            )
+            .returning(|_, _| Ok(()))
+            .times(2);
+
+        let indexer_config = crate::indexer_config::IndexerConfig {
+            account_id: near_indexer_primitives::types::AccountId::try_from(
+                "morgs.near".to_string(),
+            )
+            .unwrap(),
+            function_name: "test".to_string(),
+            rule: registry_types::Rule::ActionFunctionCall {
+                affected_account_id: "app.nearcrowd.near".to_string(),
+                status: registry_types::Status::Any,
+                function: "submit_review".to_string(),
+            },
+        };
+
+        let last_processed_block_height = process_bitmap_indexer_blocks(
+            107503704,
+            Arc::new(mock_reciever_blocks_processor),
+            &mock_lake_s3_client_from_data(),
+            Arc::new(mock_redis_client),
+            &indexer_config,
+            "stream key".to_string(),
+            &ChainId::Mainnet,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(last_processed_block_height, 107503705);
+    }
+
+    #[tokio::test]
+    async fn filters_bitmap_blocks_for_event_rule() {
+        let mock_reciever_blocks_processor = mock_reciever_blocks_processor_for("token.sweat");
+
+        let mut mock_redis_client = crate::redis::RedisClient::default();
+        mock_redis_client.expect_publish_block().never();
+        mock_redis_client
+            .expect_set_last_processed_block()
+            .with(
+                predicate::always(),
+                predicate::in_iter([107503704, 107503705]),
+            )
+            .returning(|_, _| Ok(()))
+            .times(2);
+
+        let indexer_config = crate::indexer_config::IndexerConfig {
+            account_id: near_indexer_primitives::types::AccountId::try_from(
+                "morgs.near".to_string(),
+            )
+            .unwrap(),
+            function_name: "test".to_string(),
+            rule: registry_types::Rule::Event {
+                contract_account_id: "token.sweat".to_string(),
+                standard: "nep141".to_string(),
+                version: "*".to_string(),
+                event: "ft_burn".to_string(),
+            },
+        };
+
+        process_bitmap_indexer_blocks(
+            107503704,
+            Arc::new(mock_reciever_blocks_processor),
+            &mock_lake_s3_client_from_data(),
+            Arc::new(mock_redis_client),
+            &indexer_config,
+            "stream key".to_string(),
+            &ChainId::Mainnet,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn publishes_bitmap_blocks_with_unknown_matches_for_action_any_rule() {
+        let mock_reciever_blocks_processor =
+            mock_reciever_blocks_processor_for("app.nearcrowd.near");
+
+        let mut mock_lake_s3_client = crate::lake_s3_client::SharedLakeS3Client::default();
+        mock_lake_s3_client.expect_get_object_bytes().never();
+
+        let mut mock_redis_client = crate::redis::RedisClient::default();
+        mock_redis_client.expect_publish_block().never();
+        mock_redis_client
+            .expect_publish_block_with_unknown_matches()
+            .with(
+                predicate::always(),
+                predicate::eq("stream key".to_string()),
+                predicate::in_iter([107503704, 107503705]),
+                predicate::always(),
+            )
+            .returning(|_, _, _, _| Ok(()))
+            .times(2);
+        mock_redis_client
+            .expect_set_last_processed_block()
+            .with(
+                predicate::always(),
+                predicate::in_iter([107503704, 107503705]),
+            )
+            .returning(|_, _| Ok(()))
+            .times(2);
+
+        let indexer_config = crate::indexer_config::IndexerConfig {
+            account_id: near_indexer_primitives::types::AccountId::try_from(
+                "morgs.near".to_string(),
+            )
+            .unwrap(),
+            function_name: "test".to_string(),
+            rule: registry_types::Rule::ActionAny {
+                affected_account_id: "app.nearcrowd.near".to_string(),
+                status: registry_types::Status::Success,
+            },
+        };
+
+        let last_processed_block_height = process_bitmap_indexer_blocks(
+            107503704,
+            Arc::new(mock_reciever_blocks_processor),
+            &mock_lake_s3_client,
+            Arc::new(mock_redis_client),
+            &indexer_config,
+            "stream key".to_string(),
+            &ChainId::Mainnet,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(last_processed_block_height, 107503705);
+    }
 }
@@ -65,7 +65,6 @@ impl From<&str> for ContractPatternType {
 pub struct ReceiverBlocksProcessor {
     graphql_client: GraphQLClient,
     s3_client: crate::s3_client::S3Client,
-    chain_id: ChainId,
 }
 
 impl ReceiverBlocksProcessor {
@@ -73,25 +72,21 @@ impl ReceiverBlocksProcessor {
         Self {
             graphql_client,
             s3_client,
-            chain_id: ChainId::Mainnet,
         }
     }
 
-    fn get_lake_bucket(&self) -> String {
-        match self.chain_id {
-            ChainId::Mainnet => "near-lake-data-mainnet".to_string(),
-            ChainId::Testnet => "near-lake-data-testnet".to_string(),
-        }
-    }
-
-    pub async fn get_nearest_block_date(&self, block_height: u64) -> anyhow::Result<DateTime<Utc>> {
+    pub async fn get_nearest_block_date(
+        &self,
+        block_height: u64,
+        chain_id: &ChainId,
+    ) -> anyhow::Result<DateTime<Utc>> {
         let mut current_block_height = block_height;
         let mut retry_count = 1;
         loop {
             let block_key = format!("{:0>12}/block.json", current_block_height);
             match self
                 .s3_client
-                .get_text_file(&self.get_lake_bucket(), &block_key)
+                .get_text_file(&chain_id.get_lake_bucket(), &block_key)
                 .await
             {
                 Ok(text) => {
@@ -161,9 +156,10 @@ impl ReceiverBlocksProcessor {
         &'a self,
         start_block_height: near_indexer_primitives::types::BlockHeight,
         contract_pattern: String,
+        chain_id: ChainId,
     ) -> impl futures::Stream<Item = anyhow::Result<u64>> + 'b {
         try_stream! {
-            let start_date = self.get_nearest_block_date(start_block_height).await?;
+            let start_date = self.get_nearest_block_date(start_block_height, &chain_id).await?;
             let contract_pattern_type = ContractPatternType::from(contract_pattern.as_str());
             let mut current_date = start_date
                 .with_hour(0)
@@ -321,12 +317,44 @@ mod tests {
         let reciever_blocks_processor =
             ReceiverBlocksProcessor::new(mock_graphql_client, mock_s3_client);
 
-        let stream =
-            reciever_blocks_processor.stream_matching_block_heights(0, "someone.near".to_owned());
+        let stream = reciever_blocks_processor.stream_matching_block_heights(
+            0,
+            "someone.near".to_owned(),
+            ChainId::Mainnet,
+        );
 
         assert_eq!(stream.try_collect::<Vec<u64>>().await.unwrap(), vec![1]);
     }
 
+    #[tokio::test]
+    async fn uses_lake_bucket_of_chain() {
+        let mut mock_s3_client = crate::s3_client::S3Client::default();
+        mock_s3_client
+            .expect_get_text_file()
+            .with(
+                predicate::eq("near-lake-data-testnet".to_string()),
+                predicate::eq("000000000001/block.json"),
+            )
+            .times(1)
+            .returning(move |_, _| {
+                Ok(crate::test_utils::generate_block_with_timestamp(
+                    "2023-12-09T12:00:00",
+                ))
+            });
+
+        let mock_graphql_client = crate::graphql::client::GraphQLClient::default();
+
+        let reciever_blocks_processor =
+            ReceiverBlocksProcessor::new(mock_graphql_client, mock_s3_client);
+
+        let date = reciever_blocks_processor
+            .get_nearest_block_date(1, &ChainId::Testnet)
+            .await
+            .unwrap();
+
+        assert_eq!(date.date_naive().to_string(), "2023-12-09");
+    }
+
     #[tokio::test]
     async fn filters_blocks_before_start_block() {
         let mut mock_s3_client = crate::s3_client::S3Client::default();
@@ -354,8 +382,11 @@ mod tests {
         let reciever_blocks_processor =
             ReceiverBlocksProcessor::new(mock_graphql_client, mock_s3_client);
 
-        let stream =
-            reciever_blocks_processor.stream_matching_block_heights(2, "someone.near".to_owned());
+        let stream = reciever_blocks_processor.stream_matching_block_heights(
+            2,
+            "someone.near".to_owned(),
+            ChainId::Mainnet,
+        );
 
         assert_eq!(
             stream.try_collect::<Vec<u64>>().await.unwrap(),
@@ -425,8 +456,11 @@ mod tests {
         let reciever_blocks_processor =
             ReceiverBlocksProcessor::new(mock_graphql_client, mock_s3_client);
 
-        let stream = reciever_blocks_processor
-            .stream_matching_block_heights(0, "*.someone.near".to_string());
+        let stream = reciever_blocks_processor.stream_matching_block_heights(
+            0,
+            "*.someone.near".to_string(),
+            ChainId::Mainnet,
+        );
 
         assert_eq!(
             stream.try_collect::<Vec<u64>>().await.unwrap(),
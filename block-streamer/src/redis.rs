@@ -7,6 +7,7 @@ use redis::{aio::ConnectionManager, AsyncCommands, RedisError, ToRedisArgs};
 
 use crate::indexer_config::IndexerConfig;
 use crate::metrics;
+use crate::rules::types::IndexerRuleMatch;
 use crate::utils;
 
 #[cfg(test)]
@@ -115,6 +116,9 @@ pub struct RedisClientImpl {
 #[cfg_attr(test, mockall::automock)]
 impl RedisClientImpl {
     const STREAMER_MESSAGE_PREFIX: &'static str = "streamer_message:";
+    const BLOCK_HEIGHT_FIELD: &'static str = "block_height";
+    const MATCHES_FIELD: &'static str = "matches";
+    const MATCHES_UNKNOWN_FIELD: &'static str = "matches_unknown";
 
     pub async fn connect(redis_url: &str) -> Result<Self, RedisError> {
         let commands = RedisCommands::connect(redis_url).await?;
@@ -179,11 +183,61 @@ impl RedisClientImpl {
             .context("Failed to cache streamer message")
     }
 
+    /// Publishes the block height to the stream, along with the JSON encoded rule matches
+    /// within that block when they are known
     pub async fn publish_block(
         &self,
         indexer: &IndexerConfig,
         stream: String,
         block_height: u64,
+        matches: &[IndexerRuleMatch],
+        max_size: u64,
+    ) -> anyhow::Result<()> {
+        let mut fields = vec![(
+            String::from(Self::BLOCK_HEIGHT_FIELD),
+            block_height.to_string(),
+        )];
+
+        if !matches.is_empty() {
+            fields.push((
+                String::from(Self::MATCHES_FIELD),
+                serde_json::to_string(matches).context("Failed to serialize rule matches")?,
+            ));
+        }
+
+        self.add_to_block_stream(indexer, stream, fields, max_size)
+            .await
+    }
+
+    /// Publishes a block which may not match the rule, e.g. one only pre-filtered by the bitmap
+    /// indexer, so that consumers filter the block themselves rather than treat it as matchless
+    pub async fn publish_block_with_unknown_matches(
+        &self,
+        indexer: &IndexerConfig,
+        stream: String,
+        block_height: u64,
+        max_size: u64,
+    ) -> anyhow::Result<()> {
+        let fields = vec![
+            (
+                String::from(Self::BLOCK_HEIGHT_FIELD),
+                block_height.to_string(),
+            ),
+            (
+                String::from(Self::MATCHES_UNKNOWN_FIELD),
+                String::from("true"),
+            ),
+        ];
+
+        self.add_to_block_stream(indexer, stream, fields, max_size)
+            .await
+    }
+
+    async fn add_to_block_stream(
+        &self,
+        indexer: &IndexerConfig,
+        stream: String,
+        fields: Vec<(String, String)>,
         max_size: u64,
     ) -> anyhow::Result<()> {
         loop {
@@ -205,10 +259,7 @@ impl RedisClientImpl {
             .inc();
 
         self.commands
-            .xadd(
-                stream.clone(),
-                &[(String::from("block_height"), block_height)],
-            )
+            .xadd(stream.clone(), &fields)
             .await
             .context("Failed to add block to Redis Stream")
     }
@@ -225,7 +276,7 @@ mod test {
     async fn limits_block_stream_length() {
         let mut mock_redis_commands = RedisCommands::default();
         mock_redis_commands
-            .expect_xadd::<String, u64>()
+            .expect_xadd::<String, String>()
             .with(predicate::eq("stream".to_string()), predicate::always())
             .returning(|_, _| Ok(()))
             .once();
@@ -257,7 +308,99 @@ mod test {
         tokio::time::pause();
 
         redis
-            .publish_block(&indexer_config, "stream".to_string(), 0, 1)
+            .publish_block(&indexer_config, "stream".to_string(), 0, &[], 1)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn publishes_rule_matches_with_block() {
+        let matches = vec![IndexerRuleMatch {
+            chain_id: crate::rules::types::ChainId::Mainnet,
+            payload: crate::rules::types::IndexerRuleMatchPayload::Actions {
+                block_hash: "block_hash".to_string(),
+                receipt_id: "receipt_id".to_string(),
+                transaction_hash: None,
+                predecessor_id: "morgs.near".to_string(),
+                receiver_id: "queryapi.dataplatform.near".to_string(),
+            },
+            block_height: 100,
+        }];
+        let expected_fields = vec![
+            (String::from("block_height"), String::from("100")),
+            (
+                String::from("matches"),
+                serde_json::to_string(&matches).unwrap(),
+            ),
+        ];
+
+        let mut mock_redis_commands = RedisCommands::default();
+        mock_redis_commands
+            .expect_xadd::<String, String>()
+            .withf(move |stream, fields| stream == "stream" && fields == expected_fields)
+            .returning(|_, _| Ok(()))
+            .once();
+        mock_redis_commands
+            .expect_xlen::<String>()
+            .returning(|_| Ok(None));
+
+        let redis = RedisClientImpl {
+            commands: mock_redis_commands,
+        };
+
+        let indexer_config = crate::indexer_config::IndexerConfig {
+            account_id: near_indexer_primitives::types::AccountId::try_from(
+                "morgs.near".to_string(),
+            )
+            .unwrap(),
+            function_name: "test".to_string(),
+            rule: registry_types::Rule::ActionAny {
+                affected_account_id: "queryapi.dataplatform.near".to_string(),
+                status: registry_types::Status::Success,
+            },
+        };
+
+        redis
+            .publish_block(&indexer_config, "stream".to_string(), 100, &matches, 1)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn marks_matches_of_block_as_unknown() {
+        let expected_fields = vec![
+            (String::from("block_height"), String::from("100")),
+            (String::from("matches_unknown"), String::from("true")),
+        ];
+
+        let mut mock_redis_commands = RedisCommands::default();
+        mock_redis_commands
+            .expect_xadd::<String, String>()
+            .withf(move |stream, fields| stream == "stream" && fields == expected_fields)
+            .returning(|_, _| Ok(()))
+            .once();
+        mock_redis_commands
+            .expect_xlen::<String>()
+            .returning(|_| Ok(None));
+
+        let redis = RedisClientImpl {
+            commands: mock_redis_commands,
+        };
+
+        let indexer_config = crate::indexer_config::IndexerConfig {
+            account_id: near_indexer_primitives::types::AccountId::try_from(
+                "morgs.near".to_string(),
+            )
+            .unwrap(),
+            function_name: "test".to_string(),
+            rule: registry_types::Rule::ActionAny {
+                affected_account_id: "queryapi.dataplatform.near".to_string(),
+                status: registry_types::Status::Success,
+            },
+        };
+
+        redis
+            .publish_block_with_unknown_matches(&indexer_config, "stream".to_string(), 100, 1)
             .await
             .unwrap();
     }
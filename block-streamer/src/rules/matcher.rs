@@ -1,8 +1,11 @@
 use near_lake_framework::near_indexer_primitives::{
-    views::{ActionView, ExecutionStatusView, ReceiptEnumView},
-    IndexerExecutionOutcomeWithReceipt,
+    views::{
+        AccessKeyPermissionView, ActionView, ExecutionStatusView, ReceiptEnumView,
+        StateChangeValueView, StateChangeWithCauseView,
+    },
+    IndexerExecutionOutcomeWithReceipt, IndexerTransactionWithOutcome,
 };
-use registry_types::{Rule, Status};
+use registry_types::{ActionKind, ArgsCondition, ArgsOperator, Rule, StateChangeKind, Status};
 
 use crate::rules::types::Event;
 
@@ -25,6 +28,22 @@ pub fn matches(
             function,
             receipt_execution_outcome,
         ),
+        Rule::ActionFunctionCallArgs {
+            affected_account_id,
+            status,
+            function,
+            args,
+            min_deposit,
+            min_gas,
+        } => match_action_function_call_args(
+            affected_account_id,
+            status,
+            function,
+            args,
+            min_deposit.as_deref(),
+            *min_gas,
+            receipt_execution_outcome,
+        ),
         Rule::Event {
             contract_account_id,
             event,
@@ -37,6 +56,49 @@ pub fn matches(
             version,
             receipt_execution_outcome,
         ),
+        Rule::Any { rules } => rules
+            .iter()
+            .any(|rule| matches(rule, receipt_execution_outcome)),
+        Rule::All { rules } => rules
+            .iter()
+            .all(|rule| matches(rule, receipt_execution_outcome)),
+        Rule::Not { rule } => !matches(rule, receipt_execution_outcome),
+        Rule::ActionKind {
+            affected_account_id,
+            status,
+            action,
+        } => match_action_kind(
+            affected_account_id,
+            status,
+            action,
+            receipt_execution_outcome,
+        ),
+        // State changes and transactions are matched separately, see `matches_state_change` and
+        // `matches_transaction`
+        Rule::StateChange { .. } | Rule::TransactionSigner { .. } => false,
+    }
+}
+
+pub fn matches_transaction(
+    indexer_rule: &Rule,
+    transaction: &IndexerTransactionWithOutcome,
+) -> bool {
+    match indexer_rule {
+        Rule::TransactionSigner { signer_account_id } => match_account_pattern(
+            signer_account_id,
+            transaction.transaction.signer_id.as_str(),
+        ),
+        _ => false,
+    }
+}
+
+pub fn matches_state_change(indexer_rule: &Rule, state_change: &StateChangeWithCauseView) -> bool {
+    match indexer_rule {
+        Rule::StateChange {
+            affected_account_id,
+            change,
+        } => match_state_change(affected_account_id, change, &state_change.value),
+        _ => false,
     }
 }
 
@@ -82,6 +144,151 @@ fn match_action_function_call(
     false
 }
 
+#[allow(clippy::too_many_arguments)]
+fn match_action_function_call_args(
+    account_id: &str,
+    status: &Status,
+    function: &str,
+    args_conditions: &[ArgsCondition],
+    min_deposit: Option<&str>,
+    min_gas: Option<u64>,
+    outcome_with_receipt: &IndexerExecutionOutcomeWithReceipt,
+) -> bool {
+    if match_account(account_id, outcome_with_receipt) {
+        if let ReceiptEnumView::Action { actions, .. } = &outcome_with_receipt.receipt.receipt {
+            let is_any_matching_function_call = actions.iter().any(|action| {
+                if let ActionView::FunctionCall {
+                    method_name,
+                    args,
+                    gas,
+                    deposit,
+                } = action
+                {
+                    wildmatch::WildMatch::new(function).matches(method_name)
+                        && match_minimum_gas(*gas, min_gas)
+                        && match_minimum_balance(*deposit, min_deposit)
+                        && match_args(args_conditions, args)
+                } else {
+                    false
+                }
+            });
+            if is_any_matching_function_call {
+                return match_status(
+                    status,
+                    &outcome_with_receipt.execution_outcome.outcome.status,
+                );
+            } else {
+                return false;
+            }
+        }
+    }
+    false
+}
+
+fn match_minimum_gas(gas: u64, min_gas: Option<u64>) -> bool {
+    match min_gas {
+        Some(min_gas) => gas >= min_gas,
+        None => true,
+    }
+}
+
+fn match_minimum_balance(balance: u128, min_balance: Option<&str>) -> bool {
+    match min_balance.map(str::parse::<u128>) {
+        Some(Ok(min_balance)) => balance >= min_balance,
+        Some(Err(_)) => false,
+        None => true,
+    }
+}
+
+fn match_action_kind(
+    account_id: &str,
+    status: &Status,
+    action_kind: &ActionKind,
+    outcome_with_receipt: &IndexerExecutionOutcomeWithReceipt,
+) -> bool {
+    if match_account(account_id, outcome_with_receipt) {
+        if let ReceiptEnumView::Action { actions, .. } = &outcome_with_receipt.receipt.receipt {
+            if actions
+                .iter()
+                .any(|action| match_action(action_kind, action))
+            {
+                return match_status(
+                    status,
+                    &outcome_with_receipt.execution_outcome.outcome.status,
+                );
+            }
+        }
+    }
+    false
+}
+
+pub fn match_action(action_kind: &ActionKind, action: &ActionView) -> bool {
+    match (action_kind, action) {
+        (ActionKind::CreateAccount, ActionView::CreateAccount)
+        | (ActionKind::DeployContract, ActionView::DeployContract { .. })
+        | (ActionKind::FunctionCall, ActionView::FunctionCall { .. })
+        | (ActionKind::DeleteKey, ActionView::DeleteKey { .. })
+        | (ActionKind::DeleteAccount, ActionView::DeleteAccount { .. })
+        | (ActionKind::Delegate, ActionView::Delegate { .. }) => true,
+        (ActionKind::Transfer { min_deposit }, ActionView::Transfer { deposit }) => {
+            match_minimum_balance(*deposit, min_deposit.as_deref())
+        }
+        (ActionKind::Stake { min_stake }, ActionView::Stake { stake, .. }) => {
+            match_minimum_balance(*stake, min_stake.as_deref())
+        }
+        (ActionKind::AddKey { full_access }, ActionView::AddKey { access_key, .. }) => {
+            let is_full_access =
+                matches!(access_key.permission, AccessKeyPermissionView::FullAccess);
+
+            match full_access {
+                Some(full_access) => *full_access == is_full_access,
+                None => true,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn match_args(args_conditions: &[ArgsCondition], args: &[u8]) -> bool {
+    if args_conditions.is_empty() {
+        return true;
+    }
+
+    match serde_json::from_slice::<serde_json::Value>(args) {
+        Ok(args) => args_conditions
+            .iter()
+            .all(|condition| match_args_condition(condition, &args)),
+        Err(_) => false,
+    }
+}
+
+fn match_args_condition(condition: &ArgsCondition, args: &serde_json::Value) -> bool {
+    let value = match args.pointer(&condition.pointer) {
+        Some(value) => value,
+        None => return false,
+    };
+
+    let expected = serde_json::from_str::<serde_json::Value>(&condition.value)
+        .unwrap_or_else(|_| serde_json::Value::String(condition.value.clone()));
+
+    match condition.operator {
+        // Numbers are commonly encoded as strings within arguments, so also compare the raw value
+        ArgsOperator::Equals => {
+            *value == expected || value.as_str() == Some(condition.value.as_str())
+        }
+        ArgsOperator::Contains => match (value, &expected) {
+            (serde_json::Value::String(value), serde_json::Value::String(expected)) => {
+                value.contains(expected.as_str())
+            }
+            (serde_json::Value::Array(values), expected) => values.contains(expected),
+            (serde_json::Value::Object(values), serde_json::Value::String(key)) => {
+                values.contains_key(key)
+            }
+            _ => false,
+        },
+    }
+}
+
 fn match_event(
     account_id: &str,
     event: &str,
@@ -110,20 +317,74 @@ fn match_event(
     }
 }
 
+fn match_state_change(
+    account_id: &str,
+    change_kind: &StateChangeKind,
+    change: &StateChangeValueView,
+) -> bool {
+    let (changed_account_id, is_matching_change) = match (change_kind, change) {
+        (
+            StateChangeKind::AccountUpdate,
+            StateChangeValueView::AccountUpdate { account_id, .. },
+        )
+        | (
+            StateChangeKind::AccountDeletion,
+            StateChangeValueView::AccountDeletion { account_id },
+        )
+        | (
+            StateChangeKind::AccessKeyUpdate,
+            StateChangeValueView::AccessKeyUpdate { account_id, .. },
+        )
+        | (
+            StateChangeKind::AccessKeyDeletion,
+            StateChangeValueView::AccessKeyDeletion { account_id, .. },
+        )
+        | (
+            StateChangeKind::ContractCodeUpdate,
+            StateChangeValueView::ContractCodeUpdate { account_id, .. },
+        )
+        | (
+            StateChangeKind::ContractCodeDeletion,
+            StateChangeValueView::ContractCodeDeletion { account_id },
+        ) => (account_id, true),
+        (
+            StateChangeKind::DataUpdate { key_prefix },
+            StateChangeValueView::DataUpdate {
+                account_id, key, ..
+            },
+        )
+        | (
+            StateChangeKind::DataDeletion { key_prefix },
+            StateChangeValueView::DataDeletion { account_id, key },
+        ) => match key_prefix {
+            Some(key_prefix) => (account_id, key.starts_with(key_prefix.as_bytes())),
+            None => (account_id, true),
+        },
+        _ => return false,
+    };
+
+    is_matching_change && match_account_pattern(account_id, changed_account_id.as_str())
+}
+
 fn match_account(
     account_id: &str,
     outcome_with_receipt: &IndexerExecutionOutcomeWithReceipt,
 ) -> bool {
-    match account_id {
+    match_account_pattern(
+        account_id,
+        outcome_with_receipt.receipt.receiver_id.as_str(),
+    ) || match_account_pattern(
+        account_id,
+        outcome_with_receipt.receipt.predecessor_id.as_str(),
+    )
+}
+
+fn match_account_pattern(pattern: &str, account_id: &str) -> bool {
+    match pattern {
         x if x.contains(',') => x
             .split(',')
-            .any(|sub_account_id| match_account(sub_account_id.trim(), outcome_with_receipt)),
-        _ => {
-            wildmatch::WildMatch::new(account_id)
-                .matches(outcome_with_receipt.receipt.receiver_id.as_str())
-                || wildmatch::WildMatch::new(account_id)
-                    .matches(outcome_with_receipt.receipt.predecessor_id.as_str())
-        }
+            .any(|sub_pattern| match_account_pattern(sub_pattern.trim(), account_id)),
+        _ => wildmatch::WildMatch::new(pattern).matches(account_id),
     }
 }
 
@@ -1,20 +1,64 @@
 pub mod matcher;
 pub mod outcomes_reducer;
+pub mod state_changes_reducer;
+pub mod transactions_reducer;
 pub mod types;
 
 use near_lake_framework::near_indexer_primitives::StreamerMessage;
 use registry_types::Rule;
 
+use outcomes_reducer::TransactionHashes;
 use types::{ChainId, IndexerRuleMatch};
 
+/// Reduces matches from a single block, transaction hashes are only resolved for receipts
+/// produced by transactions within the same block
 pub fn reduce_indexer_rule_matches(
     indexer_rule: &Rule,
     streamer_message: &StreamerMessage,
     chain_id: ChainId,
 ) -> Vec<IndexerRuleMatch> {
+    reduce_consecutive_indexer_rule_matches(
+        indexer_rule,
+        streamer_message,
+        &mut TransactionHashes::default(),
+        chain_id,
+    )
+}
+
+/// Reduces matches from a block which directly follows those previously reduced with the same
+/// `transaction_hashes`, so that receipts produced by transactions in earlier blocks are resolved
+pub fn reduce_consecutive_indexer_rule_matches(
+    indexer_rule: &Rule,
+    streamer_message: &StreamerMessage,
+    transaction_hashes: &mut TransactionHashes,
+    chain_id: ChainId,
+) -> Vec<IndexerRuleMatch> {
+    transaction_hashes.record(streamer_message);
+
     match &indexer_rule {
-        Rule::ActionAny { .. } | Rule::ActionFunctionCall { .. } | Rule::Event { .. } => {
-            outcomes_reducer::reduce_indexer_rule_matches_from_outcomes(
+        Rule::ActionAny { .. }
+        | Rule::ActionFunctionCall { .. }
+        | Rule::ActionFunctionCallArgs { .. }
+        | Rule::Event { .. }
+        | Rule::Any { .. }
+        | Rule::All { .. }
+        | Rule::Not { .. }
+        | Rule::ActionKind { .. } => outcomes_reducer::reduce_indexer_rule_matches_from_outcomes(
+            indexer_rule,
+            streamer_message,
+            transaction_hashes,
+            chain_id,
+        ),
+        Rule::StateChange { .. } => {
+            state_changes_reducer::reduce_indexer_rule_matches_from_state_changes(
+                indexer_rule,
+                streamer_message,
+                transaction_hashes,
+                chain_id,
+            )
+        }
+        Rule::TransactionSigner { .. } => {
+            transactions_reducer::reduce_indexer_rule_matches_from_transactions(
                 indexer_rule,
                 streamer_message,
                 chain_id,
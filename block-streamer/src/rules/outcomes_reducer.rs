@@ -1,14 +1,20 @@
+use std::collections::{HashMap, VecDeque};
+
 use crate::rules::matcher;
 use crate::rules::types::Event;
-use crate::rules::types::{ChainId, IndexerRuleMatch, IndexerRuleMatchPayload};
+use crate::rules::types::{
+    ChainId, IndexerRuleMatch, IndexerRuleMatchPayload, TransactionHashString,
+};
 use crate::rules::Rule;
 use near_lake_framework::near_indexer_primitives::{
-    IndexerExecutionOutcomeWithReceipt, StreamerMessage,
+    types::BlockHeight, views::ReceiptEnumView, CryptoHash, IndexerExecutionOutcomeWithReceipt,
+    StreamerMessage,
 };
 
 pub fn reduce_indexer_rule_matches_from_outcomes(
     indexer_rule: &Rule,
     streamer_message: &StreamerMessage,
+    transaction_hashes: &TransactionHashes,
     chain_id: ChainId,
 ) -> Vec<IndexerRuleMatch> {
     streamer_message
@@ -18,8 +24,7 @@ pub fn reduce_indexer_rule_matches_from_outcomes(
             shard
                 .receipt_execution_outcomes
                 .iter()
-                // future: when extracting Actions, Events, etc this will be a filter operation
-                .find(|receipt_execution_outcome| {
+                .filter(|receipt_execution_outcome| {
                     matcher::matches(indexer_rule, receipt_execution_outcome)
                 })
         })
@@ -29,17 +34,138 @@ pub fn reduce_indexer_rule_matches_from_outcomes(
                 receipt_execution_outcome,
                 streamer_message.block.header.hash.to_string(),
                 streamer_message.block.header.height,
+                transaction_hashes.get(&receipt_execution_outcome.receipt.receipt_id),
                 chain_id.clone(),
             )
         })
         .collect()
 }
 
+/// Number of blocks for which the transaction hash of a receipt is retained. Receipts are almost
+/// always executed within a few blocks of the transaction or receipt which produced them.
+const TRANSACTION_HASH_RETENTION_BLOCKS: BlockHeight = 100;
+
+/// Maximum number of receipts retained. Each Block Stream holds its own `TransactionHashes`, so
+/// this bounds the memory used per stream during periods of high traffic, at the cost of leaving
+/// receipts from the oldest blocks unresolved.
+const MAX_TRANSACTION_HASHES: usize = 20_000;
+
+/// Maps receipt ids to the hash of the transaction which produced them.
+///
+/// When blocks are recorded in order, receipts produced by transactions in earlier blocks are
+/// resolved too. Receipts are only unresolved if their transaction was included before the first
+/// recorded block, e.g. for blocks fetched individually, more than
+/// `TRANSACTION_HASH_RETENTION_BLOCKS` earlier, or in a block evicted to stay within the limit.
+#[derive(Debug)]
+pub struct TransactionHashes {
+    hashes: HashMap<CryptoHash, (TransactionHashString, BlockHeight)>,
+    /// Receipts recorded per block, oldest first, used to evict whole blocks at a time
+    recorded_blocks: VecDeque<(BlockHeight, Vec<CryptoHash>)>,
+    limit: usize,
+}
+
+impl Default for TransactionHashes {
+    fn default() -> Self {
+        Self::with_limit(MAX_TRANSACTION_HASHES)
+    }
+}
+
+impl TransactionHashes {
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            hashes: HashMap::new(),
+            recorded_blocks: VecDeque::new(),
+            limit,
+        }
+    }
+
+    pub fn from_block(streamer_message: &StreamerMessage) -> Self {
+        let mut transaction_hashes = Self::default();
+        transaction_hashes.record(streamer_message);
+        transaction_hashes
+    }
+
+    /// Records the receipts produced within the block, and evicts those which have expired or
+    /// exceed the limit
+    pub fn record(&mut self, streamer_message: &StreamerMessage) {
+        let block_height = streamer_message.block.header.height;
+        let mut receipt_ids = Vec::new();
+
+        for shard in &streamer_message.shards {
+            if let Some(chunk) = &shard.chunk {
+                for transaction in &chunk.transactions {
+                    for receipt_id in &transaction.outcome.execution_outcome.outcome.receipt_ids {
+                        self.hashes.insert(
+                            *receipt_id,
+                            (transaction.transaction.hash.to_string(), block_height),
+                        );
+                        receipt_ids.push(*receipt_id);
+                    }
+                }
+            }
+        }
+
+        // Receipts produced by receipts executed in this block belong to the same transaction
+        for shard in &streamer_message.shards {
+            for receipt_execution_outcome in &shard.receipt_execution_outcomes {
+                if let Some(transaction_hash) =
+                    self.get(&receipt_execution_outcome.receipt.receipt_id)
+                {
+                    for receipt_id in &receipt_execution_outcome
+                        .execution_outcome
+                        .outcome
+                        .receipt_ids
+                    {
+                        self.hashes
+                            .insert(*receipt_id, (transaction_hash.clone(), block_height));
+                        receipt_ids.push(*receipt_id);
+                    }
+                }
+            }
+        }
+
+        self.recorded_blocks.push_back((block_height, receipt_ids));
+
+        while let Some((recorded_at, _)) = self.recorded_blocks.front() {
+            let expired = *recorded_at + TRANSACTION_HASH_RETENTION_BLOCKS < block_height;
+
+            if !expired && self.hashes.len() <= self.limit {
+                break;
+            }
+
+            if let Some((recorded_at, receipt_ids)) = self.recorded_blocks.pop_front() {
+                for receipt_id in receipt_ids {
+                    // Only remove the receipt if it wasn't recorded again by a later block
+                    if matches!(self.hashes.get(&receipt_id), Some((_, height)) if *height == recorded_at)
+                    {
+                        self.hashes.remove(&receipt_id);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, receipt_id: &CryptoHash) -> Option<TransactionHashString> {
+        self.hashes
+            .get(receipt_id)
+            .map(|(transaction_hash, _)| transaction_hash.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
+
 fn build_indexer_rule_match(
     indexer_rule: &Rule,
     receipt_execution_outcome: &IndexerExecutionOutcomeWithReceipt,
     block_header_hash: String,
     block_height: u64,
+    transaction_hash: Option<TransactionHashString>,
     chain_id: ChainId,
 ) -> IndexerRuleMatch {
     IndexerRuleMatch {
@@ -48,6 +174,7 @@ fn build_indexer_rule_match(
             indexer_rule,
             receipt_execution_outcome,
             block_header_hash,
+            transaction_hash,
         ),
         block_height,
     }
@@ -57,17 +184,41 @@ fn build_indexer_rule_match_payload(
     indexer_rule: &Rule,
     receipt_execution_outcome: &IndexerExecutionOutcomeWithReceipt,
     block_header_hash: String,
+    transaction_hash: Option<TransactionHashString>,
 ) -> IndexerRuleMatchPayload {
-    // future enhancement will extract and enrich fields from block & context as
-    //   specified in the indexer function config.
-    let transaction_hash = None;
+    let receipt = &receipt_execution_outcome.receipt;
 
     match &indexer_rule {
-        Rule::ActionAny { .. } | Rule::ActionFunctionCall { .. } => {
-            IndexerRuleMatchPayload::Actions {
+        Rule::ActionAny { .. }
+        | Rule::ActionFunctionCall { .. }
+        | Rule::ActionFunctionCallArgs { .. }
+        | Rule::Not { .. }
+        | Rule::StateChange { .. }
+        | Rule::TransactionSigner { .. } => IndexerRuleMatchPayload::Actions {
+            block_hash: block_header_hash,
+            receipt_id: receipt.receipt_id.to_string(),
+            transaction_hash,
+            predecessor_id: receipt.predecessor_id.to_string(),
+            receiver_id: receipt.receiver_id.to_string(),
+        },
+        Rule::ActionKind { action, .. } => {
+            let action_indices = match &receipt.receipt {
+                ReceiptEnumView::Action { actions, .. } => actions
+                    .iter()
+                    .zip(0..)
+                    .filter(|(action_view, _)| matcher::match_action(action, action_view))
+                    .map(|(_, index)| index)
+                    .collect(),
+                _ => vec![],
+            };
+
+            IndexerRuleMatchPayload::ActionKinds {
                 block_hash: block_header_hash,
-                receipt_id: receipt_execution_outcome.receipt.receipt_id.to_string(),
+                receipt_id: receipt.receipt_id.to_string(),
                 transaction_hash,
+                predecessor_id: receipt.predecessor_id.to_string(),
+                receiver_id: receipt.receiver_id.to_string(),
+                action_indices,
             }
         }
         Rule::Event {
@@ -100,23 +251,49 @@ fn build_indexer_rule_match_payload(
 
             IndexerRuleMatchPayload::Events {
                 block_hash: block_header_hash,
-                receipt_id: receipt_execution_outcome.receipt.receipt_id.to_string(),
+                receipt_id: receipt.receipt_id.to_string(),
                 transaction_hash,
+                predecessor_id: receipt.predecessor_id.to_string(),
+                receiver_id: receipt.receiver_id.to_string(),
                 event: event.event.clone(),
                 standard: event.standard.clone(),
                 version: event.version.clone(),
                 data: event.data.as_ref().map(|data| data.to_string()),
             }
         }
+        // Build the payload from the first nested rule which contributed to the match
+        Rule::Any { rules } | Rule::All { rules } => rules
+            .iter()
+            .find(|rule| {
+                !matches!(rule, Rule::Not { .. })
+                    && matcher::matches(rule, receipt_execution_outcome)
+            })
+            .map(|rule| {
+                build_indexer_rule_match_payload(
+                    rule,
+                    receipt_execution_outcome,
+                    block_header_hash.clone(),
+                    transaction_hash.clone(),
+                )
+            })
+            .unwrap_or_else(|| IndexerRuleMatchPayload::Actions {
+                block_hash: block_header_hash,
+                receipt_id: receipt.receipt_id.to_string(),
+                transaction_hash,
+                predecessor_id: receipt.predecessor_id.to_string(),
+                receiver_id: receipt.receiver_id.to_string(),
+            }),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use registry_types::{Rule, Status};
+    use registry_types::{ActionKind, ArgsCondition, ArgsOperator, Rule, Status};
 
-    use crate::rules::outcomes_reducer::reduce_indexer_rule_matches_from_outcomes;
-    use crate::rules::types::{ChainId, IndexerRuleMatch};
+    use crate::rules::outcomes_reducer::{
+        reduce_indexer_rule_matches_from_outcomes, TransactionHashes,
+    };
+    use crate::rules::types::{ChainId, IndexerRuleMatch, IndexerRuleMatchPayload};
 
     #[tokio::test]
     async fn match_wildcard_no_match() {
@@ -129,6 +306,7 @@ mod tests {
         let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
             &wildcard_rule,
             &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
             ChainId::Testnet,
         );
 
@@ -146,10 +324,11 @@ mod tests {
         let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
             &wildcard_rule,
             &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
             ChainId::Testnet,
         );
 
-        assert_eq!(result.len(), 1); // There are two matches, until we add Extraction we are just matching the first one (block matching)
+        assert_eq!(result.len(), 2);
     }
 
     #[tokio::test]
@@ -163,10 +342,11 @@ mod tests {
         let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
             &wildcard_rule,
             &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
             ChainId::Testnet,
         );
 
-        assert_eq!(result.len(), 1); // see Extraction note in previous test
+        assert_eq!(result.len(), 2);
 
         let wildcard_rule = Rule::ActionAny {
             affected_account_id: "app.nea*owd.near".to_string(),
@@ -176,10 +356,129 @@ mod tests {
         let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
             &wildcard_rule,
             &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn match_all_receipts_with_details() {
+        let rule = Rule::ActionAny {
+            affected_account_id: "app.nearcrowd.near".to_string(),
+            status: Status::Success,
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
             ChainId::Testnet,
         );
 
-        assert_eq!(result.len(), 1); // see Extraction note in previous test
+        assert_eq!(result.len(), 2);
+        assert!(matches!(
+            &result[0].payload,
+            IndexerRuleMatchPayload::Actions {
+                receipt_id,
+                transaction_hash: Some(transaction_hash),
+                predecessor_id,
+                receiver_id,
+                ..
+            } if receipt_id == "DwVQmRqaeHiMwRESakJhp2jdYbq5ATLwa5BdKQtaek8n"
+                && transaction_hash == "HwDUCUs3zts1s13UJaSH8Qbzj8fyvgURAYkaPH2KKq2o"
+                && predecessor_id == "app.nearcrowd.near"
+                && receiver_id == "app.nearcrowd.near"
+        ));
+        // Originates from a transaction in an earlier block
+        assert!(matches!(
+            &result[1].payload,
+            IndexerRuleMatchPayload::Actions {
+                receipt_id,
+                transaction_hash: None,
+                predecessor_id,
+                ..
+            } if receipt_id == "AjPq8fHZqoAHQ5x6gMvYh86mrtM6DiybpwQf2LNrbRfD"
+                && predecessor_id == "gareva.near"
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolves_transaction_hashes_from_previous_blocks() {
+        let rule = Rule::ActionAny {
+            affected_account_id: "priceoracle.near".to_string(),
+            status: Status::Success,
+        };
+
+        let previous_streamer_message = crate::test_utils::get_streamer_message(107503704);
+        let streamer_message = crate::test_utils::get_streamer_message(107503705);
+
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Mainnet,
+        );
+
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            &result[0].payload,
+            IndexerRuleMatchPayload::Actions {
+                transaction_hash: None,
+                ..
+            }
+        ));
+
+        let mut transaction_hashes = TransactionHashes::default();
+        transaction_hashes.record(&previous_streamer_message);
+        transaction_hashes.record(&streamer_message);
+
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &rule,
+            &streamer_message,
+            &transaction_hashes,
+            ChainId::Mainnet,
+        );
+
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            &result[0].payload,
+            IndexerRuleMatchPayload::Actions {
+                receipt_id,
+                transaction_hash: Some(transaction_hash),
+                ..
+            } if receipt_id == "5bQgy1VSQF2qpyxdyejHgypVbvCsskHA2gNwwk6fWhFN"
+                && transaction_hash == "4BsQfHbUWd78m6KtcSXEpqrKGpeM2R3t5gUyXXsK53Px"
+        ));
+    }
+
+    #[tokio::test]
+    async fn evicts_oldest_blocks_over_the_limit() {
+        let previous_streamer_message = crate::test_utils::get_streamer_message(107503704);
+        let streamer_message = crate::test_utils::get_streamer_message(107503705);
+
+        let latest_block_size = TransactionHashes::from_block(&streamer_message).len();
+        assert!(latest_block_size > 0);
+
+        let mut transaction_hashes = TransactionHashes::default();
+        transaction_hashes.record(&previous_streamer_message);
+        transaction_hashes.record(&streamer_message);
+
+        assert!(transaction_hashes.len() > latest_block_size);
+
+        let mut transaction_hashes = TransactionHashes::with_limit(latest_block_size);
+        transaction_hashes.record(&previous_streamer_message);
+        transaction_hashes.record(&streamer_message);
+
+        assert!(transaction_hashes.len() <= latest_block_size);
+
+        let mut transaction_hashes = TransactionHashes::with_limit(0);
+        transaction_hashes.record(&previous_streamer_message);
+        transaction_hashes.record(&streamer_message);
+
+        assert!(transaction_hashes.is_empty());
     }
 
     #[tokio::test]
@@ -193,10 +492,11 @@ mod tests {
         let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
             &wildcard_rule,
             &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
             ChainId::Testnet,
         );
 
-        assert_eq!(result.len(), 1); // There are two matches, until we add Extraction we are just matching the first one (block matching)
+        assert_eq!(result.len(), 2);
     }
 
     #[tokio::test]
@@ -210,9 +510,241 @@ mod tests {
         let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
             &wildcard_rule,
             &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn match_function_call_args() {
+        let args_rule = Rule::ActionFunctionCallArgs {
+            affected_account_id: "token.sweat".to_string(),
+            status: Status::Success,
+            function: "ft_transfer".to_string(),
+            args: vec![
+                ArgsCondition {
+                    pointer: "/receiver_id".to_string(),
+                    operator: ArgsOperator::Equals,
+                    value: "reward-optin.sweat".to_string(),
+                },
+                ArgsCondition {
+                    pointer: "/memo".to_string(),
+                    operator: ArgsOperator::Contains,
+                    value: "rew:optin".to_string(),
+                },
+            ],
+            min_deposit: Some("1".to_string()),
+            min_gas: Some(14_000_000_000_000),
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &args_rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn match_function_call_args_no_match() {
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+
+        let mismatched_args_rule = Rule::ActionFunctionCallArgs {
+            affected_account_id: "token.sweat".to_string(),
+            status: Status::Success,
+            function: "ft_transfer".to_string(),
+            args: vec![ArgsCondition {
+                pointer: "/receiver_id".to_string(),
+                operator: ArgsOperator::Equals,
+                value: "morgs.near".to_string(),
+            }],
+            min_deposit: None,
+            min_gas: None,
+        };
+
+        let insufficient_deposit_rule = Rule::ActionFunctionCallArgs {
+            affected_account_id: "token.sweat".to_string(),
+            status: Status::Success,
+            function: "ft_transfer".to_string(),
+            args: vec![],
+            min_deposit: Some("2".to_string()),
+            min_gas: None,
+        };
+
+        for rule in [mismatched_args_rule, insufficient_deposit_rule] {
+            let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+                &rule,
+                &streamer_message,
+                &TransactionHashes::from_block(&streamer_message),
+                ChainId::Testnet,
+            );
+
+            assert_eq!(result.len(), 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn match_action_kind() {
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+
+        let transfer_rule = Rule::ActionKind {
+            affected_account_id: "sweat_welcome.near".to_string(),
+            status: Status::Success,
+            action: ActionKind::Transfer { min_deposit: None },
+        };
+
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &transfer_rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 8);
+
+        let large_transfer_rule = Rule::ActionKind {
+            affected_account_id: "sweat_welcome.near".to_string(),
+            status: Status::Success,
+            action: ActionKind::Transfer {
+                min_deposit: Some("10000000000000000000000".to_string()),
+            },
+        };
+
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &large_transfer_rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            &result[0].payload,
+            IndexerRuleMatchPayload::ActionKinds {
+                receipt_id,
+                action_indices,
+                ..
+            } if receipt_id == "BiijgyfTANtByLAU1MUCXAUmiUSaNRbJ4GotMyGg54bC"
+                && action_indices == &[0]
+        ));
+    }
+
+    #[tokio::test]
+    async fn match_action_kind_no_match() {
+        let add_full_access_key_rule = Rule::ActionKind {
+            affected_account_id: "*".to_string(),
+            status: Status::Any,
+            action: ActionKind::AddKey {
+                full_access: Some(true),
+            },
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &add_full_access_key_rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn match_any_composite_rule() {
+        let any_rule = Rule::Any {
+            rules: vec![
+                Rule::ActionFunctionCall {
+                    affected_account_id: "notintheblockaccount.near".to_string(),
+                    status: Status::Success,
+                    function: "*".to_string(),
+                },
+                Rule::Event {
+                    contract_account_id: "token.sweat".to_string(),
+                    standard: "nep141".to_string(),
+                    version: "*".to_string(),
+                    event: "ft_transfer".to_string(),
+                },
+            ],
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &any_rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 2);
+        assert!(matches!(
+            result[0].payload,
+            IndexerRuleMatchPayload::Events { ref event, .. } if event == "ft_transfer"
+        ));
+    }
+
+    #[tokio::test]
+    async fn match_all_composite_rule() {
+        let all_rule = Rule::All {
+            rules: vec![
+                Rule::ActionAny {
+                    affected_account_id: "app.nearcrowd.near".to_string(),
+                    status: Status::Success,
+                },
+                Rule::ActionFunctionCall {
+                    affected_account_id: "*".to_string(),
+                    status: Status::Any,
+                    function: "not_a_function".to_string(),
+                },
+            ],
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &all_rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn match_not_composite_rule() {
+        let all_rule = Rule::All {
+            rules: vec![
+                Rule::ActionAny {
+                    affected_account_id: "app.nearcrowd.near".to_string(),
+                    status: Status::Success,
+                },
+                Rule::Not {
+                    rule: Box::new(Rule::ActionFunctionCall {
+                        affected_account_id: "app.nearcrowd.near".to_string(),
+                        status: Status::Any,
+                        function: "approve_solution".to_string(),
+                    }),
+                },
+            ],
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_outcomes(
+            &all_rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
             ChainId::Testnet,
         );
 
-        assert_eq!(result.len(), 1); // There are two matches, until we add Extraction we are just matching the first one (block matching)
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            result[0].payload,
+            IndexerRuleMatchPayload::Actions { .. }
+        ));
     }
 }
@@ -0,0 +1,135 @@
+use crate::rules::matcher;
+use crate::rules::outcomes_reducer::TransactionHashes;
+use crate::rules::types::{ChainId, IndexerRuleMatch, IndexerRuleMatchPayload};
+use crate::rules::Rule;
+use near_lake_framework::near_indexer_primitives::{views::StateChangeCauseView, StreamerMessage};
+
+pub fn reduce_indexer_rule_matches_from_state_changes(
+    indexer_rule: &Rule,
+    streamer_message: &StreamerMessage,
+    transaction_hashes: &TransactionHashes,
+    chain_id: ChainId,
+) -> Vec<IndexerRuleMatch> {
+    streamer_message
+        .shards
+        .iter()
+        .flat_map(|shard| shard.state_changes.iter())
+        .filter(|state_change| matcher::matches_state_change(indexer_rule, state_change))
+        .map(|state_change| {
+            let (receipt_id, transaction_hash) = match &state_change.cause {
+                StateChangeCauseView::TransactionProcessing { tx_hash } => {
+                    (None, Some(tx_hash.to_string()))
+                }
+                StateChangeCauseView::ActionReceiptProcessingStarted { receipt_hash }
+                | StateChangeCauseView::ActionReceiptGasReward { receipt_hash }
+                | StateChangeCauseView::ReceiptProcessing { receipt_hash }
+                | StateChangeCauseView::PostponedReceipt { receipt_hash } => (
+                    Some(receipt_hash.to_string()),
+                    transaction_hashes.get(receipt_hash),
+                ),
+                _ => (None, None),
+            };
+
+            IndexerRuleMatch {
+                chain_id: chain_id.clone(),
+                payload: IndexerRuleMatchPayload::StateChanges {
+                    block_hash: streamer_message.block.header.hash.to_string(),
+                    receipt_id,
+                    transaction_hash,
+                },
+                block_height: streamer_message.block.header.height,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use registry_types::{Rule, StateChangeKind};
+
+    use crate::rules::outcomes_reducer::TransactionHashes;
+    use crate::rules::state_changes_reducer::reduce_indexer_rule_matches_from_state_changes;
+    use crate::rules::types::{ChainId, IndexerRuleMatch, IndexerRuleMatchPayload};
+
+    #[tokio::test]
+    async fn match_data_update_with_key_prefix() {
+        let rule = Rule::StateChange {
+            affected_account_id: "app.nearcrowd.near".to_string(),
+            change: StateChangeKind::DataUpdate {
+                key_prefix: Some("STATE".to_string()),
+            },
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_state_changes(
+            &rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            &result[0].payload,
+            IndexerRuleMatchPayload::StateChanges {
+                receipt_id: Some(receipt_id),
+                transaction_hash: Some(transaction_hash),
+                ..
+            } if receipt_id == "DwVQmRqaeHiMwRESakJhp2jdYbq5ATLwa5BdKQtaek8n"
+                && transaction_hash == "HwDUCUs3zts1s13UJaSH8Qbzj8fyvgURAYkaPH2KKq2o"
+        ));
+    }
+
+    #[tokio::test]
+    async fn match_state_change_kinds() {
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+
+        for (change, expected_matches) in [
+            (StateChangeKind::AccountUpdate, 5),
+            (StateChangeKind::AccessKeyUpdate, 1),
+            (StateChangeKind::AccessKeyDeletion, 0),
+            (StateChangeKind::DataUpdate { key_prefix: None }, 5),
+            (StateChangeKind::DataDeletion { key_prefix: None }, 1),
+        ] {
+            let rule = Rule::StateChange {
+                affected_account_id: "*.nearcrowd.near".to_string(),
+                change,
+            };
+
+            let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_state_changes(
+                &rule,
+                &streamer_message,
+                &TransactionHashes::from_block(&streamer_message),
+                ChainId::Testnet,
+            );
+
+            assert_eq!(result.len(), expected_matches);
+        }
+    }
+
+    #[tokio::test]
+    async fn match_account_update_from_transaction() {
+        let rule = Rule::StateChange {
+            affected_account_id: "beevaapaa.near".to_string(),
+            change: StateChangeKind::AccountUpdate,
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_state_changes(
+            &rule,
+            &streamer_message,
+            &TransactionHashes::from_block(&streamer_message),
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            &result[0].payload,
+            IndexerRuleMatchPayload::StateChanges {
+                receipt_id: None,
+                transaction_hash: Some(transaction_hash),
+                ..
+            } if transaction_hash == "3zuxLwgGW9eE3MzCMu5SdZRgJr57gFrgsdyPHTZSkSBJ"
+        ));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::rules::matcher;
+use crate::rules::types::{ChainId, IndexerRuleMatch, IndexerRuleMatchPayload};
+use crate::rules::Rule;
+use near_lake_framework::near_indexer_primitives::StreamerMessage;
+
+pub fn reduce_indexer_rule_matches_from_transactions(
+    indexer_rule: &Rule,
+    streamer_message: &StreamerMessage,
+    chain_id: ChainId,
+) -> Vec<IndexerRuleMatch> {
+    streamer_message
+        .shards
+        .iter()
+        .filter_map(|shard| shard.chunk.as_ref())
+        .flat_map(|chunk| chunk.transactions.iter())
+        .filter(|transaction| matcher::matches_transaction(indexer_rule, transaction))
+        .map(|transaction| IndexerRuleMatch {
+            chain_id: chain_id.clone(),
+            payload: IndexerRuleMatchPayload::Transactions {
+                block_hash: streamer_message.block.header.hash.to_string(),
+                transaction_hash: transaction.transaction.hash.to_string(),
+                signer_id: transaction.transaction.signer_id.to_string(),
+                receiver_id: transaction.transaction.receiver_id.to_string(),
+            },
+            block_height: streamer_message.block.header.height,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use registry_types::Rule;
+
+    use crate::rules::transactions_reducer::reduce_indexer_rule_matches_from_transactions;
+    use crate::rules::types::{ChainId, IndexerRuleMatch, IndexerRuleMatchPayload};
+
+    #[tokio::test]
+    async fn match_transaction_signer() {
+        let rule = Rule::TransactionSigner {
+            signer_account_id: "beevaapaa.near".to_string(),
+        };
+
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+        let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_transactions(
+            &rule,
+            &streamer_message,
+            ChainId::Testnet,
+        );
+
+        assert_eq!(result.len(), 1);
+        assert!(matches!(
+            &result[0].payload,
+            IndexerRuleMatchPayload::Transactions {
+                transaction_hash,
+                signer_id,
+                receiver_id,
+                ..
+            } if transaction_hash == "3zuxLwgGW9eE3MzCMu5SdZRgJr57gFrgsdyPHTZSkSBJ"
+                && signer_id == "beevaapaa.near"
+                && receiver_id == "app.nearcrowd.near"
+        ));
+    }
+
+    #[tokio::test]
+    async fn match_transaction_signer_patterns() {
+        let streamer_message = crate::test_utils::get_streamer_message(93085141);
+
+        for (signer_account_id, expected_matches) in [
+            ("*.sweat", 1),
+            ("app.nearcrowd.near, sweat_welcome.near", 2),
+            ("token.sweat", 0),
+        ] {
+            let rule = Rule::TransactionSigner {
+                signer_account_id: signer_account_id.to_string(),
+            };
+
+            let result: Vec<IndexerRuleMatch> = reduce_indexer_rule_matches_from_transactions(
+                &rule,
+                &streamer_message,
+                ChainId::Testnet,
+            );
+
+            assert_eq!(result.len(), expected_matches);
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::fmt;
 pub type TransactionHashString = String;
 pub type ReceiptIdString = String;
 pub type BlockHashString = String;
+pub type AccountIdString = String;
 
 #[derive(
     borsh::BorshSerialize,
@@ -18,6 +19,9 @@ pub struct IndexerRuleMatch {
     pub block_height: u64,
 }
 
+/// `transaction_hash` is `None` when the transaction which produced the receipt wasn't seen by
+/// the stream. Blocks matched during bitmap backfill are fetched and reduced individually, so
+/// their matches are only resolved for receipts produced by a transaction in the same block.
 #[derive(
     borsh::BorshSerialize,
     borsh::BorshDeserialize,
@@ -31,16 +35,35 @@ pub enum IndexerRuleMatchPayload {
         block_hash: BlockHashString,
         receipt_id: ReceiptIdString,
         transaction_hash: Option<TransactionHashString>,
+        predecessor_id: AccountIdString,
+        receiver_id: AccountIdString,
     },
     Events {
         block_hash: BlockHashString,
         receipt_id: ReceiptIdString,
         transaction_hash: Option<TransactionHashString>,
+        predecessor_id: AccountIdString,
+        receiver_id: AccountIdString,
         event: String,
         standard: String,
         version: String,
         data: Option<String>,
     },
+    ActionKinds {
+        block_hash: BlockHashString,
+        receipt_id: ReceiptIdString,
+        transaction_hash: Option<TransactionHashString>,
+        predecessor_id: AccountIdString,
+        receiver_id: AccountIdString,
+        /// Positions of the matching actions within the receipt
+        action_indices: Vec<u32>,
+    },
+    Transactions {
+        block_hash: BlockHashString,
+        transaction_hash: TransactionHashString,
+        signer_id: AccountIdString,
+        receiver_id: AccountIdString,
+    },
     StateChanges {
         block_hash: BlockHashString,
         receipt_id: Option<ReceiptIdString>,
@@ -55,11 +78,22 @@ pub enum IndexerRuleMatchPayload {
     serde::Deserialize,
     Clone,
     Debug,
+    PartialEq,
+    Eq,
 )]
 pub enum ChainId {
     Mainnet,
     Testnet,
 }
+impl ChainId {
+    pub fn get_lake_bucket(&self) -> String {
+        match self {
+            ChainId::Mainnet => "near-lake-data-mainnet".to_string(),
+            ChainId::Testnet => "near-lake-data-testnet".to_string(),
+        }
+    }
+}
+
 impl fmt::Display for ChainId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
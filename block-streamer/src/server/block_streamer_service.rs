@@ -18,7 +18,6 @@ pub struct BlockStreamerService {
     redis: std::sync::Arc<crate::redis::RedisClient>,
     receiver_blocks_processor: std::sync::Arc<ReceiverBlocksProcessor>,
     lake_s3_client: crate::lake_s3_client::SharedLakeS3Client,
-    chain_id: ChainId,
     block_streams: Mutex<HashMap<String, block_stream::BlockStream>>,
 }
 
@@ -56,7 +55,6 @@ impl BlockStreamerService {
             redis,
             receiver_blocks_processor,
             lake_s3_client,
-            chain_id: ChainId::Mainnet,
             block_streams: Mutex::new(HashMap::new()),
         }
     }
@@ -80,6 +78,220 @@ impl BlockStreamerService {
             ))),
         }
     }
+
+    fn match_action_any_rule(action_any: &ActionAnyRule) -> Result<registry_types::Rule, Status> {
+        Ok(registry_types::Rule::ActionAny {
+            affected_account_id: action_any.affected_account_id.clone(),
+            status: Self::match_status(action_any.status)?,
+        })
+    }
+
+    fn match_action_function_call_rule(
+        action_function_call: &ActionFunctionCallRule,
+    ) -> Result<registry_types::Rule, Status> {
+        Ok(registry_types::Rule::ActionFunctionCall {
+            affected_account_id: action_function_call.affected_account_id.clone(),
+            status: Self::match_status(action_function_call.status)?,
+            function: action_function_call.function_name.clone(),
+        })
+    }
+
+    fn match_args_operator(grpc_operator: i32) -> Result<registry_types::ArgsOperator, Status> {
+        match grpc_operator {
+            1 => Ok(registry_types::ArgsOperator::Equals),
+            2 => Ok(registry_types::ArgsOperator::Contains),
+            operator => Err(Status::invalid_argument(format!(
+                "Invalid ArgsOperator provided: {}",
+                operator
+            ))),
+        }
+    }
+
+    fn match_action_function_call_args_rule(
+        action_function_call_args: &ActionFunctionCallArgsRule,
+    ) -> Result<registry_types::Rule, Status> {
+        let args = action_function_call_args
+            .args
+            .iter()
+            .map(|condition| {
+                Ok(registry_types::ArgsCondition {
+                    pointer: condition.pointer.clone(),
+                    operator: Self::match_args_operator(condition.operator)?,
+                    value: condition.value.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        Ok(registry_types::Rule::ActionFunctionCallArgs {
+            affected_account_id: action_function_call_args.affected_account_id.clone(),
+            status: Self::match_status(action_function_call_args.status)?,
+            function: action_function_call_args.function_name.clone(),
+            args,
+            min_deposit: Self::match_minimum_balance(&action_function_call_args.min_deposit)?,
+            min_gas: action_function_call_args.min_gas,
+        })
+    }
+
+    fn match_minimum_balance(min_balance: &Option<String>) -> Result<Option<String>, Status> {
+        if let Some(min_balance) = min_balance {
+            min_balance.parse::<u128>().map_err(|_| {
+                Status::invalid_argument(format!(
+                    "Invalid minimum balance provided: {}",
+                    min_balance
+                ))
+            })?;
+        }
+
+        Ok(min_balance.clone())
+    }
+
+    fn match_action_kind_rule(
+        action_kind: &ActionKindRule,
+    ) -> Result<registry_types::Rule, Status> {
+        let action = match action_kind.action_kind {
+            1 => registry_types::ActionKind::CreateAccount,
+            2 => registry_types::ActionKind::DeployContract,
+            3 => registry_types::ActionKind::FunctionCall,
+            4 => registry_types::ActionKind::Transfer {
+                min_deposit: Self::match_minimum_balance(&action_kind.min_deposit)?,
+            },
+            5 => registry_types::ActionKind::Stake {
+                min_stake: Self::match_minimum_balance(&action_kind.min_stake)?,
+            },
+            6 => registry_types::ActionKind::AddKey {
+                full_access: action_kind.full_access,
+            },
+            7 => registry_types::ActionKind::DeleteKey,
+            8 => registry_types::ActionKind::DeleteAccount,
+            9 => registry_types::ActionKind::Delegate,
+            kind => {
+                return Err(Status::invalid_argument(format!(
+                    "Invalid ActionKind provided: {}",
+                    kind
+                )))
+            }
+        };
+
+        Ok(registry_types::Rule::ActionKind {
+            affected_account_id: action_kind.affected_account_id.clone(),
+            status: Self::match_status(action_kind.status)?,
+            action,
+        })
+    }
+
+    fn match_state_change_rule(
+        state_change: &StateChangeRule,
+    ) -> Result<registry_types::Rule, Status> {
+        let key_prefix = state_change.key_prefix.clone();
+
+        let change = match state_change.change_kind {
+            1 => registry_types::StateChangeKind::AccountUpdate,
+            2 => registry_types::StateChangeKind::AccountDeletion,
+            3 => registry_types::StateChangeKind::AccessKeyUpdate,
+            4 => registry_types::StateChangeKind::AccessKeyDeletion,
+            5 => registry_types::StateChangeKind::DataUpdate { key_prefix },
+            6 => registry_types::StateChangeKind::DataDeletion { key_prefix },
+            7 => registry_types::StateChangeKind::ContractCodeUpdate,
+            8 => registry_types::StateChangeKind::ContractCodeDeletion,
+            kind => {
+                return Err(Status::invalid_argument(format!(
+                    "Invalid StateChangeKind provided: {}",
+                    kind
+                )))
+            }
+        };
+
+        Ok(registry_types::Rule::StateChange {
+            affected_account_id: state_change.affected_account_id.clone(),
+            change,
+        })
+    }
+
+    fn match_event_rule(event: &EventRule) -> registry_types::Rule {
+        registry_types::Rule::Event {
+            contract_account_id: event.contract_account_id.clone(),
+            standard: event.standard.clone(),
+            version: event.version.clone(),
+            event: event.event.clone(),
+        }
+    }
+
+    fn match_any_rule(any: &AnyRule) -> Result<registry_types::Rule, Status> {
+        Ok(registry_types::Rule::Any {
+            rules: Self::match_sub_rules(&any.rules)?,
+        })
+    }
+
+    fn match_all_rule(all: &AllRule) -> Result<registry_types::Rule, Status> {
+        Ok(registry_types::Rule::All {
+            rules: Self::match_sub_rules(&all.rules)?,
+        })
+    }
+
+    fn match_not_rule(not: &NotRule) -> Result<registry_types::Rule, Status> {
+        let rule = not
+            .rule
+            .as_ref()
+            .ok_or(Status::invalid_argument("Not rule must contain a rule"))?;
+
+        Ok(registry_types::Rule::Not {
+            rule: Box::new(Self::match_sub_rule(rule)?),
+        })
+    }
+
+    fn match_sub_rules(sub_rules: &[SubRule]) -> Result<Vec<registry_types::Rule>, Status> {
+        if sub_rules.is_empty() {
+            return Err(Status::invalid_argument(
+                "Composite rules must contain at least one rule",
+            ));
+        }
+
+        sub_rules.iter().map(Self::match_sub_rule).collect()
+    }
+
+    fn match_sub_rule(sub_rule: &SubRule) -> Result<registry_types::Rule, Status> {
+        let rule = sub_rule
+            .rule
+            .as_ref()
+            .ok_or(Status::invalid_argument("Nested rule must be provided"))?;
+
+        match rule {
+            sub_rule::Rule::ActionAnyRule(action_any) => Self::match_action_any_rule(action_any),
+            sub_rule::Rule::ActionFunctionCallRule(action_function_call) => {
+                Self::match_action_function_call_rule(action_function_call)
+            }
+            sub_rule::Rule::ActionFunctionCallArgsRule(action_function_call_args) => {
+                Self::match_action_function_call_args_rule(action_function_call_args)
+            }
+            sub_rule::Rule::ActionKindRule(action_kind) => {
+                Self::match_action_kind_rule(action_kind)
+            }
+            sub_rule::Rule::EventRule(event) => Ok(Self::match_event_rule(event)),
+            sub_rule::Rule::AnyRule(any) => Self::match_any_rule(any),
+            sub_rule::Rule::AllRule(all) => Self::match_all_rule(all),
+            sub_rule::Rule::NotRule(not) => Self::match_not_rule(not),
+        }
+    }
+
+    fn match_chain_id(grpc_chain_id: i32) -> Result<ChainId, Status> {
+        match grpc_chain_id {
+            0 | 1 => Ok(ChainId::Mainnet),
+            2 => Ok(ChainId::Testnet),
+            chain_id => Err(Status::invalid_argument(format!(
+                "Invalid ChainId provided: {}",
+                chain_id
+            ))),
+        }
+    }
+}
+
+impl From<&ChainId> for blockstreamer::ChainId {
+    fn from(chain_id: &ChainId) -> Self {
+        match chain_id {
+            ChainId::Mainnet => blockstreamer::ChainId::Mainnet,
+            ChainId::Testnet => blockstreamer::ChainId::Testnet,
+        }
+    }
 }
 
 #[tonic::async_trait]
@@ -113,6 +325,7 @@ impl blockstreamer::block_streamer_server::BlockStreamer for BlockStreamerServic
                 function_name: stream.indexer_config.function_name.to_string(),
                 version: stream.version,
                 health: Some(stream_health.into()),
+                chain_id: blockstreamer::ChainId::from(&stream.chain_id).into(),
             }))
         } else {
             Err(Status::not_found(format!(
@@ -135,20 +348,33 @@ impl blockstreamer::block_streamer_server::BlockStreamer for BlockStreamerServic
 
         let rule = match rule {
             start_stream_request::Rule::ActionAnyRule(action_any) => {
-                registry_types::Rule::ActionAny {
-                    affected_account_id: action_any.affected_account_id,
-                    status: Self::match_status(action_any.status)?,
-                }
+                Self::match_action_any_rule(&action_any)?
             }
             start_stream_request::Rule::ActionFunctionCallRule(action_function_call) => {
-                registry_types::Rule::ActionFunctionCall {
-                    affected_account_id: action_function_call.affected_account_id,
-                    status: Self::match_status(action_function_call.status)?,
-                    function: action_function_call.function_name,
+                Self::match_action_function_call_rule(&action_function_call)?
+            }
+            start_stream_request::Rule::ActionFunctionCallArgsRule(action_function_call_args) => {
+                Self::match_action_function_call_args_rule(&action_function_call_args)?
+            }
+            start_stream_request::Rule::ActionKindRule(action_kind) => {
+                Self::match_action_kind_rule(&action_kind)?
+            }
+            start_stream_request::Rule::StateChangeRule(state_change) => {
+                Self::match_state_change_rule(&state_change)?
+            }
+            start_stream_request::Rule::TransactionSignerRule(transaction_signer) => {
+                registry_types::Rule::TransactionSigner {
+                    signer_account_id: transaction_signer.signer_account_id,
                 }
             }
+            start_stream_request::Rule::EventRule(event) => Self::match_event_rule(&event),
+            start_stream_request::Rule::AnyRule(any) => Self::match_any_rule(&any)?,
+            start_stream_request::Rule::AllRule(all) => Self::match_all_rule(&all)?,
+            start_stream_request::Rule::NotRule(not) => Self::match_not_rule(&not)?,
         };
 
+        let chain_id = Self::match_chain_id(request.chain_id)?;
+
         let account_id = near_indexer_primitives::types::AccountId::try_from(request.account_id)
             .map_err(|err| {
                 Status::invalid_argument(format!(
@@ -170,7 +396,7 @@ impl blockstreamer::block_streamer_server::BlockStreamer for BlockStreamerServic
 
         let mut block_stream = block_stream::BlockStream::new(
             indexer_config.clone(),
-            self.chain_id.clone(),
+            chain_id,
             request.version,
             request.redis_stream,
         );
@@ -256,6 +482,7 @@ impl blockstreamer::block_streamer_server::BlockStreamer for BlockStreamerServic
                     function_name: block_stream.indexer_config.function_name.to_string(),
                     version: block_stream.version,
                     health: stream_health.map(|health| health.into()),
+                    chain_id: blockstreamer::ChainId::from(&block_stream.chain_id).into(),
                 }
             })
             .collect();
@@ -281,7 +508,7 @@ mod tests {
         mock_s3_client
             .expect_get_text_file()
             .with(
-                predicate::eq("near-lake-data-mainnet".to_string()),
+                predicate::in_iter(["near-lake-data-mainnet", "near-lake-data-testnet"]),
                 predicate::always(),
             )
             .returning(move |_, _| {
@@ -328,6 +555,7 @@ mod tests {
                 function_name: "test".to_string(),
                 version: 0,
                 redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
                 rule: Some(start_stream_request::Rule::ActionAnyRule(ActionAnyRule {
                     affected_account_id: "queryapi.dataplatform.near".to_string(),
                     status: 1,
@@ -350,6 +578,60 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn starts_a_testnet_block_stream() {
+        let block_streamer_service = create_block_streamer_service();
+
+        block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.testnet".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Testnet.into(),
+                rule: Some(start_stream_request::Rule::ActionAnyRule(ActionAnyRule {
+                    affected_account_id: "queryapi.dataplatform.testnet".to_string(),
+                    status: 1,
+                })),
+            }))
+            .await
+            .unwrap();
+
+        let stream = block_streamer_service
+            .get_stream(Request::new(GetStreamRequest {
+                account_id: "morgs.testnet".to_string(),
+                function_name: "test".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(stream.chain_id(), blockstreamer::ChainId::Testnet);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_chain_id() {
+        let block_streamer_service = create_block_streamer_service();
+
+        let response = block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: 100,
+                rule: Some(start_stream_request::Rule::ActionAnyRule(ActionAnyRule {
+                    affected_account_id: "queryapi.dataplatform.near".to_string(),
+                    status: 1,
+                })),
+            }))
+            .await;
+
+        assert_eq!(response.err().unwrap().code(), tonic::Code::InvalidArgument);
+    }
+
     #[tokio::test]
     async fn get_non_existant_block_stream() {
         let block_streamer_service = create_block_streamer_service();
@@ -385,6 +667,7 @@ mod tests {
                 function_name: "test".to_string(),
                 version: 0,
                 redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
                 rule: Some(start_stream_request::Rule::ActionAnyRule(ActionAnyRule {
                     affected_account_id: "queryapi.dataplatform.near".to_string(),
                     status: 1,
@@ -397,6 +680,315 @@ mod tests {
         assert_eq!(lock.len(), 1);
     }
 
+    #[tokio::test]
+    async fn starts_a_block_stream_with_event_rule() {
+        let block_streamer_service = create_block_streamer_service();
+
+        block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::EventRule(EventRule {
+                    contract_account_id: "nft.near".to_string(),
+                    standard: "nep171".to_string(),
+                    version: "*".to_string(),
+                    event: "nft_mint".to_string(),
+                })),
+            }))
+            .await
+            .unwrap();
+
+        let lock = block_streamer_service.get_block_streams_lock().unwrap();
+        let block_stream = lock.get("16210176318434468568").unwrap();
+
+        assert_eq!(
+            block_stream.indexer_config.rule,
+            registry_types::Rule::Event {
+                contract_account_id: "nft.near".to_string(),
+                standard: "nep171".to_string(),
+                version: "*".to_string(),
+                event: "nft_mint".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn starts_a_block_stream_with_function_call_args_rule() {
+        let block_streamer_service = create_block_streamer_service();
+
+        block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::ActionFunctionCallArgsRule(
+                    ActionFunctionCallArgsRule {
+                        affected_account_id: "token.sweat".to_string(),
+                        function_name: "ft_transfer".to_string(),
+                        status: 1,
+                        args: vec![ArgsCondition {
+                            pointer: "/receiver_id".to_string(),
+                            operator: ArgsOperator::Equals.into(),
+                            value: "morgs.near".to_string(),
+                        }],
+                        min_deposit: Some("1".to_string()),
+                        min_gas: None,
+                    },
+                )),
+            }))
+            .await
+            .unwrap();
+
+        let lock = block_streamer_service.get_block_streams_lock().unwrap();
+        let block_stream = lock.get("16210176318434468568").unwrap();
+
+        assert_eq!(
+            block_stream.indexer_config.rule,
+            registry_types::Rule::ActionFunctionCallArgs {
+                affected_account_id: "token.sweat".to_string(),
+                status: registry_types::Status::Success,
+                function: "ft_transfer".to_string(),
+                args: vec![registry_types::ArgsCondition {
+                    pointer: "/receiver_id".to_string(),
+                    operator: registry_types::ArgsOperator::Equals,
+                    value: "morgs.near".to_string(),
+                }],
+                min_deposit: Some("1".to_string()),
+                min_gas: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_args_operator() {
+        let block_streamer_service = create_block_streamer_service();
+
+        let response = block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::ActionFunctionCallArgsRule(
+                    ActionFunctionCallArgsRule {
+                        affected_account_id: "token.sweat".to_string(),
+                        function_name: "ft_transfer".to_string(),
+                        status: 1,
+                        args: vec![ArgsCondition {
+                            pointer: "/receiver_id".to_string(),
+                            operator: ArgsOperator::Unspecified.into(),
+                            value: "morgs.near".to_string(),
+                        }],
+                        min_deposit: None,
+                        min_gas: None,
+                    },
+                )),
+            }))
+            .await;
+
+        assert_eq!(response.err().unwrap().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn starts_a_block_stream_with_action_kind_rule() {
+        let block_streamer_service = create_block_streamer_service();
+
+        block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::ActionKindRule(ActionKindRule {
+                    affected_account_id: "*.near".to_string(),
+                    status: 1,
+                    action_kind: ActionKind::Transfer.into(),
+                    min_deposit: Some("1000".to_string()),
+                    min_stake: None,
+                    full_access: None,
+                })),
+            }))
+            .await
+            .unwrap();
+
+        let lock = block_streamer_service.get_block_streams_lock().unwrap();
+        let block_stream = lock.get("16210176318434468568").unwrap();
+
+        assert_eq!(
+            block_stream.indexer_config.rule,
+            registry_types::Rule::ActionKind {
+                affected_account_id: "*.near".to_string(),
+                status: registry_types::Status::Success,
+                action: registry_types::ActionKind::Transfer {
+                    min_deposit: Some("1000".to_string()),
+                },
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn starts_a_block_stream_with_state_change_rule() {
+        let block_streamer_service = create_block_streamer_service();
+
+        block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::StateChangeRule(
+                    StateChangeRule {
+                        affected_account_id: "app.nearcrowd.near".to_string(),
+                        change_kind: StateChangeKind::DataUpdate.into(),
+                        key_prefix: Some("STATE".to_string()),
+                    },
+                )),
+            }))
+            .await
+            .unwrap();
+
+        let lock = block_streamer_service.get_block_streams_lock().unwrap();
+        let block_stream = lock.get("16210176318434468568").unwrap();
+
+        assert_eq!(
+            block_stream.indexer_config.rule,
+            registry_types::Rule::StateChange {
+                affected_account_id: "app.nearcrowd.near".to_string(),
+                change: registry_types::StateChangeKind::DataUpdate {
+                    key_prefix: Some("STATE".to_string()),
+                },
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn starts_a_block_stream_with_transaction_signer_rule() {
+        let block_streamer_service = create_block_streamer_service();
+
+        block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::TransactionSignerRule(
+                    TransactionSignerRule {
+                        signer_account_id: "morgs.near".to_string(),
+                    },
+                )),
+            }))
+            .await
+            .unwrap();
+
+        let lock = block_streamer_service.get_block_streams_lock().unwrap();
+        let block_stream = lock.get("16210176318434468568").unwrap();
+
+        assert_eq!(
+            block_stream.indexer_config.rule,
+            registry_types::Rule::TransactionSigner {
+                signer_account_id: "morgs.near".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn starts_a_block_stream_with_composite_rule() {
+        let block_streamer_service = create_block_streamer_service();
+
+        block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::AnyRule(AnyRule {
+                    rules: vec![
+                        SubRule {
+                            rule: Some(sub_rule::Rule::EventRule(EventRule {
+                                contract_account_id: "nft.near".to_string(),
+                                standard: "nep171".to_string(),
+                                version: "*".to_string(),
+                                event: "nft_mint".to_string(),
+                            })),
+                        },
+                        SubRule {
+                            rule: Some(sub_rule::Rule::NotRule(Box::new(NotRule {
+                                rule: Some(Box::new(SubRule {
+                                    rule: Some(sub_rule::Rule::ActionAnyRule(ActionAnyRule {
+                                        affected_account_id: "nft.near".to_string(),
+                                        status: 1,
+                                    })),
+                                })),
+                            }))),
+                        },
+                    ],
+                })),
+            }))
+            .await
+            .unwrap();
+
+        let lock = block_streamer_service.get_block_streams_lock().unwrap();
+        let block_stream = lock.get("16210176318434468568").unwrap();
+
+        assert_eq!(
+            block_stream.indexer_config.rule,
+            registry_types::Rule::Any {
+                rules: vec![
+                    registry_types::Rule::Event {
+                        contract_account_id: "nft.near".to_string(),
+                        standard: "nep171".to_string(),
+                        version: "*".to_string(),
+                        event: "nft_mint".to_string(),
+                    },
+                    registry_types::Rule::Not {
+                        rule: Box::new(registry_types::Rule::ActionAny {
+                            affected_account_id: "nft.near".to_string(),
+                            status: registry_types::Status::Success,
+                        }),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_composite_rule() {
+        let block_streamer_service = create_block_streamer_service();
+
+        let response = block_streamer_service
+            .start_stream(Request::new(StartStreamRequest {
+                start_block_height: 0,
+                account_id: "morgs.near".to_string(),
+                function_name: "test".to_string(),
+                version: 0,
+                redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
+                rule: Some(start_stream_request::Rule::AllRule(AllRule {
+                    rules: vec![],
+                })),
+            }))
+            .await;
+
+        assert_eq!(response.err().unwrap().code(), tonic::Code::InvalidArgument);
+    }
+
     #[tokio::test]
     async fn stops_a_block_stream() {
         let block_streamer_service = create_block_streamer_service();
@@ -419,6 +1011,7 @@ mod tests {
                 function_name: "test".to_string(),
                 version: 0,
                 redis_stream: "stream".to_string(),
+                chain_id: blockstreamer::ChainId::Mainnet.into(),
                 rule: Some(start_stream_request::Rule::ActionAnyRule(ActionAnyRule {
                     affected_account_id: "queryapi.dataplatform.near".to_string(),
                     status: 1,
@@ -3,13 +3,16 @@
 use std::time::{Duration, SystemTime};
 
 pub use block_streamer::StreamInfo;
-use block_streamer::{StartStreamResponse, StopStreamResponse};
+use block_streamer::{ListStreamsResponse, StartStreamResponse, StopStreamResponse};
 
 use anyhow::Context;
 use block_streamer::block_streamer_client::BlockStreamerClient;
 use block_streamer::{
-    start_stream_request::Rule, ActionAnyRule, ActionFunctionCallRule, GetStreamRequest,
-    ProcessingState, StartStreamRequest, Status, StopStreamRequest,
+    start_stream_request::Rule, sub_rule, ActionAnyRule, ActionFunctionCallArgsRule,
+    ActionFunctionCallRule, ActionKind, ActionKindRule, AllRule, AnyRule, ArgsCondition,
+    ArgsOperator, ChainId, EventRule, GetStreamRequest, ListStreamsRequest, NotRule,
+    ProcessingState, StartStreamRequest, StateChangeKind, StateChangeRule, Status,
+    StopStreamRequest, SubRule, TransactionSignerRule,
 };
 use near_primitives::types::AccountId;
 use registry_types::StartBlock;
@@ -77,6 +80,16 @@ impl BlockStreamsClientWrapperImpl {
     {
         self.inner.clone().start_stream(request).await
     }
+
+    pub async fn list_streams<R>(
+        &self,
+        request: R,
+    ) -> std::result::Result<tonic::Response<ListStreamsResponse>, tonic::Status>
+    where
+        R: tonic::IntoRequest<ListStreamsRequest> + 'static,
+    {
+        self.inner.clone().list_streams(request).await
+    }
 }
 
 #[cfg(not(test))]
@@ -88,11 +101,16 @@ pub use MockBlockStreamsHandlerImpl as BlockStreamsHandler;
 pub struct BlockStreamsHandlerImpl {
     client: BlockStreamsClientWrapper,
     redis_client: RedisClient,
+    chain_id: ChainId,
 }
 
 #[cfg_attr(test, mockall::automock)]
 impl BlockStreamsHandlerImpl {
-    pub fn connect(block_streamer_url: &str, redis_client: RedisClient) -> anyhow::Result<Self> {
+    pub fn connect(
+        block_streamer_url: &str,
+        redis_client: RedisClient,
+        chain_id: ChainId,
+    ) -> anyhow::Result<Self> {
         let channel = Channel::from_shared(block_streamer_url.to_string())
             .context("Block Streamer URL is invalid")?
             .connect_lazy();
@@ -101,6 +119,7 @@ impl BlockStreamsHandlerImpl {
         Ok(Self {
             client: BlockStreamsClientWrapper::new(client),
             redis_client,
+            chain_id,
         })
     }
 
@@ -127,6 +146,187 @@ impl BlockStreamsHandlerImpl {
         .into()
     }
 
+    fn match_args_operator(operator: &registry_types::ArgsOperator) -> i32 {
+        match operator {
+            registry_types::ArgsOperator::Equals => ArgsOperator::Equals,
+            registry_types::ArgsOperator::Contains => ArgsOperator::Contains,
+        }
+        .into()
+    }
+
+    fn match_action_kind(
+        affected_account_id: &str,
+        status: &registry_types::Status,
+        action: &registry_types::ActionKind,
+    ) -> ActionKindRule {
+        let mut action_kind_rule = ActionKindRule {
+            affected_account_id: affected_account_id.to_owned(),
+            status: Self::match_status(status),
+            ..Default::default()
+        };
+
+        let action_kind = match action {
+            registry_types::ActionKind::CreateAccount => ActionKind::CreateAccount,
+            registry_types::ActionKind::DeployContract => ActionKind::DeployContract,
+            registry_types::ActionKind::FunctionCall => ActionKind::FunctionCall,
+            registry_types::ActionKind::Transfer { min_deposit } => {
+                action_kind_rule.min_deposit = min_deposit.to_owned();
+                ActionKind::Transfer
+            }
+            registry_types::ActionKind::Stake { min_stake } => {
+                action_kind_rule.min_stake = min_stake.to_owned();
+                ActionKind::Stake
+            }
+            registry_types::ActionKind::AddKey { full_access } => {
+                action_kind_rule.full_access = *full_access;
+                ActionKind::AddKey
+            }
+            registry_types::ActionKind::DeleteKey => ActionKind::DeleteKey,
+            registry_types::ActionKind::DeleteAccount => ActionKind::DeleteAccount,
+            registry_types::ActionKind::Delegate => ActionKind::Delegate,
+        };
+
+        action_kind_rule.set_action_kind(action_kind);
+
+        action_kind_rule
+    }
+
+    fn match_state_change(
+        affected_account_id: &str,
+        change: &registry_types::StateChangeKind,
+    ) -> StateChangeRule {
+        let mut state_change_rule = StateChangeRule {
+            affected_account_id: affected_account_id.to_owned(),
+            ..Default::default()
+        };
+
+        let change_kind = match change {
+            registry_types::StateChangeKind::AccountUpdate => StateChangeKind::AccountUpdate,
+            registry_types::StateChangeKind::AccountDeletion => StateChangeKind::AccountDeletion,
+            registry_types::StateChangeKind::AccessKeyUpdate => StateChangeKind::AccessKeyUpdate,
+            registry_types::StateChangeKind::AccessKeyDeletion => {
+                StateChangeKind::AccessKeyDeletion
+            }
+            registry_types::StateChangeKind::DataUpdate { key_prefix } => {
+                state_change_rule.key_prefix = key_prefix.to_owned();
+                StateChangeKind::DataUpdate
+            }
+            registry_types::StateChangeKind::DataDeletion { key_prefix } => {
+                state_change_rule.key_prefix = key_prefix.to_owned();
+                StateChangeKind::DataDeletion
+            }
+            registry_types::StateChangeKind::ContractCodeUpdate => {
+                StateChangeKind::ContractCodeUpdate
+            }
+            registry_types::StateChangeKind::ContractCodeDeletion => {
+                StateChangeKind::ContractCodeDeletion
+            }
+        };
+
+        state_change_rule.set_change_kind(change_kind);
+
+        state_change_rule
+    }
+
+    fn match_rule(rule: &registry_types::Rule) -> Rule {
+        match rule {
+            registry_types::Rule::ActionAny {
+                affected_account_id,
+                status,
+            } => Rule::ActionAnyRule(ActionAnyRule {
+                affected_account_id: affected_account_id.to_owned(),
+                status: Self::match_status(status),
+            }),
+            registry_types::Rule::ActionFunctionCall {
+                affected_account_id,
+                status,
+                function,
+            } => Rule::ActionFunctionCallRule(ActionFunctionCallRule {
+                affected_account_id: affected_account_id.to_owned(),
+                function_name: function.to_owned(),
+                status: Self::match_status(status),
+            }),
+            registry_types::Rule::ActionFunctionCallArgs {
+                affected_account_id,
+                status,
+                function,
+                args,
+                min_deposit,
+                min_gas,
+            } => Rule::ActionFunctionCallArgsRule(ActionFunctionCallArgsRule {
+                affected_account_id: affected_account_id.to_owned(),
+                function_name: function.to_owned(),
+                status: Self::match_status(status),
+                args: args
+                    .iter()
+                    .map(|condition| ArgsCondition {
+                        pointer: condition.pointer.to_owned(),
+                        operator: Self::match_args_operator(&condition.operator),
+                        value: condition.value.to_owned(),
+                    })
+                    .collect(),
+                min_deposit: min_deposit.to_owned(),
+                min_gas: *min_gas,
+            }),
+            registry_types::Rule::ActionKind {
+                affected_account_id,
+                status,
+                action,
+            } => Rule::ActionKindRule(Self::match_action_kind(affected_account_id, status, action)),
+            registry_types::Rule::StateChange {
+                affected_account_id,
+                change,
+            } => Rule::StateChangeRule(Self::match_state_change(affected_account_id, change)),
+            registry_types::Rule::TransactionSigner { signer_account_id } => {
+                Rule::TransactionSignerRule(TransactionSignerRule {
+                    signer_account_id: signer_account_id.to_owned(),
+                })
+            }
+            registry_types::Rule::Event {
+                contract_account_id,
+                standard,
+                version,
+                event,
+            } => Rule::EventRule(EventRule {
+                contract_account_id: contract_account_id.to_owned(),
+                standard: standard.to_owned(),
+                version: version.to_owned(),
+                event: event.to_owned(),
+            }),
+            registry_types::Rule::Any { rules } => Rule::AnyRule(AnyRule {
+                rules: rules.iter().map(Self::match_sub_rule).collect(),
+            }),
+            registry_types::Rule::All { rules } => Rule::AllRule(AllRule {
+                rules: rules.iter().map(Self::match_sub_rule).collect(),
+            }),
+            registry_types::Rule::Not { rule } => Rule::NotRule(NotRule {
+                rule: Some(Box::new(Self::match_sub_rule(rule))),
+            }),
+        }
+    }
+
+    fn match_sub_rule(rule: &registry_types::Rule) -> SubRule {
+        let rule = match Self::match_rule(rule) {
+            Rule::ActionAnyRule(rule) => Some(sub_rule::Rule::ActionAnyRule(rule)),
+            Rule::ActionFunctionCallRule(rule) => {
+                Some(sub_rule::Rule::ActionFunctionCallRule(rule))
+            }
+            Rule::ActionFunctionCallArgsRule(rule) => {
+                Some(sub_rule::Rule::ActionFunctionCallArgsRule(rule))
+            }
+            Rule::ActionKindRule(rule) => Some(sub_rule::Rule::ActionKindRule(rule)),
+            Rule::EventRule(rule) => Some(sub_rule::Rule::EventRule(rule)),
+            Rule::AnyRule(rule) => Some(sub_rule::Rule::AnyRule(rule)),
+            Rule::AllRule(rule) => Some(sub_rule::Rule::AllRule(rule)),
+            Rule::NotRule(rule) => Some(sub_rule::Rule::NotRule(Box::new(rule))),
+            // State changes and transactions cannot be nested, leave it to Block Streamer to
+            // reject the rule
+            Rule::StateChangeRule(_) | Rule::TransactionSignerRule(_) => None,
+        };
+
+        SubRule { rule }
+    }
+
     pub async fn get(
         &self,
         account_id: AccountId,
@@ -147,36 +347,37 @@ impl BlockStreamsHandlerImpl {
         }
     }
 
+    pub async fn list(&self) -> anyhow::Result<Vec<StreamInfo>> {
+        let response = self
+            .client
+            .list_streams(ListStreamsRequest {})
+            .await
+            .context("Failed to list streams")?;
+
+        Ok(response.into_inner().streams)
+    }
+
+    /// Checks that Block Streamer is reachable, a `NotFound` response for the probe still
+    /// indicates that it is
+    pub async fn ping(&self) -> anyhow::Result<()> {
+        let request = GetStreamRequest {
+            account_id: "ping".to_string(),
+            function_name: "ping".to_string(),
+        };
+
+        match self.client.get_stream(request).await {
+            Ok(_) => Ok(()),
+            Err(status) if status.code() == tonic::Code::NotFound => Ok(()),
+            Err(err) => Err(err).context("Block Streamer is unreachable"),
+        }
+    }
+
     pub async fn start(
         &self,
         start_block_height: u64,
         indexer_config: &IndexerConfig,
     ) -> anyhow::Result<()> {
-        let rule = match &indexer_config.rule {
-            registry_types::Rule::ActionAny {
-                affected_account_id,
-                status,
-            } => Rule::ActionAnyRule(ActionAnyRule {
-                affected_account_id: affected_account_id.to_owned(),
-                status: Self::match_status(status),
-            }),
-            registry_types::Rule::ActionFunctionCall {
-                affected_account_id,
-                status,
-                function,
-            } => Rule::ActionFunctionCallRule(ActionFunctionCallRule {
-                affected_account_id: affected_account_id.to_owned(),
-                function_name: function.to_owned(),
-                status: Self::match_status(status),
-            }),
-            unsupported_rule => {
-                tracing::error!(
-                    "Encountered unsupported indexer rule: {:?}",
-                    unsupported_rule
-                );
-                return Ok(());
-            }
-        };
+        let rule = Self::match_rule(&indexer_config.rule);
 
         let request = StartStreamRequest {
             start_block_height,
@@ -185,6 +386,7 @@ impl BlockStreamsHandlerImpl {
             account_id: indexer_config.account_id.to_string(),
             function_name: indexer_config.function_name.clone(),
             rule: Some(rule),
+            chain_id: self.chain_id.into(),
         };
 
         let response = self.client.start_stream(request).await.context(format!(
@@ -435,6 +637,7 @@ mod tests {
             let handler = BlockStreamsHandlerImpl {
                 client: mock_client,
                 redis_client: mock_redis,
+                chain_id: ChainId::Mainnet,
             };
 
             assert_eq!(
@@ -465,6 +668,7 @@ mod tests {
                 })),
                 start_block_height: last_published_block + 1,
                 version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
             }))
             .returning(|_| Ok(Response::new(StartStreamResponse::default())))
             .once();
@@ -478,6 +682,7 @@ mod tests {
         let handler = BlockStreamsHandlerImpl {
             client: mock_client,
             redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
         };
 
         handler.resume(&config).await.unwrap();
@@ -493,6 +698,7 @@ mod tests {
             stream_id: "stream-id".to_string(),
             version: config.get_registry_version() - 1,
             health: None,
+            chain_id: ChainId::Mainnet.into(),
         };
 
         let mut mock_client = BlockStreamsClientWrapper::default();
@@ -525,6 +731,7 @@ mod tests {
                     unreachable!()
                 },
                 version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
             }))
             .returning(|_| Ok(Response::new(StartStreamResponse::default())));
 
@@ -537,6 +744,7 @@ mod tests {
         let handler = BlockStreamsHandlerImpl {
             client: mock_client,
             redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
         };
 
         handler.reconfigure(&config).await.unwrap();
@@ -563,6 +771,7 @@ mod tests {
                     unreachable!()
                 },
                 version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
             }))
             .returning(|_| Ok(Response::new(StartStreamResponse::default())));
 
@@ -571,6 +780,301 @@ mod tests {
         let handler = BlockStreamsHandlerImpl {
             client: mock_client,
             redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
+        };
+
+        handler.start_new_block_stream(&config).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn starts_streams_with_event_rule() {
+        let config = IndexerConfig {
+            rule: registry_types::Rule::Event {
+                contract_account_id: "nft.near".to_string(),
+                standard: "nep171".to_string(),
+                version: "1.0.0".to_string(),
+                event: "nft_mint".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let mut mock_client = BlockStreamsClientWrapper::default();
+        mock_client
+            .expect_start_stream::<StartStreamRequest>()
+            .with(eq(StartStreamRequest {
+                account_id: config.account_id.to_string(),
+                function_name: config.function_name.clone(),
+                redis_stream: config.get_redis_stream_key(),
+                rule: Some(Rule::EventRule(EventRule {
+                    contract_account_id: "nft.near".to_string(),
+                    standard: "nep171".to_string(),
+                    version: "1.0.0".to_string(),
+                    event: "nft_mint".to_string(),
+                })),
+                start_block_height: 100,
+                version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
+            }))
+            .returning(|_| Ok(Response::new(StartStreamResponse::default())))
+            .once();
+
+        let mock_redis = RedisClient::default();
+
+        let handler = BlockStreamsHandlerImpl {
+            client: mock_client,
+            redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
+        };
+
+        handler.start_new_block_stream(&config).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn starts_streams_with_function_call_args_rule() {
+        let config = IndexerConfig {
+            rule: registry_types::Rule::ActionFunctionCallArgs {
+                affected_account_id: "token.sweat".to_string(),
+                status: registry_types::Status::Success,
+                function: "ft_transfer".to_string(),
+                args: vec![registry_types::ArgsCondition {
+                    pointer: "/receiver_id".to_string(),
+                    operator: registry_types::ArgsOperator::Equals,
+                    value: "morgs.near".to_string(),
+                }],
+                min_deposit: Some("1".to_string()),
+                min_gas: Some(30_000_000_000_000),
+            },
+            ..Default::default()
+        };
+
+        let mut mock_client = BlockStreamsClientWrapper::default();
+        mock_client
+            .expect_start_stream::<StartStreamRequest>()
+            .with(eq(StartStreamRequest {
+                account_id: config.account_id.to_string(),
+                function_name: config.function_name.clone(),
+                redis_stream: config.get_redis_stream_key(),
+                rule: Some(Rule::ActionFunctionCallArgsRule(
+                    ActionFunctionCallArgsRule {
+                        affected_account_id: "token.sweat".to_string(),
+                        function_name: "ft_transfer".to_string(),
+                        status: Status::Success.into(),
+                        args: vec![ArgsCondition {
+                            pointer: "/receiver_id".to_string(),
+                            operator: ArgsOperator::Equals.into(),
+                            value: "morgs.near".to_string(),
+                        }],
+                        min_deposit: Some("1".to_string()),
+                        min_gas: Some(30_000_000_000_000),
+                    },
+                )),
+                start_block_height: 100,
+                version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
+            }))
+            .returning(|_| Ok(Response::new(StartStreamResponse::default())))
+            .once();
+
+        let mock_redis = RedisClient::default();
+
+        let handler = BlockStreamsHandlerImpl {
+            client: mock_client,
+            redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
+        };
+
+        handler.start_new_block_stream(&config).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn starts_streams_with_action_kind_rule() {
+        let config = IndexerConfig {
+            rule: registry_types::Rule::ActionKind {
+                affected_account_id: "*.near".to_string(),
+                status: registry_types::Status::Success,
+                action: registry_types::ActionKind::AddKey {
+                    full_access: Some(true),
+                },
+            },
+            ..Default::default()
+        };
+
+        let mut mock_client = BlockStreamsClientWrapper::default();
+        mock_client
+            .expect_start_stream::<StartStreamRequest>()
+            .with(eq(StartStreamRequest {
+                account_id: config.account_id.to_string(),
+                function_name: config.function_name.clone(),
+                redis_stream: config.get_redis_stream_key(),
+                rule: Some(Rule::ActionKindRule(ActionKindRule {
+                    affected_account_id: "*.near".to_string(),
+                    status: Status::Success.into(),
+                    action_kind: ActionKind::AddKey.into(),
+                    min_deposit: None,
+                    min_stake: None,
+                    full_access: Some(true),
+                })),
+                start_block_height: 100,
+                version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
+            }))
+            .returning(|_| Ok(Response::new(StartStreamResponse::default())))
+            .once();
+
+        let mock_redis = RedisClient::default();
+
+        let handler = BlockStreamsHandlerImpl {
+            client: mock_client,
+            redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
+        };
+
+        handler.start_new_block_stream(&config).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn starts_streams_with_state_change_rule() {
+        let config = IndexerConfig {
+            rule: registry_types::Rule::StateChange {
+                affected_account_id: "app.nearcrowd.near".to_string(),
+                change: registry_types::StateChangeKind::DataUpdate {
+                    key_prefix: Some("STATE".to_string()),
+                },
+            },
+            ..Default::default()
+        };
+
+        let mut mock_client = BlockStreamsClientWrapper::default();
+        mock_client
+            .expect_start_stream::<StartStreamRequest>()
+            .with(eq(StartStreamRequest {
+                account_id: config.account_id.to_string(),
+                function_name: config.function_name.clone(),
+                redis_stream: config.get_redis_stream_key(),
+                rule: Some(Rule::StateChangeRule(StateChangeRule {
+                    affected_account_id: "app.nearcrowd.near".to_string(),
+                    change_kind: StateChangeKind::DataUpdate.into(),
+                    key_prefix: Some("STATE".to_string()),
+                })),
+                start_block_height: 100,
+                version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
+            }))
+            .returning(|_| Ok(Response::new(StartStreamResponse::default())))
+            .once();
+
+        let mock_redis = RedisClient::default();
+
+        let handler = BlockStreamsHandlerImpl {
+            client: mock_client,
+            redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
+        };
+
+        handler.start_new_block_stream(&config).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn starts_streams_with_transaction_signer_rule() {
+        let config = IndexerConfig {
+            rule: registry_types::Rule::TransactionSigner {
+                signer_account_id: "morgs.near".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let mut mock_client = BlockStreamsClientWrapper::default();
+        mock_client
+            .expect_start_stream::<StartStreamRequest>()
+            .with(eq(StartStreamRequest {
+                account_id: config.account_id.to_string(),
+                function_name: config.function_name.clone(),
+                redis_stream: config.get_redis_stream_key(),
+                rule: Some(Rule::TransactionSignerRule(TransactionSignerRule {
+                    signer_account_id: "morgs.near".to_string(),
+                })),
+                start_block_height: 100,
+                version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
+            }))
+            .returning(|_| Ok(Response::new(StartStreamResponse::default())))
+            .once();
+
+        let mock_redis = RedisClient::default();
+
+        let handler = BlockStreamsHandlerImpl {
+            client: mock_client,
+            redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
+        };
+
+        handler.start_new_block_stream(&config).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn starts_streams_with_composite_rule() {
+        let config = IndexerConfig {
+            rule: registry_types::Rule::All {
+                rules: vec![
+                    registry_types::Rule::ActionAny {
+                        affected_account_id: "nft.near".to_string(),
+                        status: registry_types::Status::Success,
+                    },
+                    registry_types::Rule::Not {
+                        rule: Box::new(registry_types::Rule::ActionFunctionCall {
+                            affected_account_id: "nft.near".to_string(),
+                            status: registry_types::Status::Any,
+                            function: "nft_transfer".to_string(),
+                        }),
+                    },
+                ],
+            },
+            ..Default::default()
+        };
+
+        let mut mock_client = BlockStreamsClientWrapper::default();
+        mock_client
+            .expect_start_stream::<StartStreamRequest>()
+            .with(eq(StartStreamRequest {
+                account_id: config.account_id.to_string(),
+                function_name: config.function_name.clone(),
+                redis_stream: config.get_redis_stream_key(),
+                rule: Some(Rule::AllRule(AllRule {
+                    rules: vec![
+                        SubRule {
+                            rule: Some(sub_rule::Rule::ActionAnyRule(ActionAnyRule {
+                                affected_account_id: "nft.near".to_string(),
+                                status: Status::Success.into(),
+                            })),
+                        },
+                        SubRule {
+                            rule: Some(sub_rule::Rule::NotRule(Box::new(NotRule {
+                                rule: Some(Box::new(SubRule {
+                                    rule: Some(sub_rule::Rule::ActionFunctionCallRule(
+                                        ActionFunctionCallRule {
+                                            affected_account_id: "nft.near".to_string(),
+                                            function_name: "nft_transfer".to_string(),
+                                            status: Status::Any.into(),
+                                        },
+                                    )),
+                                })),
+                            }))),
+                        },
+                    ],
+                })),
+                start_block_height: 100,
+                version: config.get_registry_version(),
+                chain_id: ChainId::Mainnet.into(),
+            }))
+            .returning(|_| Ok(Response::new(StartStreamResponse::default())))
+            .once();
+
+        let mock_redis = RedisClient::default();
+
+        let handler = BlockStreamsHandlerImpl {
+            client: mock_client,
+            redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
         };
 
         handler.start_new_block_stream(&config).await.unwrap();
@@ -594,6 +1098,7 @@ mod tests {
                     .as_secs(),
                 processing_state: ProcessingState::Stalled.into(),
             }),
+            chain_id: ChainId::Mainnet.into(),
         };
 
         let mock_client = BlockStreamsClientWrapper::default();
@@ -602,6 +1107,7 @@ mod tests {
         let handler = BlockStreamsHandlerImpl {
             client: mock_client,
             redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
         };
 
         assert!(!handler.is_healthy(&existing_stream));
@@ -632,6 +1138,7 @@ mod tests {
                         .as_secs(),
                     processing_state: healthy_state.into(),
                 }),
+                chain_id: ChainId::Mainnet.into(),
             };
 
             let mock_client = BlockStreamsClientWrapper::default();
@@ -640,6 +1147,7 @@ mod tests {
             let handler = BlockStreamsHandlerImpl {
                 client: mock_client,
                 redis_client: mock_redis,
+                chain_id: ChainId::Mainnet,
             };
 
             assert!(handler.is_healthy(&existing_stream));
@@ -692,6 +1200,7 @@ mod tests {
         let handler = BlockStreamsHandlerImpl {
             client: mock_client,
             redis_client: mock_redis,
+            chain_id: ChainId::Mainnet,
         };
 
         handler.reconfigure(&config_with_latest).await.unwrap();
@@ -7,17 +7,22 @@ pub use runner::data_layer::TaskStatus;
 use anyhow::Context;
 use runner::data_layer::data_layer_client::DataLayerClient;
 use runner::data_layer::{
-    DeprovisionRequest, GetTaskStatusRequest, GetTaskStatusResponse, ProvisionRequest,
-    StartTaskResponse,
+    DeprovisionRequest, GetTaskStatusRequest, GetTaskStatusResponse, MigrateSchemaRequest,
+    ProvisionRequest, StartTaskResponse, SwapSchemaRequest,
 };
 use tonic::transport::channel::Channel;
 use tonic::Status;
 
 use crate::indexer_config::IndexerConfig;
+use crate::indexer_state::MigrationStrategy;
 
 type TaskId = String;
 
-const TASK_TIMEOUT_SECONDS: u64 = 600; // 10 minutes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub status: TaskStatus,
+    pub error: Option<String>,
+}
 
 #[cfg(not(test))]
 use DataLayerClientWrapperImpl as DataLayerClientWrapper;
@@ -62,6 +67,26 @@ impl DataLayerClientWrapperImpl {
         self.inner.clone().start_deprovisioning_task(request).await
     }
 
+    pub async fn migrate_schema<R>(
+        &self,
+        request: R,
+    ) -> std::result::Result<tonic::Response<StartTaskResponse>, tonic::Status>
+    where
+        R: tonic::IntoRequest<MigrateSchemaRequest> + 'static,
+    {
+        self.inner.clone().migrate_schema(request).await
+    }
+
+    pub async fn swap_schema<R>(
+        &self,
+        request: R,
+    ) -> std::result::Result<tonic::Response<StartTaskResponse>, tonic::Status>
+    where
+        R: tonic::IntoRequest<SwapSchemaRequest> + 'static,
+    {
+        self.inner.clone().swap_schema(request).await
+    }
+
     pub async fn get_task_status<R>(
         &self,
         request: R,
@@ -116,7 +141,7 @@ impl DataLayerHandlerImpl {
         &self,
         account_id: AccountId,
         function_name: String,
-    ) -> anyhow::Result<TaskId> {
+    ) -> Result<TaskId, Status> {
         let request = DeprovisionRequest {
             account_id: account_id.to_string(),
             function_name,
@@ -127,124 +152,123 @@ impl DataLayerHandlerImpl {
         Ok(response.into_inner().task_id)
     }
 
+    /// Starts migrating the Data Layer from `previous_schema` to the schema of `indexer_config`
+    pub async fn start_migration(
+        &self,
+        indexer_config: &IndexerConfig,
+        previous_schema: String,
+        strategy: MigrationStrategy,
+    ) -> anyhow::Result<TaskId> {
+        let request = MigrateSchemaRequest {
+            account_id: indexer_config.account_id.to_string(),
+            function_name: indexer_config.function_name.clone(),
+            previous_schema,
+            schema: indexer_config.schema.clone(),
+            strategy: match strategy {
+                MigrationStrategy::Additive => runner::data_layer::MigrationStrategy::Additive,
+                MigrationStrategy::Recreate => runner::data_layer::MigrationStrategy::Recreate,
+            }
+            .into(),
+        };
+
+        let response = self.client.migrate_schema(request).await?;
+
+        Ok(response.into_inner().task_id)
+    }
+
+    /// Starts replacing the live Data Layer of `indexer_config` with the one provisioned for
+    /// `shadow_config`
+    pub async fn start_swap(
+        &self,
+        indexer_config: &IndexerConfig,
+        shadow_config: &IndexerConfig,
+    ) -> anyhow::Result<TaskId> {
+        let request = SwapSchemaRequest {
+            account_id: indexer_config.account_id.to_string(),
+            function_name: indexer_config.function_name.clone(),
+            shadow_function_name: shadow_config.function_name.clone(),
+            schema: shadow_config.schema.clone(),
+        };
+
+        let response = self.client.swap_schema(request).await?;
+
+        Ok(response.into_inner().task_id)
+    }
+
     pub async fn get_task_status(&self, task_id: TaskId) -> anyhow::Result<TaskStatus> {
+        Ok(self.get_task(task_id).await?.status)
+    }
+
+    /// Returns the status of a task along with the reason it failed, if any
+    pub async fn get_task(&self, task_id: TaskId) -> anyhow::Result<Task> {
         let request = GetTaskStatusRequest { task_id };
 
         let response = self.client.get_task_status(request).await;
 
         if let Err(error) = response {
             if error.code() == tonic::Code::NotFound {
-                return Ok(TaskStatus::Failed);
+                return Ok(Task {
+                    status: TaskStatus::Failed,
+                    error: Some(error.message().to_string()),
+                });
             }
 
             return Err(error.into());
         }
 
-        let status = match response.unwrap().into_inner().status {
+        let response = response.unwrap().into_inner();
+
+        let status = match response.status {
             1 => TaskStatus::Pending,
             2 => TaskStatus::Complete,
             3 => TaskStatus::Failed,
             _ => anyhow::bail!("Received invalid task status"),
         };
 
-        Ok(status)
+        Ok(Task {
+            status,
+            error: Some(response.error).filter(|error| !error.is_empty()),
+        })
     }
 
-    pub async fn ensure_provisioned(&self, indexer_config: &IndexerConfig) -> anyhow::Result<()> {
-        let start_task_result = self.start_provisioning_task(indexer_config).await;
-
-        if let Err(error) = start_task_result {
-            // Already provisioned
-            if error.code() == tonic::Code::FailedPrecondition {
-                return Ok(());
-            }
-
-            return Err(error.into());
-        }
-
-        let task_id = start_task_result.unwrap();
-
-        tracing::info!(?task_id, "Started provisioning task");
-
-        let mut iterations = 0;
-        let delay_seconds = 1;
-
-        loop {
-            match self.get_task_status(task_id.clone()).await? {
-                TaskStatus::Pending => {}
-                TaskStatus::Complete => break,
-                TaskStatus::Failed | TaskStatus::Unspecified => {
-                    tracing::warn!("Provisioning task failed");
-                    anyhow::bail!("Provisioning task failed")
-                }
-            }
-
-            tokio::time::sleep(std::time::Duration::from_secs(delay_seconds)).await;
-
-            iterations += 1;
-
-            if iterations * delay_seconds % 10 == 0 {
-                let delay = iterations * delay_seconds;
-
-                if delay > TASK_TIMEOUT_SECONDS {
-                    tracing::warn!("Provisioning task timed out");
-                    anyhow::bail!("Provisioning task timed out");
-                }
+    /// Starts provisioning the Data Layer without waiting for it to complete, returning the task
+    /// to poll, or `None` if the Data Layer is already provisioned
+    pub async fn start_provisioning(
+        &self,
+        indexer_config: &IndexerConfig,
+    ) -> anyhow::Result<Option<TaskId>> {
+        match self.start_provisioning_task(indexer_config).await {
+            Ok(task_id) => {
+                tracing::info!(?task_id, "Started provisioning task");
 
-                tracing::warn!(
-                    "Still waiting for provisioning to complete after {} seconds",
-                    delay
-                );
+                Ok(Some(task_id))
             }
+            // Already provisioned
+            Err(error) if error.code() == tonic::Code::FailedPrecondition => Ok(None),
+            Err(error) => Err(error.into()),
         }
-
-        Ok(())
     }
 
-    pub async fn ensure_deprovisioned(
+    /// Starts deprovisioning the Data Layer without waiting for it to complete, returning the
+    /// task to poll, or `None` if the Data Layer is already deprovisioned
+    pub async fn start_deprovisioning(
         &self,
         account_id: AccountId,
         function_name: String,
-    ) -> anyhow::Result<()> {
-        let task_id = self
-            .start_deprovisioning_task(account_id.clone(), function_name.clone())
-            .await?;
-
-        tracing::info!(?task_id, "Started deprovisioning task");
-
-        let mut iterations = 0;
-        let delay_seconds = 1;
-
-        loop {
-            match self.get_task_status(task_id.clone()).await? {
-                TaskStatus::Pending => {}
-                TaskStatus::Complete => break,
-                TaskStatus::Failed | TaskStatus::Unspecified => {
-                    tracing::warn!("Deprovisioning task failed");
-                    anyhow::bail!("Deprovisioning task failed")
-                }
-            }
-
-            tokio::time::sleep(std::time::Duration::from_secs(delay_seconds)).await;
-
-            iterations += 1;
-
-            if iterations * delay_seconds % 10 == 0 {
-                let delay = iterations * delay_seconds;
-
-                if delay > TASK_TIMEOUT_SECONDS {
-                    tracing::warn!("Deprovisioning task timed out");
-                    anyhow::bail!("Deprovisioning task timed out");
-                }
+    ) -> anyhow::Result<Option<TaskId>> {
+        match self
+            .start_deprovisioning_task(account_id, function_name)
+            .await
+        {
+            Ok(task_id) => {
+                tracing::info!(?task_id, "Started deprovisioning task");
 
-                tracing::warn!(
-                    "Still waiting for Deprovisioning to complete after {} seconds",
-                    delay
-                );
+                Ok(Some(task_id))
             }
+            // Already deprovisioned
+            Err(error) if error.code() == tonic::Code::FailedPrecondition => Ok(None),
+            Err(error) => Err(error.into()),
         }
-
-        Ok(())
     }
 }
 
@@ -263,7 +287,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn provisions_data_layer() {
+    async fn starts_provisioning_task() {
         let config = IndexerConfig::default();
 
         let mut mock_client = DataLayerClientWrapper::default();
@@ -282,89 +306,45 @@ mod tests {
             .once();
         mock_client
             .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Pending.into(),
-                }))
-            })
-            .once();
-        mock_client
-            .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Complete.into(),
-                }))
-            })
-            .once();
+            .never();
 
         let handler = DataLayerHandlerImpl {
             client: mock_client,
         };
 
-        handler.ensure_provisioned(&config).await.unwrap();
+        assert_eq!(
+            handler.start_provisioning(&config).await.unwrap(),
+            Some("task_id".to_string())
+        );
     }
 
     #[tokio::test]
-    async fn timesout_provisioning_task() {
-        tokio::time::pause();
-
+    async fn ignores_already_provisioned_data_layer() {
         let config = IndexerConfig::default();
 
         let mut mock_client = DataLayerClientWrapper::default();
         mock_client
             .expect_start_provisioning_task::<ProvisionRequest>()
-            .with(eq(ProvisionRequest {
-                account_id: config.account_id.to_string(),
-                function_name: config.function_name.clone(),
-                schema: config.schema.clone(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(StartTaskResponse {
-                    task_id: "task_id".to_string(),
-                }))
-            })
+            .returning(|_| Err(tonic::Status::failed_precondition("already provisioned")))
             .once();
-        mock_client
-            .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Pending.into(),
-                }))
-            })
-            .times(610);
 
         let handler = DataLayerHandlerImpl {
             client: mock_client,
         };
 
-        let result = handler.ensure_provisioned(&config).await;
-
-        assert_eq!(
-            result.err().unwrap().to_string(),
-            "Provisioning task timed out"
-        );
+        assert_eq!(handler.start_provisioning(&config).await.unwrap(), None);
     }
 
     #[tokio::test]
-    async fn propagates_provisioning_failures() {
+    async fn starts_deprovisioning_task() {
         let config = IndexerConfig::default();
 
         let mut mock_client = DataLayerClientWrapper::default();
         mock_client
-            .expect_start_provisioning_task::<ProvisionRequest>()
-            .with(eq(ProvisionRequest {
+            .expect_start_deprovisioning_task::<DeprovisionRequest>()
+            .with(eq(DeprovisionRequest {
                 account_id: config.account_id.to_string(),
                 function_name: config.function_name.clone(),
-                schema: config.schema.clone(),
             }))
             .returning(|_| {
                 Ok(tonic::Response::new(StartTaskResponse {
@@ -374,64 +354,74 @@ mod tests {
             .once();
         mock_client
             .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Failed.into(),
-                }))
-            })
-            .once();
+            .never();
 
         let handler = DataLayerHandlerImpl {
             client: mock_client,
         };
 
-        let result = handler.ensure_provisioned(&config).await;
-
         assert_eq!(
-            result.err().unwrap().to_string(),
-            "Provisioning task failed"
+            handler
+                .start_deprovisioning(config.account_id, config.function_name)
+                .await
+                .unwrap(),
+            Some("task_id".to_string())
         );
     }
 
     #[tokio::test]
-    async fn deprovisions_data_layer() {
+    async fn does_not_start_deprovisioning_already_deprovisioned_data_layer() {
         let config = IndexerConfig::default();
 
         let mut mock_client = DataLayerClientWrapper::default();
         mock_client
             .expect_start_deprovisioning_task::<DeprovisionRequest>()
-            .with(eq(DeprovisionRequest {
-                account_id: config.account_id.to_string(),
-                function_name: config.function_name.clone(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(StartTaskResponse {
-                    task_id: "task_id".to_string(),
-                }))
-            })
+            .returning(|_| Err(tonic::Status::failed_precondition("not provisioned")))
             .once();
+
+        let handler = DataLayerHandlerImpl {
+            client: mock_client,
+        };
+
+        assert_eq!(
+            handler
+                .start_deprovisioning(config.account_id, config.function_name)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn treats_missing_task_as_failed() {
+        let mut mock_client = DataLayerClientWrapper::default();
         mock_client
             .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Pending.into(),
-                }))
-            })
+            .returning(|_| Err(tonic::Status::not_found("task not found")))
             .once();
+
+        let handler = DataLayerHandlerImpl {
+            client: mock_client,
+        };
+
+        assert_eq!(
+            handler
+                .get_task_status("task_id".to_string())
+                .await
+                .unwrap(),
+            TaskStatus::Failed
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_task_error() {
+        let mut mock_client = DataLayerClientWrapper::default();
         mock_client
             .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
             .returning(|_| {
                 Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Complete.into(),
+                    status: TaskStatus::Failed.into(),
+                    error: "relation already exists".to_string(),
                 }))
             })
             .once();
@@ -440,24 +430,28 @@ mod tests {
             client: mock_client,
         };
 
-        handler
-            .ensure_deprovisioned(config.account_id, config.function_name)
-            .await
-            .unwrap();
+        assert_eq!(
+            handler.get_task("task_id".to_string()).await.unwrap(),
+            Task {
+                status: TaskStatus::Failed,
+                error: Some("relation already exists".to_string()),
+            }
+        );
     }
 
     #[tokio::test]
-    async fn timesout_deprovisioning_task() {
-        tokio::time::pause();
-
+    async fn starts_migration_task() {
         let config = IndexerConfig::default();
 
         let mut mock_client = DataLayerClientWrapper::default();
         mock_client
-            .expect_start_deprovisioning_task::<DeprovisionRequest>()
-            .with(eq(DeprovisionRequest {
+            .expect_migrate_schema::<MigrateSchemaRequest>()
+            .with(eq(MigrateSchemaRequest {
                 account_id: config.account_id.to_string(),
                 function_name: config.function_name.clone(),
+                previous_schema: "previous_schema".to_string(),
+                schema: config.schema.clone(),
+                strategy: runner::data_layer::MigrationStrategy::Recreate.into(),
             }))
             .returning(|_| {
                 Ok(tonic::Response::new(StartTaskResponse {
@@ -465,42 +459,37 @@ mod tests {
                 }))
             })
             .once();
-        mock_client
-            .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Pending.into(),
-                }))
-            })
-            .times(610);
 
         let handler = DataLayerHandlerImpl {
             client: mock_client,
         };
 
-        let result = handler
-            .ensure_deprovisioned(config.account_id, config.function_name)
-            .await;
-
         assert_eq!(
-            result.err().unwrap().to_string(),
-            "Deprovisioning task timed out"
+            handler
+                .start_migration(
+                    &config,
+                    "previous_schema".to_string(),
+                    MigrationStrategy::Recreate
+                )
+                .await
+                .unwrap(),
+            "task_id".to_string()
         );
     }
 
     #[tokio::test]
-    async fn propagates_deprovisioning_failures() {
+    async fn starts_swap_task() {
         let config = IndexerConfig::default();
+        let shadow_config = config.get_shadow_config();
 
         let mut mock_client = DataLayerClientWrapper::default();
         mock_client
-            .expect_start_deprovisioning_task::<DeprovisionRequest>()
-            .with(eq(DeprovisionRequest {
+            .expect_swap_schema::<SwapSchemaRequest>()
+            .with(eq(SwapSchemaRequest {
                 account_id: config.account_id.to_string(),
                 function_name: config.function_name.clone(),
+                shadow_function_name: shadow_config.function_name.clone(),
+                schema: shadow_config.schema.clone(),
             }))
             .returning(|_| {
                 Ok(tonic::Response::new(StartTaskResponse {
@@ -508,29 +497,14 @@ mod tests {
                 }))
             })
             .once();
-        mock_client
-            .expect_get_task_status::<GetTaskStatusRequest>()
-            .with(eq(GetTaskStatusRequest {
-                task_id: "task_id".to_string(),
-            }))
-            .returning(|_| {
-                Ok(tonic::Response::new(GetTaskStatusResponse {
-                    status: TaskStatus::Failed.into(),
-                }))
-            })
-            .once();
 
         let handler = DataLayerHandlerImpl {
             client: mock_client,
         };
 
-        let result = handler
-            .ensure_deprovisioned(config.account_id, config.function_name)
-            .await;
-
         assert_eq!(
-            result.err().unwrap().to_string(),
-            "Deprovisioning task failed"
+            handler.start_swap(&config, &shadow_config).await.unwrap(),
+            "task_id".to_string()
         );
     }
 }
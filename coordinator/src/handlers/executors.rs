@@ -6,8 +6,8 @@ pub use runner::ExecutorInfo;
 use anyhow::Context;
 use runner::runner_client::RunnerClient;
 use runner::{
-    ExecutionState, GetExecutorRequest, StartExecutorRequest, StartExecutorResponse,
-    StopExecutorRequest, StopExecutorResponse,
+    ExecutionState, GetExecutorRequest, ListExecutorsRequest, ListExecutorsResponse,
+    StartExecutorRequest, StartExecutorResponse, StopExecutorRequest, StopExecutorResponse,
 };
 use tonic::transport::channel::Channel;
 
@@ -71,6 +71,16 @@ impl ExecutorsClientWrapperImpl {
     {
         self.inner.clone().stop_executor(request).await
     }
+
+    pub async fn list_executors<R>(
+        &self,
+        request: R,
+    ) -> std::result::Result<tonic::Response<ListExecutorsResponse>, tonic::Status>
+    where
+        R: tonic::IntoRequest<ListExecutorsRequest> + 'static,
+    {
+        self.inner.clone().list_executors(request).await
+    }
 }
 
 #[cfg(not(test))]
@@ -116,6 +126,31 @@ impl ExecutorsHandlerImpl {
         }
     }
 
+    pub async fn list(&self) -> anyhow::Result<Vec<ExecutorInfo>> {
+        let response = self
+            .client
+            .list_executors(ListExecutorsRequest {})
+            .await
+            .context("Failed to list executors")?;
+
+        Ok(response.into_inner().executors)
+    }
+
+    /// Checks that Runner is reachable, a `NotFound` response for the probe still indicates that
+    /// it is
+    pub async fn ping(&self) -> anyhow::Result<()> {
+        let request = GetExecutorRequest {
+            account_id: "ping".to_string(),
+            function_name: "ping".to_string(),
+        };
+
+        match self.client.get_executor(request).await {
+            Ok(_) => Ok(()),
+            Err(status) if status.code() == tonic::Code::NotFound => Ok(()),
+            Err(err) => Err(err).context("Runner is unreachable"),
+        }
+    }
+
     pub async fn start(&self, indexer_config: &IndexerConfig) -> anyhow::Result<()> {
         let request = StartExecutorRequest {
             code: indexer_config.code.clone(),
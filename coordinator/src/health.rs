@@ -0,0 +1,169 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::handlers::block_streams::BlockStreamsHandler;
+use crate::handlers::executors::ExecutorsHandler;
+use crate::redis::RedisClient;
+use crate::registry::Registry;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// The registry is synced continuously, so a stale snapshot indicates RPC is unreachable
+const MAX_REGISTRY_STALENESS: Duration = Duration::from_secs(60);
+
+/// Reachability of each dependency, as of the last check
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct HealthStatus {
+    pub redis: bool,
+    pub rpc: bool,
+    pub block_streamer: bool,
+    pub runner: bool,
+}
+
+impl HealthStatus {
+    pub fn is_ready(&self) -> bool {
+        self.redis && self.rpc && self.block_streamer && self.runner
+    }
+}
+
+/// Periodically checks the reachability of all dependencies, so that health endpoints can respond
+/// without making requests themselves
+pub struct HealthChecker {
+    redis_client: RedisClient,
+    registry: Arc<Registry>,
+    block_streams_handler: BlockStreamsHandler,
+    executors_handler: ExecutorsHandler,
+    status: RwLock<HealthStatus>,
+}
+
+impl HealthChecker {
+    pub fn new(
+        redis_client: RedisClient,
+        registry: Arc<Registry>,
+        block_streams_handler: BlockStreamsHandler,
+        executors_handler: ExecutorsHandler,
+    ) -> Self {
+        Self {
+            redis_client,
+            registry,
+            block_streams_handler,
+            executors_handler,
+            status: RwLock::new(HealthStatus::default()),
+        }
+    }
+
+    pub fn get_status(&self) -> HealthStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    async fn check(&self) {
+        let (redis, block_streamer, runner) = tokio::join!(
+            self.redis_client.ping(),
+            self.block_streams_handler.ping(),
+            self.executors_handler.ping()
+        );
+
+        let rpc = self
+            .registry
+            .get_staleness()
+            .is_some_and(|staleness| staleness < MAX_REGISTRY_STALENESS);
+
+        let status = HealthStatus {
+            redis: redis.is_ok(),
+            rpc,
+            block_streamer: block_streamer.is_ok(),
+            runner: runner.is_ok(),
+        };
+
+        if !status.is_ready() {
+            tracing::warn!(
+                ?status,
+                redis_error = ?redis.err(),
+                block_streamer_error = ?block_streamer.err(),
+                runner_error = ?runner.err(),
+                "Dependencies are unhealthy"
+            );
+        }
+
+        *self.status.write().unwrap() = status;
+    }
+
+    pub async fn run(&self) {
+        loop {
+            self.check().await;
+
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_unreachable_dependencies() {
+        let mut redis_client = RedisClient::default();
+        redis_client.expect_ping().returning(|| Ok(()));
+
+        let mut registry = Registry::default();
+        registry
+            .expect_get_staleness()
+            .returning(|| Some(Duration::from_secs(1)));
+
+        let mut block_streams_handler = BlockStreamsHandler::default();
+        block_streams_handler.expect_ping().returning(|| Ok(()));
+
+        let mut executors_handler = ExecutorsHandler::default();
+        executors_handler
+            .expect_ping()
+            .returning(|| anyhow::bail!("unreachable"));
+
+        let health_checker = HealthChecker::new(
+            redis_client,
+            Arc::new(registry),
+            block_streams_handler,
+            executors_handler,
+        );
+
+        health_checker.check().await;
+
+        assert_eq!(
+            health_checker.get_status(),
+            HealthStatus {
+                redis: true,
+                rpc: true,
+                block_streamer: true,
+                runner: false,
+            }
+        );
+        assert!(!health_checker.get_status().is_ready());
+    }
+
+    #[tokio::test]
+    async fn reports_stale_registry_as_unreachable_rpc() {
+        let mut redis_client = RedisClient::default();
+        redis_client.expect_ping().returning(|| Ok(()));
+
+        let mut registry = Registry::default();
+        registry
+            .expect_get_staleness()
+            .returning(|| Some(MAX_REGISTRY_STALENESS));
+
+        let mut block_streams_handler = BlockStreamsHandler::default();
+        block_streams_handler.expect_ping().returning(|| Ok(()));
+
+        let mut executors_handler = ExecutorsHandler::default();
+        executors_handler.expect_ping().returning(|| Ok(()));
+
+        let health_checker = HealthChecker::new(
+            redis_client,
+            Arc::new(registry),
+            block_streams_handler,
+            executors_handler,
+        );
+
+        health_checker.check().await;
+
+        assert!(!health_checker.get_status().rpc);
+    }
+}
@@ -1,5 +1,5 @@
 use near_primitives::types::AccountId;
-use registry_types::{Rule, StartBlock};
+use registry_types::{Rule, StartBlock, SHADOW_FUNCTION_NAME_SUFFIX};
 
 use crate::redis::KeyProvider;
 
@@ -59,4 +59,14 @@ impl IndexerConfig {
     pub fn is_deleted(&self) -> bool {
         self.deleted_at_block_height.is_some()
     }
+
+    /// Config identifying the shadow of this Indexer, used to run a new version alongside the
+    /// live one during a blue/green rollout. The distinct function name gives the shadow its own
+    /// Block Stream, Executor, Redis keys and Data Layer schema.
+    pub fn get_shadow_config(&self) -> Self {
+        Self {
+            function_name: format!("{}{SHADOW_FUNCTION_NAME_SUFFIX}", self.function_name),
+            ..self.clone()
+        }
+    }
 }
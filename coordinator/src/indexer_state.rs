@@ -37,6 +37,341 @@ pub struct IndexerState {
     pub block_stream_synced_at: Option<u64>,
     pub enabled: bool,
     pub lifecycle_state: LifecycleState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repair: Option<RepairState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deletion: Option<DeletionState>,
+    /// Registry version of the Indexer when it was suspended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspended_at_version: Option<u64>,
+    #[serde(default, skip_serializing_if = "ResumePolicy::is_default")]
+    pub resume_policy: ResumePolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_stream_restart: Option<RestartState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_restart: Option<RestartState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset: Option<ResetState>,
+    /// Provisioning progress of the Data Layer, including the in-flight task so that it can be
+    /// polled across iterations and Coordinator restarts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provisioned_state: Option<ProvisionedState>,
+    /// Schema the Data Layer was last provisioned or migrated with, used to detect schema changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provisioned_schema: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migration: Option<MigrationState>,
+    #[serde(default, skip_serializing_if = "MigrationStrategy::is_default")]
+    pub migration_strategy: MigrationStrategy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<RolloutState>,
+    #[serde(default, skip_serializing_if = "RolloutStrategy::is_default")]
+    pub rollout_strategy: RolloutStrategy,
+    /// Why the Indexer is currently being held back by the `QuotaPolicy`, if at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_hold: Option<QuotaHold>,
+    /// Requests merged into this state, which are acknowledged once it is persisted, see
+    /// `IndexerRequest`
+    #[serde(skip)]
+    pub applied_requests: Vec<(String, String)>,
+}
+
+#[cfg(test)]
+impl Default for IndexerState {
+    fn default() -> Self {
+        let indexer_config = IndexerConfig::default();
+
+        Self {
+            account_id: indexer_config.account_id,
+            function_name: indexer_config.function_name,
+            block_stream_synced_at: None,
+            enabled: true,
+            lifecycle_state: LifecycleState::default(),
+            repair: None,
+            deletion: None,
+            suspended_at_version: None,
+            resume_policy: ResumePolicy::default(),
+            block_stream_restart: None,
+            executor_restart: None,
+            reset: None,
+            provisioned_state: None,
+            provisioned_schema: None,
+            migration: None,
+            migration_strategy: MigrationStrategy::default(),
+            rollout: None,
+            rollout_strategy: RolloutStrategy::default(),
+            quota_hold: None,
+            applied_requests: vec![],
+        }
+    }
+}
+
+/// Change to an Indexer requested via the API. Requests are persisted separately from the state so
+/// that any Coordinator can accept them, regardless of which one manages the Indexer, and without
+/// racing the lifecycle for the state itself. Pending requests are merged in to the state when it
+/// is read, and acknowledged atomically when the lifecycle persists the merged state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexerRequest {
+    Enabled,
+    ResumePolicy,
+    MigrationStrategy,
+    RolloutStrategy,
+    /// Replaces any reset in progress, see `LifecycleState::Resetting`
+    Reset,
+}
+
+impl IndexerRequest {
+    /// Field of the requests hash the request is stored under
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::Enabled => "enabled",
+            Self::ResumePolicy => "resume_policy",
+            Self::MigrationStrategy => "migration_strategy",
+            Self::RolloutStrategy => "rollout_strategy",
+            Self::Reset => "reset",
+        }
+    }
+
+    fn from_field(field: &str) -> Option<Self> {
+        [
+            Self::Enabled,
+            Self::ResumePolicy,
+            Self::MigrationStrategy,
+            Self::RolloutStrategy,
+            Self::Reset,
+        ]
+        .into_iter()
+        .find(|request| request.field() == field)
+    }
+}
+
+impl IndexerState {
+    /// Initial state of a newly registered Indexer
+    pub fn new(indexer_config: &IndexerConfig) -> Self {
+        Self {
+            account_id: indexer_config.account_id.clone(),
+            function_name: indexer_config.function_name.clone(),
+            block_stream_synced_at: None,
+            repair: None,
+            deletion: None,
+            enabled: true,
+            suspended_at_version: None,
+            resume_policy: ResumePolicy::default(),
+            block_stream_restart: None,
+            reset: None,
+            provisioned_state: None,
+            provisioned_schema: None,
+            migration: None,
+            migration_strategy: MigrationStrategy::default(),
+            rollout: None,
+            rollout_strategy: RolloutStrategy::default(),
+            quota_hold: None,
+            applied_requests: vec![],
+            executor_restart: None,
+            lifecycle_state: LifecycleState::default(),
+        }
+    }
+
+    fn apply_request(&mut self, request: IndexerRequest, value: &str) -> anyhow::Result<()> {
+        match request {
+            IndexerRequest::Enabled => self.enabled = serde_json::from_str(value)?,
+            IndexerRequest::ResumePolicy => self.resume_policy = serde_json::from_str(value)?,
+            IndexerRequest::MigrationStrategy => {
+                self.migration_strategy = serde_json::from_str(value)?
+            }
+            IndexerRequest::RolloutStrategy => self.rollout_strategy = serde_json::from_str(value)?,
+            IndexerRequest::Reset => {
+                let ResetRequest {
+                    block_height,
+                    clear_data,
+                } = serde_json::from_str(value)?;
+
+                self.reset = Some(ResetState {
+                    block_height,
+                    clear_data,
+                    block_stream_cleared: false,
+                    deprovisioning_task_id: None,
+                    data_layer_deprovisioned: false,
+                });
+            }
+        }
+
+        self.applied_requests
+            .push((request.field().to_string(), value.to_string()));
+
+        Ok(())
+    }
+}
+
+/// Requested reset of an Indexer to a specific block height, along with its progress so that
+/// completed steps are not repeated on retry, see `LifecycleState::Resetting`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct ResetState {
+    /// Block height to resume processing from
+    pub block_height: u64,
+    /// Whether the Data Layer should be reprovisioned, removing all indexed data
+    pub clear_data: bool,
+    pub block_stream_cleared: bool,
+    /// In-flight deprovisioning task, polled across iterations and Coordinator restarts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprovisioning_task_id: Option<String>,
+    pub data_layer_deprovisioned: bool,
+}
+
+/// Reset requested via the API, see `IndexerRequest::Reset`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+struct ResetRequest {
+    block_height: u64,
+    clear_data: bool,
+}
+
+/// Tracks migration of the Data Layer to a new schema, see `LifecycleState::Migrating`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct MigrationState {
+    /// Schema being migrated to
+    pub schema: String,
+    pub strategy: MigrationStrategy,
+    /// Migration task, once started
+    pub task_id: Option<String>,
+    /// Cause of the failure, if the migration failed
+    pub error: Option<String>,
+}
+
+/// Determines how the Data Layer is migrated when the schema of an Indexer changes
+#[derive(Default, Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum MigrationStrategy {
+    /// Only create new tables and columns, failing if existing ones were changed or removed
+    #[default]
+    Additive,
+    /// Drop all existing data and provision the new schema from scratch
+    Recreate,
+}
+
+impl MigrationStrategy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Tracks a blue/green rollout of a new version, run alongside the live one against a shadow Data
+/// Layer, see `LifecycleState::RollingOut`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct RolloutState {
+    /// Registry version being rolled out
+    pub version: u64,
+    /// Schema the shadow Data Layer is provisioned with
+    pub schema: String,
+    /// Provisioning progress of the shadow Data Layer
+    pub provisioned_state: Option<ProvisionedState>,
+    /// In-flight task deprovisioning the shadow Data Layer, while it is being torn down
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprovisioning_task_id: Option<String>,
+    /// Whether the shadow Block Stream and Executor have been started
+    pub shadow_started: bool,
+    /// Task swapping the shadow Data Layer in place of the live one, once started
+    pub swap_task_id: Option<String>,
+    /// Cause of the failure, if the rollout was abandoned
+    pub error: Option<String>,
+}
+
+/// Determines how a new version of an Indexer is rolled out
+#[derive(Default, Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum RolloutStrategy {
+    /// Stop the current version and start the new one in its place
+    #[default]
+    InPlace,
+    /// Run the new version alongside the current one, against a shadow Data Layer, and swap it in
+    /// once it has caught up
+    BlueGreen,
+}
+
+impl RolloutStrategy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Restriction placed on an Indexer to comply with the `QuotaPolicy`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum QuotaHold {
+    /// Account has more active Indexers than allowed, the Indexer is suspended until capacity is
+    /// available
+    Suspended { reason: String },
+    /// All backfill slots are taken, starting the Block Stream and Executor is deferred
+    Queued {
+        reason: String,
+        /// 1-based position in the backfill queue, or 0 if the queue has not been evaluated yet
+        position: usize,
+    },
+}
+
+impl QuotaHold {
+    pub fn reason(&self) -> &str {
+        match self {
+            Self::Suspended { reason } | Self::Queued { reason, .. } => reason,
+        }
+    }
+}
+
+/// Tracks restarts of an unhealthy Block Stream or Executor, see `RestartPolicy`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct RestartState {
+    /// Number of restarts since the component was last stable
+    pub attempts: u32,
+    /// Unix timestamp, in milliseconds, before which the component should not be restarted
+    pub next_restart_at: u64,
+}
+
+/// Determines whether a suspended Indexer is resumed when a new version is published
+#[derive(Default, Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum ResumePolicy {
+    /// Resume when a new version of the Indexer is published to the registry
+    #[default]
+    OnUpdate,
+    /// Only resume when explicitly enabled
+    Manual,
+}
+
+impl ResumePolicy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Tracks attempts to recover an Indexer from a failure, see `LifecycleState::Repairing`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct RepairState {
+    /// Cause of the most recent failure
+    pub error: String,
+    /// Number of failed repair attempts so far
+    pub attempts: u32,
+    /// Unix timestamp, in milliseconds, before which the next attempt should not be made
+    pub next_attempt_at: u64,
+}
+
+/// Tracks progress of Indexer deletion so that completed steps are not repeated on retry, see
+/// `LifecycleState::Deleting`
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct DeletionState {
+    /// Unix timestamp, in milliseconds, of when deletion was first observed
+    pub requested_at: u64,
+    /// In-flight deprovisioning task, polled across iterations and Coordinator restarts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprovisioning_task_id: Option<String>,
+    pub data_layer_deprovisioned: bool,
+    pub block_stream_cleared: bool,
+}
+
+/// Record of a lifecycle transition, appended to the Indexer history
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct IndexerTransition {
+    /// Unix timestamp, in milliseconds, of when the transition occurred
+    pub timestamp: u64,
+    pub from: LifecycleState,
+    pub to: LifecycleState,
+    /// Cause of the transition, including the error if it was triggered by a failure
+    pub reason: String,
+    pub registry_version: u64,
 }
 
 impl KeyProvider for IndexerState {
@@ -58,6 +393,9 @@ pub struct IndexerStateManagerImpl {
     redis_client: RedisClient,
 }
 
+/// Number of transitions retained in the history of each Indexer
+const HISTORY_MAX_LEN: usize = 1000;
+
 #[cfg_attr(test, mockall::automock)]
 impl IndexerStateManagerImpl {
     pub fn new(redis_client: RedisClient) -> Self {
@@ -94,7 +432,22 @@ impl IndexerStateManagerImpl {
                 account_id: old_state.account_id,
                 function_name: old_state.function_name,
                 block_stream_synced_at: old_state.block_stream_synced_at,
+                repair: None,
+                deletion: None,
                 enabled: old_state.enabled,
+                suspended_at_version: None,
+                resume_policy: ResumePolicy::default(),
+                block_stream_restart: None,
+                reset: None,
+                provisioned_state: None,
+                provisioned_schema: None,
+                migration: None,
+                migration_strategy: MigrationStrategy::default(),
+                rollout: None,
+                rollout_strategy: RolloutStrategy::default(),
+                quota_hold: None,
+                applied_requests: vec![],
+                executor_restart: None,
                 lifecycle_state: migrated_lifecycle_state,
             };
             self.redis_client
@@ -106,29 +459,43 @@ impl IndexerStateManagerImpl {
     }
 
     fn get_default_state(&self, indexer_config: &IndexerConfig) -> IndexerState {
-        IndexerState {
-            account_id: indexer_config.account_id.clone(),
-            function_name: indexer_config.function_name.clone(),
-            block_stream_synced_at: None,
-            enabled: true,
-            lifecycle_state: LifecycleState::default(),
-        }
+        IndexerState::new(indexer_config)
     }
 
+    /// Returns the persisted state with any pending `IndexerRequest`s merged in
     pub async fn get_state(&self, indexer_config: &IndexerConfig) -> anyhow::Result<IndexerState> {
         let raw_state = self.redis_client.get_indexer_state(indexer_config).await?;
 
-        if let Some(raw_state) = raw_state {
-            return Ok(serde_json::from_str(&raw_state)?);
-        }
+        let mut state = match raw_state {
+            Some(raw_state) => serde_json::from_str(&raw_state)?,
+            None => {
+                tracing::info!(
+                    account_id = indexer_config.account_id.to_string(),
+                    function_name = indexer_config.function_name.as_str(),
+                    "Creating new state using default"
+                );
 
-        tracing::info!(
-            account_id = indexer_config.account_id.to_string(),
-            function_name = indexer_config.function_name.as_str(),
-            "Creating new state using default"
-        );
+                self.get_default_state(indexer_config)
+            }
+        };
+
+        let requests = self
+            .redis_client
+            .get_indexer_requests(indexer_config)
+            .await?;
+
+        for (field, value) in requests {
+            let Some(request) = IndexerRequest::from_field(&field) else {
+                tracing::warn!(field, "Ignoring unknown request");
+                continue;
+            };
 
-        Ok(self.get_default_state(indexer_config))
+            if let Err(error) = state.apply_request(request, &value) {
+                tracing::warn!(?error, ?request, value, "Ignoring invalid request");
+            }
+        }
+
+        Ok(state)
     }
 
     pub async fn delete_state(&self, indexer_state: &IndexerState) -> anyhow::Result<()> {
@@ -145,7 +512,7 @@ impl IndexerStateManagerImpl {
         let raw_state = serde_json::to_string(&state)?;
 
         self.redis_client
-            .set_indexer_state(indexer_config, raw_state)
+            .set_indexer_state(indexer_config, raw_state, state.applied_requests)
             .await
     }
 
@@ -159,17 +526,114 @@ impl IndexerStateManagerImpl {
         Ok(())
     }
 
+    /// Queues a request to be applied by the lifecycle, see `IndexerRequest`
+    async fn request(
+        &self,
+        indexer_config: &IndexerConfig,
+        request: IndexerRequest,
+        value: String,
+    ) -> anyhow::Result<()> {
+        self.redis_client
+            .set_indexer_request(indexer_config, request.field(), value)
+            .await
+    }
+
     pub async fn set_enabled(
         &self,
         indexer_config: &IndexerConfig,
         enabled: bool,
     ) -> anyhow::Result<()> {
-        let mut indexer_state = self.get_state(indexer_config).await?;
-        indexer_state.enabled = enabled;
+        self.request(
+            indexer_config,
+            IndexerRequest::Enabled,
+            serde_json::to_string(&enabled)?,
+        )
+        .await
+    }
 
-        self.set_state(indexer_config, indexer_state).await?;
+    pub async fn set_resume_policy(
+        &self,
+        indexer_config: &IndexerConfig,
+        resume_policy: ResumePolicy,
+    ) -> anyhow::Result<()> {
+        self.request(
+            indexer_config,
+            IndexerRequest::ResumePolicy,
+            serde_json::to_string(&resume_policy)?,
+        )
+        .await
+    }
 
-        Ok(())
+    pub async fn set_migration_strategy(
+        &self,
+        indexer_config: &IndexerConfig,
+        migration_strategy: MigrationStrategy,
+    ) -> anyhow::Result<()> {
+        self.request(
+            indexer_config,
+            IndexerRequest::MigrationStrategy,
+            serde_json::to_string(&migration_strategy)?,
+        )
+        .await
+    }
+
+    pub async fn set_rollout_strategy(
+        &self,
+        indexer_config: &IndexerConfig,
+        rollout_strategy: RolloutStrategy,
+    ) -> anyhow::Result<()> {
+        self.request(
+            indexer_config,
+            IndexerRequest::RolloutStrategy,
+            serde_json::to_string(&rollout_strategy)?,
+        )
+        .await
+    }
+
+    pub async fn request_reset(
+        &self,
+        indexer_config: &IndexerConfig,
+        block_height: u64,
+        clear_data: bool,
+    ) -> anyhow::Result<()> {
+        self.request(
+            indexer_config,
+            IndexerRequest::Reset,
+            serde_json::to_string(&ResetRequest {
+                block_height,
+                clear_data,
+            })?,
+        )
+        .await
+    }
+
+    pub async fn record_transition(
+        &self,
+        indexer_config: &IndexerConfig,
+        transition: IndexerTransition,
+    ) -> anyhow::Result<()> {
+        let raw_transition = serde_json::to_string(&transition)?;
+
+        self.redis_client
+            .append_indexer_history(indexer_config, raw_transition, HISTORY_MAX_LEN)
+            .await
+    }
+
+    /// Returns up to `count` of the most recent transitions, most recent first
+    pub async fn get_history(
+        &self,
+        indexer_config: &IndexerConfig,
+        count: usize,
+    ) -> anyhow::Result<Vec<IndexerTransition>> {
+        self.redis_client
+            .get_indexer_history(indexer_config, count)
+            .await?
+            .iter()
+            .map(|raw_transition| {
+                serde_json::from_str(raw_transition)
+                    .context(format!("Failed to deserialize {raw_transition}"))
+            })
+            .collect()
     }
 
     pub async fn list(&self) -> anyhow::Result<Vec<IndexerState>> {
@@ -206,15 +670,15 @@ mod tests {
             account_id: "morgs.near".parse().unwrap(),
             function_name: "test_migrate_stopping".to_string(),
             block_stream_synced_at: Some(200),
-            enabled: true,
             lifecycle_state: LifecycleState::Suspending,
+            ..Default::default()
         };
         let migrated_suspended = IndexerState {
             account_id: "morgs.near".parse().unwrap(),
             function_name: "test_migrate_stopped".to_string(),
             block_stream_synced_at: Some(200),
-            enabled: true,
             lifecycle_state: LifecycleState::Suspended,
+            ..Default::default()
         };
         mock_redis_client
             .expect_list_indexer_states()
@@ -266,6 +730,37 @@ mod tests {
         assert!(indexer_manager.list().await.is_err());
     }
 
+    #[tokio::test]
+    async fn get_indexer_history() {
+        let transition = IndexerTransition {
+            timestamp: 1000,
+            from: LifecycleState::Running,
+            to: LifecycleState::Suspending,
+            reason: "Indexer was disabled".to_string(),
+            registry_version: 2,
+        };
+
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_get_indexer_history::<IndexerConfig>()
+            .with(predicate::always(), predicate::eq(10))
+            .returning({
+                let transition = transition.clone();
+                move |_, _| Ok(vec![serde_json::to_string(&transition).unwrap()])
+            })
+            .once();
+
+        let indexer_manager = IndexerStateManagerImpl::new(redis_client);
+
+        assert_eq!(
+            indexer_manager
+                .get_history(&IndexerConfig::default(), 10)
+                .await
+                .unwrap(),
+            vec![transition]
+        );
+    }
+
     #[tokio::test]
     pub async fn disable_indexer() {
         let indexer_config = IndexerConfig {
@@ -285,28 +780,138 @@ mod tests {
 
         let mut redis_client = RedisClient::default();
         redis_client
-            .expect_get_indexer_state()
-            .with(predicate::eq(indexer_config.clone()))
+            .expect_get_indexer_state::<IndexerConfig>()
+            .never();
+        redis_client
+            .expect_set_indexer_state::<IndexerConfig>()
+            .never();
+        redis_client
+            .expect_set_indexer_request::<IndexerConfig>()
+            .with(
+                predicate::eq(indexer_config.clone()),
+                predicate::eq("enabled"),
+                predicate::eq("false".to_string()),
+            )
+            .returning(|_, _, _| Ok(()))
+            .once();
+
+        let indexer_manager = IndexerStateManagerImpl::new(redis_client);
+
+        indexer_manager
+            .set_enabled(&indexer_config, false)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn merges_and_acknowledges_pending_requests() {
+        let indexer_config = IndexerConfig::default();
+
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_get_indexer_state::<IndexerConfig>()
             .returning(|_| {
                 Ok(Some(
-                    serde_json::json!({ "account_id": "morgs.near", "function_name": "test", "block_stream_synced_at": 123, "enabled": true, "lifecycle_state": "Initializing" })
+                    serde_json::json!({ "account_id": "morgs.near", "function_name": "test", "block_stream_synced_at": 123, "enabled": true, "lifecycle_state": "Running" })
                         .to_string(),
                 ))
-            });
+            })
+            .once();
+        redis_client
+            .expect_get_indexer_requests::<IndexerConfig>()
+            .returning(|_| {
+                Ok(std::collections::HashMap::from([
+                    ("enabled".to_string(), "false".to_string()),
+                    ("resume_policy".to_string(), "\"Manual\"".to_string()),
+                    ("unknown".to_string(), "true".to_string()),
+                ]))
+            })
+            .once();
         redis_client
             .expect_set_indexer_state::<IndexerConfig>()
             .with(
                 predicate::always(),
-                predicate::eq("{\"account_id\":\"morgs.near\",\"function_name\":\"test\",\"block_stream_synced_at\":123,\"enabled\":false,\"lifecycle_state\":\"Initializing\"}".to_string()),
+                predicate::eq("{\"account_id\":\"morgs.near\",\"function_name\":\"test\",\"block_stream_synced_at\":123,\"enabled\":false,\"lifecycle_state\":\"Running\",\"resume_policy\":\"Manual\"}".to_string()),
+                predicate::function(|acknowledged_requests: &Vec<(String, String)>| {
+                    let mut acknowledged_requests = acknowledged_requests.clone();
+                    acknowledged_requests.sort();
+
+                    acknowledged_requests
+                        == vec![
+                            ("enabled".to_string(), "false".to_string()),
+                            ("resume_policy".to_string(), "\"Manual\"".to_string()),
+                        ]
+                }),
             )
-            .returning(|_, _| Ok(()))
+            .returning(|_, _, _| Ok(()))
             .once();
 
         let indexer_manager = IndexerStateManagerImpl::new(redis_client);
 
+        let state = indexer_manager.get_state(&indexer_config).await.unwrap();
+
+        assert!(!state.enabled);
+        assert_eq!(state.resume_policy, ResumePolicy::Manual);
+
         indexer_manager
-            .set_enabled(&indexer_config, false)
+            .set_state(&indexer_config, state)
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn queues_reset_requests() {
+        let indexer_config = IndexerConfig::default();
+
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_set_indexer_request::<IndexerConfig>()
+            .with(
+                predicate::always(),
+                predicate::eq("reset"),
+                predicate::eq("{\"block_height\":100,\"clear_data\":true}".to_string()),
+            )
+            .returning(|_, _, _| Ok(()))
+            .once();
+        redis_client
+            .expect_get_indexer_state::<IndexerConfig>()
+            .returning(|_| Ok(None))
+            .once();
+        redis_client
+            .expect_get_indexer_requests::<IndexerConfig>()
+            .returning(|_| {
+                Ok(std::collections::HashMap::from([(
+                    "reset".to_string(),
+                    "{\"block_height\":100,\"clear_data\":true}".to_string(),
+                )]))
+            })
+            .once();
+
+        let indexer_manager = IndexerStateManagerImpl::new(redis_client);
+
+        indexer_manager
+            .request_reset(&indexer_config, 100, true)
+            .await
+            .unwrap();
+
+        let state = indexer_manager.get_state(&indexer_config).await.unwrap();
+
+        assert_eq!(
+            state.reset,
+            Some(ResetState {
+                block_height: 100,
+                clear_data: true,
+                block_stream_cleared: false,
+                deprovisioning_task_id: None,
+                data_layer_deprovisioned: false,
+            })
+        );
+        assert_eq!(
+            state.applied_requests,
+            vec![(
+                "reset".to_string(),
+                "{\"block_height\":100,\"clear_data\":true}".to_string()
+            )]
+        );
+    }
 }
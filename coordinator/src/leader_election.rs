@@ -0,0 +1,180 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::redis::RedisClient;
+
+const LEASE_KEY: &str = "coordinator:leader";
+const LEASE_TTL: Duration = Duration::from_secs(10);
+const RENEW_INTERVAL: Duration = Duration::from_secs(3);
+const ACQUIRE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Ensures only a single Coordinator manages Indexers at any one time. Leadership is held via a
+/// lease in Redis which must be continuously renewed, each new leadership term is issued a
+/// fencing token, so that state writes from a stale leader are rejected.
+pub struct LeaderElection {
+    redis_client: RedisClient,
+    holder_id: String,
+    /// Fencing token of the current leadership term, 0 when not the leader
+    fencing_token: AtomicU64,
+}
+
+impl LeaderElection {
+    pub fn new(redis_client: RedisClient, holder_id: String) -> Self {
+        Self {
+            redis_client,
+            holder_id,
+            fencing_token: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.fencing_token.load(Ordering::SeqCst) > 0
+    }
+
+    /// Fencing token of the current leadership term, 0 when not the leader
+    pub fn get_fencing_token(&self) -> u64 {
+        self.fencing_token.load(Ordering::SeqCst)
+    }
+
+    async fn try_acquire(&self) -> anyhow::Result<bool> {
+        let fencing_token = self
+            .redis_client
+            .acquire_lease(LEASE_KEY, LEASE_TTL)
+            .await?;
+
+        if let Some(fencing_token) = fencing_token {
+            tracing::info!(
+                holder_id = self.holder_id,
+                fencing_token,
+                "Acquired leadership"
+            );
+
+            self.redis_client
+                .set_fence(Some((LEASE_KEY.to_string(), fencing_token)));
+            self.fencing_token.store(fencing_token, Ordering::SeqCst);
+        }
+
+        Ok(fencing_token.is_some())
+    }
+
+    async fn renew(&self) -> anyhow::Result<bool> {
+        let renewed = self
+            .redis_client
+            .renew_lease(
+                LEASE_KEY,
+                self.fencing_token.load(Ordering::SeqCst),
+                LEASE_TTL,
+            )
+            .await?;
+
+        if !renewed {
+            self.step_down();
+        }
+
+        Ok(renewed)
+    }
+
+    fn step_down(&self) {
+        tracing::warn!(holder_id = self.holder_id, "Lost leadership");
+
+        self.redis_client.set_fence(None);
+        self.fencing_token.store(0, Ordering::SeqCst);
+    }
+
+    /// Continuously acquires or renews leadership
+    pub async fn run(&self) {
+        loop {
+            if self.is_leader() {
+                match self.renew().await {
+                    Ok(_) => {}
+                    Err(error) => {
+                        tracing::warn!(?error, "Failed to renew leadership");
+
+                        // Without renewal the lease will expire and another Coordinator may
+                        // take over, so step down rather than risk overlapping leadership
+                        self.step_down();
+                    }
+                }
+
+                tokio::time::sleep(RENEW_INTERVAL).await;
+            } else {
+                if let Err(error) = self.try_acquire().await {
+                    tracing::warn!(?error, "Failed to acquire leadership");
+                }
+
+                tokio::time::sleep(ACQUIRE_INTERVAL).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockall::predicate::*;
+
+    #[tokio::test]
+    async fn acquires_leadership_with_fencing_token() {
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_acquire_lease()
+            .with(eq(LEASE_KEY), eq(LEASE_TTL))
+            .returning(|_, _| Ok(Some(5)))
+            .once();
+        redis_client
+            .expect_set_fence()
+            .with(eq(Some((LEASE_KEY.to_string(), 5))))
+            .return_const(())
+            .once();
+
+        let leader_election = LeaderElection::new(redis_client, "coordinator-1".to_string());
+
+        assert!(leader_election.try_acquire().await.unwrap());
+        assert!(leader_election.is_leader());
+    }
+
+    #[tokio::test]
+    async fn remains_follower_while_lease_is_held() {
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_acquire_lease()
+            .returning(|_, _| Ok(None))
+            .once();
+        redis_client.expect_set_fence().never();
+
+        let leader_election = LeaderElection::new(redis_client, "coordinator-1".to_string());
+
+        assert!(!leader_election.try_acquire().await.unwrap());
+        assert!(!leader_election.is_leader());
+    }
+
+    #[tokio::test]
+    async fn steps_down_when_lease_is_lost() {
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_acquire_lease()
+            .returning(|_, _| Ok(Some(5)));
+        redis_client
+            .expect_renew_lease()
+            .with(eq(LEASE_KEY), eq(5), eq(LEASE_TTL))
+            .returning(|_, _, _| Ok(false))
+            .once();
+        redis_client
+            .expect_set_fence()
+            .with(eq(Some((LEASE_KEY.to_string(), 5))))
+            .return_const(());
+        redis_client
+            .expect_set_fence()
+            .with(eq(None))
+            .return_const(())
+            .once();
+
+        let leader_election = LeaderElection::new(redis_client, "coordinator-1".to_string());
+
+        leader_election.try_acquire().await.unwrap();
+
+        assert!(!leader_election.renew().await.unwrap());
+        assert!(!leader_election.is_leader());
+    }
+}
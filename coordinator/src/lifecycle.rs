@@ -1,15 +1,144 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use near_primitives::types::AccountId;
+use rand::Rng;
+use registry_types::StartBlock;
 use tracing::{info, warn};
 
 use crate::handlers::block_streams::{BlockStreamStatus, BlockStreamsHandler};
-use crate::handlers::data_layer::DataLayerHandler;
+use crate::handlers::data_layer::{DataLayerHandler, Task, TaskStatus};
 use crate::handlers::executors::{ExecutorStatus, ExecutorsHandler};
 use crate::indexer_config::IndexerConfig;
-use crate::indexer_state::{IndexerState, IndexerStateManager};
+use crate::indexer_state::{
+    DeletionState, IndexerState, IndexerStateManager, IndexerTransition, MigrationState,
+    ProvisionedState, QuotaHold, RepairState, RestartState, ResumePolicy, RolloutState,
+    RolloutStrategy,
+};
+use crate::metrics;
+use crate::quota::QuotaScheduler;
 use crate::redis::{KeyProvider, RedisClient};
 use crate::registry::Registry;
 
 const LOOP_THROTTLE_MS: u64 = 1000;
-const RESTART_TIMEOUT_SECONDS: u64 = 600;
+pub const DELETION_GRACE_PERIOD: Duration = Duration::from_secs(3600);
+
+/// Controls how failed Indexers are retried while `Repairing`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairPolicy {
+    /// Number of failed attempts after which the Indexer is considered `Failed`
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RepairPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(600),
+        }
+    }
+}
+
+impl RepairPolicy {
+    fn get_delay(&self, attempts: u32) -> Duration {
+        get_backoff_delay(self.initial_delay, self.max_delay, attempts)
+    }
+
+    fn schedule(&self, error: String, attempts: u32) -> RepairState {
+        RepairState {
+            error,
+            attempts,
+            next_attempt_at: now_ms() + self.get_delay(attempts).as_millis() as u64,
+        }
+    }
+}
+
+/// Controls when unhealthy Block Streams and Executors are restarted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Number of restarts, without becoming stable in between, after which the Indexer is
+    /// considered to be crash looping and is suspended
+    pub max_restarts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_delay: Duration::from_secs(600),
+            max_delay: Duration::from_secs(6 * 3600),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RestartDecision {
+    Wait,
+    Restart,
+    Suspend,
+}
+
+impl RestartPolicy {
+    fn get_delay(&self, attempts: u32) -> Duration {
+        get_backoff_delay(self.initial_delay, self.max_delay, attempts)
+    }
+
+    /// Determines what to do with an unhealthy component, scheduling the next restart if needed
+    fn on_unhealthy(&self, restart: &mut Option<RestartState>) -> RestartDecision {
+        let now = now_ms();
+
+        match restart {
+            None => {
+                *restart = Some(RestartState {
+                    attempts: 0,
+                    next_restart_at: now + self.get_delay(0).as_millis() as u64,
+                });
+                RestartDecision::Wait
+            }
+            Some(restart) if now < restart.next_restart_at => RestartDecision::Wait,
+            Some(restart) if restart.attempts >= self.max_restarts => RestartDecision::Suspend,
+            Some(restart) => {
+                restart.attempts += 1;
+                restart.next_restart_at = now + self.get_delay(restart.attempts).as_millis() as u64;
+                RestartDecision::Restart
+            }
+        }
+    }
+
+    /// Forgets previous restarts once the component has remained healthy until its next
+    /// scheduled restart
+    fn on_healthy(&self, restart: &mut Option<RestartState>) {
+        if let Some(state) = restart {
+            if now_ms() >= state.next_restart_at {
+                *restart = None;
+            }
+        }
+    }
+}
+
+/// Exponential backoff with jitter, the returned delay is within `[delay / 2, delay]`
+fn get_backoff_delay(initial_delay: Duration, max_delay: Duration, attempts: u32) -> Duration {
+    let delay = initial_delay
+        .saturating_mul(2_u32.saturating_pow(attempts))
+        .min(max_delay);
+
+    let half_delay_ms = (delay.as_millis() / 2) as u64;
+    let jitter_ms = rand::thread_rng().gen_range(0..=half_delay_ms);
+
+    Duration::from_millis(half_delay_ms + jitter_ms)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before unix epoch")
+        .as_millis() as u64
+}
 
 /// Represents the different lifecycle states of an Indexer
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
@@ -24,8 +153,18 @@ pub enum LifecycleState {
     /// Indexer is functional, Block Stream and Executors are continouously monitored to ensure
     /// they are running the latest version of the Indexer.
     ///
+    /// Unhealthy Block Streams and Executors are restarted according to the `RestartPolicy`,
+    /// without blocking other transitions.
+    ///
     /// Transitions:
+    /// - `Resetting` if a reset is requested
     /// - `Suspending` if suspended
+    /// - `Suspending` if the account has exceeded its quota of active Indexers
+    /// - `Running` without starting the Block Stream and Executor, if a backfill is queued by the
+    /// `QuotaPolicy`
+    /// - `RollingOut` if a new version is published, and the `RolloutStrategy` is `BlueGreen`
+    /// - `Migrating` if the schema has changed
+    /// - `Suspending` if Block Stream or Executor keeps failing after restarts
     /// - `Running` if Block Stream or Executor fails to synchronise, essentially triggering a
     /// retry
     /// - `Running` on success
@@ -39,22 +178,117 @@ pub enum LifecycleState {
     /// Indexer is suspended, Block Stream and Executors are not running.
     ///
     /// Transitions:
-    /// - `Running` if unsuspended
+    /// - `Resetting` if a reset is requested
+    /// - `Running` if unsuspended, and the account is within its quota of active Indexers
+    /// - `Running` if a new version is published, and the `ResumePolicy` allows it
     Suspended,
-    /// Indexer is in a bad state, currently requires manual intervention, but should eventually
-    /// self heal. This is a dead-end state
+    /// Indexer is in a bad state, the cause is recorded in `IndexerState::repair`. Reparation is
+    /// retried with exponential backoff according to the `RepairPolicy`.
     ///
     /// Transitions:
-    /// - `Repairing` continuously
-    Repairing, // TODO Add `error` to enable reparation
-    /// Indexer is being deleted, all resources are being cleaned up
+    /// - `Initializing` on success
+    /// - `Repairing` on failure, or while waiting for the next attempt
+    /// - `Failed` once attempts are exhausted
+    Repairing,
+    /// Indexer is being deleted. Block Stream and Executors are stopped immediately, all other
+    /// resources, i.e. Data Layer and Redis keys, are cleaned up after a grace period. Progress
+    /// is recorded in `IndexerState::deletion` so completed steps are not repeated.
     ///
     /// Transitions:
-    /// - `Deleting` on failure, triggering a retry
+    /// - `Initializing` if restored within the grace period
+    /// - `Deleting` on failure, triggering a retry, or during the grace period
     /// - `Deleted` on success
     Deleting,
     /// Indexer is deleted, all resources are cleaned up, lifecycle manager will exit
     Deleted,
+    /// Indexer could not be repaired and requires manual intervention. This is a dead-end state
+    ///
+    /// Transitions:
+    /// - `Deleting` if deleted
+    /// - `Failed` continuously
+    Failed,
+    /// Indexer is being reset to the block height recorded in `IndexerState::reset`. Block Stream
+    /// and Executors are stopped, the Redis Stream is cleared, and optionally the Data Layer is
+    /// reprovisioned. Progress is recorded so completed steps are not repeated.
+    ///
+    /// Transitions:
+    /// - `Deleting` if deleted
+    /// - `Resetting` on failure, triggering a retry
+    /// - `Running` on success, resuming from the requested block height
+    /// - `Suspended` on success, if the Indexer is disabled
+    Resetting,
+    /// Data Layer is being migrated to a new schema, according to the `MigrationStrategy`. Block
+    /// Stream and Executors are stopped until the migration completes, progress is recorded in
+    /// `IndexerState::migration`.
+    ///
+    /// Transitions:
+    /// - `Deleting` if deleted
+    /// - `Migrating` while the migration is in progress, or on failure to start it
+    /// - `MigrationFailed` if the migration fails
+    /// - `Running` on success
+    /// - `Suspended` on success, if the Indexer is disabled
+    Migrating,
+    /// Data Layer could not be migrated to the new schema, e.g. because changes were not additive.
+    /// The cause is recorded in `IndexerState::migration`.
+    ///
+    /// Transitions:
+    /// - `Deleting` if deleted
+    /// - `Migrating` if the schema or `MigrationStrategy` is changed, retrying the migration
+    /// - `MigrationFailed` otherwise
+    MigrationFailed,
+    /// A new version is being run alongside the live one, see `IndexerState::rollout`. The shadow
+    /// Data Layer is provisioned, and the shadow Block Stream and Executor process from the start
+    /// block, until they catch up with the live version. The live version continues to run,
+    /// but is not monitored.
+    ///
+    /// Transitions:
+    /// - `Deleting`, `Resetting`, or `Suspending` if requested, after tearing down the shadow
+    /// - `RollingOut` while catching up, or on failure, triggering a retry
+    /// - `RollingOut` if a newer version is published, restarting the rollout
+    /// - `Running` if the rollout fails, after tearing down the shadow, so that the version is
+    /// rolled out in place instead
+    /// - `Promoting` once the shadow has caught up with the live version
+    RollingOut,
+    /// The caught up shadow is replacing the live version. Once the shadow Executor has drained its
+    /// Redis Stream, both versions are stopped and the shadow Data Layer is swapped in place of the
+    /// live one, after which the live Block Stream continues from where the shadow left off.
+    ///
+    /// Transitions:
+    /// - `RollingOut` if deleted or a newer version is published before the swap has started
+    /// - `Promoting` while waiting for the shadow Executor or the swap, or on failure, triggering
+    /// a retry
+    /// - `Running` on success, running the new version against the swapped Data Layer
+    /// - `Suspended` on success, if the Indexer is disabled
+    Promoting,
+}
+
+/// Returns the Indexers which require a lifecycle manager: all non-deleted Indexers, along with
+/// deleted Indexers which have not finished `Deleting`, so that persisted deletion progress is
+/// resumed after a restart or reassignment
+pub async fn list_managed_indexers(
+    registry: &Registry,
+    state_manager: &IndexerStateManager,
+) -> anyhow::Result<Vec<IndexerConfig>> {
+    let mut indexers: Vec<IndexerConfig> = registry.fetch().await?.iter().cloned().collect();
+
+    for state in state_manager.list().await? {
+        if state.lifecycle_state != LifecycleState::Deleting
+            || indexers
+                .iter()
+                .any(|config| config.get_full_name() == state.prefix())
+        {
+            continue;
+        }
+
+        if let Some(config) = registry
+            .fetch_indexer(&state.account_id, &state.function_name)
+            .await?
+        {
+            indexers.push(config);
+        }
+    }
+
+    Ok(indexers)
 }
 
 pub struct LifecycleManager<'a> {
@@ -65,6 +299,10 @@ pub struct LifecycleManager<'a> {
     registry: &'a Registry,
     state_manager: &'a IndexerStateManager,
     redis_client: &'a RedisClient,
+    repair_policy: RepairPolicy,
+    deletion_grace_period: Duration,
+    restart_policy: RestartPolicy,
+    quota_scheduler: Option<Arc<QuotaScheduler>>,
 }
 
 impl<'a> LifecycleManager<'a> {
@@ -85,30 +323,156 @@ impl<'a> LifecycleManager<'a> {
             registry,
             state_manager,
             redis_client,
+            repair_policy: RepairPolicy::default(),
+            deletion_grace_period: DELETION_GRACE_PERIOD,
+            restart_policy: RestartPolicy::default(),
+            quota_scheduler: None,
         }
     }
 
+    pub fn with_repair_policy(mut self, repair_policy: RepairPolicy) -> Self {
+        self.repair_policy = repair_policy;
+        self
+    }
+
+    pub fn with_deletion_grace_period(mut self, deletion_grace_period: Duration) -> Self {
+        self.deletion_grace_period = deletion_grace_period;
+        self
+    }
+
+    pub fn with_quota_scheduler(mut self, quota_scheduler: Arc<QuotaScheduler>) -> Self {
+        self.quota_scheduler = Some(quota_scheduler);
+        self
+    }
+
+    fn get_quota_hold(&self, config: &IndexerConfig, state: &IndexerState) -> Option<QuotaHold> {
+        self.quota_scheduler
+            .as_ref()
+            .and_then(|quota_scheduler| quota_scheduler.get_hold(config, state))
+    }
+
     #[tracing::instrument(name = "initializing", skip_all)]
     async fn handle_initializing(
         &self,
         config: &IndexerConfig,
-        _state: &IndexerState,
+        state: &mut IndexerState,
     ) -> LifecycleState {
         if config.is_deleted() {
             return LifecycleState::Deleting;
         }
 
-        if self
+        match self.poll_provisioning(config, state).await {
+            Ok(true) => LifecycleState::Running,
+            Ok(false) => LifecycleState::Initializing,
+            Err(error) => {
+                warn!(?error, "Failed to provision data layer");
+                state.repair = Some(self.repair_policy.schedule(format!("{error:#}"), 0));
+                LifecycleState::Repairing
+            }
+        }
+    }
+
+    /// Advances provisioning of the Data Layer by a single step, returning whether it is
+    /// provisioned. The in-flight task is recorded in `IndexerState::provisioned_state` so that it
+    /// is polled, rather than started again, on subsequent iterations.
+    async fn poll_provisioning(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> anyhow::Result<bool> {
+        let in_flight = matches!(
+            state.provisioned_state,
+            Some(ProvisionedState::Provisioning { .. })
+        );
+
+        let provisioned = self
+            .poll_data_layer_provisioning(config, &mut state.provisioned_state)
+            .await?;
+
+        if !in_flight {
+            state.provisioned_schema = Some(config.schema.clone());
+        }
+
+        Ok(provisioned)
+    }
+
+    /// Advances provisioning of the Data Layer of `config`, recording progress in
+    /// `provisioned_state`, see `poll_provisioning`
+    async fn poll_data_layer_provisioning(
+        &self,
+        config: &IndexerConfig,
+        provisioned_state: &mut Option<ProvisionedState>,
+    ) -> anyhow::Result<bool> {
+        if let Some(ProvisionedState::Provisioning { task_id }) = &*provisioned_state {
+            match self
+                .data_layer_handler
+                .get_task_status(task_id.clone())
+                .await?
+            {
+                TaskStatus::Pending => return Ok(false),
+                TaskStatus::Complete => {
+                    info!(task_id, "Provisioning task completed");
+                    *provisioned_state = Some(ProvisionedState::Provisioned);
+                    return Ok(true);
+                }
+                TaskStatus::Failed | TaskStatus::Unspecified => {
+                    *provisioned_state = Some(ProvisionedState::Failed);
+                    anyhow::bail!("Provisioning task failed");
+                }
+            }
+        }
+
+        match self.data_layer_handler.start_provisioning(config).await? {
+            Some(task_id) => {
+                *provisioned_state = Some(ProvisionedState::Provisioning { task_id });
+                Ok(false)
+            }
+            None => {
+                *provisioned_state = Some(ProvisionedState::Provisioned);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Advances deprovisioning of the Data Layer by a single step, starting the task if
+    /// `task_id` is not yet set, or checking the status of the existing one otherwise. Returns
+    /// `true` once the Data Layer is deprovisioned.
+    async fn poll_data_layer_deprovisioning(
+        &self,
+        account_id: &AccountId,
+        function_name: &str,
+        task_id: &mut Option<String>,
+    ) -> anyhow::Result<bool> {
+        if let Some(existing_task_id) = task_id.clone() {
+            match self
+                .data_layer_handler
+                .get_task_status(existing_task_id.clone())
+                .await?
+            {
+                TaskStatus::Pending => return Ok(false),
+                TaskStatus::Complete => {
+                    info!(task_id = existing_task_id, "Deprovisioning task completed");
+                    *task_id = None;
+                    return Ok(true);
+                }
+                TaskStatus::Failed | TaskStatus::Unspecified => {
+                    *task_id = None;
+                    anyhow::bail!("Deprovisioning task failed");
+                }
+            }
+        }
+
+        match self
             .data_layer_handler
-            .ensure_provisioned(config)
-            .await
-            .is_err()
+            .start_deprovisioning(account_id.clone(), function_name.to_string())
+            .await?
         {
-            tracing::warn!("Failed to provision data layer");
-            return LifecycleState::Repairing;
+            Some(new_task_id) => {
+                *task_id = Some(new_task_id);
+                Ok(false)
+            }
+            None => Ok(true),
         }
-
-        LifecycleState::Running
     }
 
     #[tracing::instrument(name = "running", skip_all)]
@@ -121,10 +485,51 @@ impl<'a> LifecycleManager<'a> {
             return LifecycleState::Deleting;
         }
 
+        if state.reset.is_some() {
+            return LifecycleState::Resetting;
+        }
+
         if !state.enabled {
             return LifecycleState::Suspending;
         }
 
+        state.quota_hold = self.get_quota_hold(config, state);
+
+        match state.quota_hold {
+            Some(QuotaHold::Suspended { .. }) => return LifecycleState::Suspending,
+            // Only new versions are queued, the Block Stream and Executor of the previous version,
+            // if any, are left as is until admitted
+            Some(QuotaHold::Queued { .. }) => return LifecycleState::Running,
+            None => {}
+        }
+
+        // Provisioned before schemas were tracked, use the current schema as the baseline
+        let provisioned_schema = state
+            .provisioned_schema
+            .get_or_insert_with(|| config.schema.clone());
+
+        let registry_version = config.get_registry_version();
+
+        // Versions which don't reprocess from a start block have nothing to catch up on, so are
+        // rolled out in place. Failed rollouts are also rolled out in place.
+        let should_roll_out = state.rollout_strategy == RolloutStrategy::BlueGreen
+            && matches!(config.start_block, StartBlock::Height(_))
+            && state
+                .block_stream_synced_at
+                .is_some_and(|synced_at| synced_at != registry_version)
+            && !state
+                .rollout
+                .as_ref()
+                .is_some_and(|rollout| rollout.version == registry_version);
+
+        if should_roll_out {
+            return LifecycleState::RollingOut;
+        }
+
+        if *provisioned_schema != config.schema {
+            return LifecycleState::Migrating;
+        }
+
         let stream_status = match self
             .block_streams_handler
             .get_status(config, state.block_stream_synced_at)
@@ -133,17 +538,34 @@ impl<'a> LifecycleManager<'a> {
             Ok(status) => status,
             Err(error) => {
                 warn!(?error, "Failed to get block stream status");
+                metrics::BLOCK_STREAM_SYNC_FAILURES_COUNT
+                    .with_label_values(&[&config.get_full_name()])
+                    .inc();
                 return LifecycleState::Running;
             }
         };
 
         if let Err(error) = match stream_status {
-            BlockStreamStatus::Active => Ok(()),
+            BlockStreamStatus::Active => {
+                self.restart_policy
+                    .on_healthy(&mut state.block_stream_restart);
+                Ok(())
+            }
             BlockStreamStatus::Inactive => self.block_streams_handler.resume(config).await,
             BlockStreamStatus::Outdated => self.block_streams_handler.reconfigure(config).await,
             BlockStreamStatus::Unhealthy => {
-                tokio::time::sleep(tokio::time::Duration::from_secs(RESTART_TIMEOUT_SECONDS)).await;
-                self.block_streams_handler.restart(config).await
+                match self
+                    .restart_policy
+                    .on_unhealthy(&mut state.block_stream_restart)
+                {
+                    RestartDecision::Wait => Ok(()),
+                    RestartDecision::Restart => self.block_streams_handler.restart(config).await,
+                    RestartDecision::Suspend => {
+                        tracing::error!("Block stream is crash looping, suspending indexer");
+                        state.enabled = false;
+                        return LifecycleState::Suspending;
+                    }
+                }
             }
             BlockStreamStatus::NotStarted => {
                 self.block_streams_handler
@@ -152,6 +574,9 @@ impl<'a> LifecycleManager<'a> {
             }
         } {
             warn!(?error, "Failed to synchronise block stream, retrying...");
+            metrics::BLOCK_STREAM_SYNC_FAILURES_COUNT
+                .with_label_values(&[&config.get_full_name()])
+                .inc();
             return LifecycleState::Running;
         }
 
@@ -161,20 +586,39 @@ impl<'a> LifecycleManager<'a> {
             Ok(status) => status,
             Err(error) => {
                 warn!(?error, "Failed to synchronise executor");
+                metrics::EXECUTOR_SYNC_FAILURES_COUNT
+                    .with_label_values(&[&config.get_full_name()])
+                    .inc();
                 return LifecycleState::Running;
             }
         };
 
         if let Err(error) = match executor_status {
-            ExecutorStatus::Active => Ok(()),
+            ExecutorStatus::Active => {
+                self.restart_policy.on_healthy(&mut state.executor_restart);
+                Ok(())
+            }
             ExecutorStatus::Inactive => self.executors_handler.start(config).await,
             ExecutorStatus::Outdated => self.executors_handler.restart(config).await,
             ExecutorStatus::Unhealthy => {
-                tokio::time::sleep(tokio::time::Duration::from_secs(RESTART_TIMEOUT_SECONDS)).await;
-                self.executors_handler.restart(config).await
+                match self
+                    .restart_policy
+                    .on_unhealthy(&mut state.executor_restart)
+                {
+                    RestartDecision::Wait => Ok(()),
+                    RestartDecision::Restart => self.executors_handler.restart(config).await,
+                    RestartDecision::Suspend => {
+                        tracing::error!("Executor is crash looping, suspending indexer");
+                        state.enabled = false;
+                        return LifecycleState::Suspending;
+                    }
+                }
             }
         } {
             warn!(?error, "Failed to synchronise executor, retrying...");
+            metrics::EXECUTOR_SYNC_FAILURES_COUNT
+                .with_label_values(&[&config.get_full_name()])
+                .inc();
             return LifecycleState::Running;
         }
 
@@ -182,7 +626,11 @@ impl<'a> LifecycleManager<'a> {
     }
 
     #[tracing::instrument(name = "suspending", skip_all)]
-    async fn handle_suspending(&self, config: &IndexerConfig) -> LifecycleState {
+    async fn handle_suspending(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> LifecycleState {
         if config.is_deleted() {
             return LifecycleState::Deleting;
         }
@@ -205,6 +653,8 @@ impl<'a> LifecycleManager<'a> {
             return LifecycleState::Suspending;
         }
 
+        state.suspended_at_version = Some(config.get_registry_version());
+
         LifecycleState::Suspended
     }
 
@@ -212,19 +662,57 @@ impl<'a> LifecycleManager<'a> {
     async fn handle_suspended(
         &self,
         config: &IndexerConfig,
-        state: &IndexerState,
+        state: &mut IndexerState,
     ) -> LifecycleState {
         if config.is_deleted() {
             return LifecycleState::Deleting;
         }
 
-        // TODO Transistion to `Running` on config update
+        if state.reset.is_some() {
+            return LifecycleState::Resetting;
+        }
 
         if state.enabled {
+            state.quota_hold = self.get_quota_hold(config, state);
+
+            if matches!(state.quota_hold, Some(QuotaHold::Suspended { .. })) {
+                return LifecycleState::Suspended;
+            }
+
             tracing::debug!("Suspended indexer was reactivated");
+            state.suspended_at_version = None;
+            state.block_stream_restart = None;
+            state.executor_restart = None;
             return LifecycleState::Running;
         }
 
+        // Disabled Indexers do not count towards the quota
+        state.quota_hold = None;
+
+        let registry_version = config.get_registry_version();
+
+        match state.suspended_at_version {
+            // Suspended before versions were tracked, use the current version as the baseline
+            None => {
+                state.suspended_at_version = Some(registry_version);
+            }
+            Some(suspended_at_version)
+                if registry_version > suspended_at_version
+                    && state.resume_policy == ResumePolicy::OnUpdate =>
+            {
+                info!(
+                    suspended_at_version,
+                    registry_version, "Resuming suspended indexer after update"
+                );
+                state.enabled = true;
+                state.suspended_at_version = None;
+                state.block_stream_restart = None;
+                state.executor_restart = None;
+                return LifecycleState::Running;
+            }
+            Some(_) => {}
+        }
+
         LifecycleState::Suspended
     }
 
@@ -232,207 +720,3369 @@ impl<'a> LifecycleManager<'a> {
     async fn handle_repairing(
         &self,
         config: &IndexerConfig,
-        _state: &IndexerState,
+        state: &mut IndexerState,
     ) -> LifecycleState {
         if config.is_deleted() {
             return LifecycleState::Deleting;
         }
 
-        // TODO Add more robust error handling, for now just stop
-        LifecycleState::Repairing
+        // An in-flight attempt is polled each iteration, only new attempts are delayed
+        let in_flight = matches!(
+            state.provisioned_state,
+            Some(ProvisionedState::Provisioning { .. })
+        );
+
+        let attempts = match &state.repair {
+            Some(repair) if !in_flight && now_ms() < repair.next_attempt_at => {
+                return LifecycleState::Repairing
+            }
+            Some(repair) => repair.attempts,
+            None => 0,
+        };
+
+        if !in_flight {
+            info!(attempts, "Attempting repair");
+        }
+
+        match self.poll_provisioning(config, state).await {
+            Ok(true) => {}
+            Ok(false) => return LifecycleState::Repairing,
+            Err(error) => {
+                let attempts = attempts + 1;
+
+                if attempts >= self.repair_policy.max_attempts {
+                    tracing::error!(?error, attempts, "Failed to repair indexer, giving up");
+                    state.repair = Some(RepairState {
+                        error: format!("{error:#}"),
+                        attempts,
+                        next_attempt_at: 0,
+                    });
+                    return LifecycleState::Failed;
+                }
+
+                warn!(?error, attempts, "Failed to repair indexer, retrying...");
+                state.repair = Some(self.repair_policy.schedule(format!("{error:#}"), attempts));
+                return LifecycleState::Repairing;
+            }
+        }
+
+        state.repair = None;
+
+        LifecycleState::Initializing
     }
 
-    #[tracing::instrument(name = "deleting", skip_all)]
-    async fn handle_deleting(&self, state: &IndexerState) -> LifecycleState {
+    #[tracing::instrument(name = "failed", skip_all)]
+    async fn handle_failed(&self, config: &IndexerConfig) -> LifecycleState {
+        if config.is_deleted() {
+            return LifecycleState::Deleting;
+        }
+
+        LifecycleState::Failed
+    }
+
+    #[tracing::instrument(name = "resetting", skip_all)]
+    async fn handle_resetting(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> LifecycleState {
+        if config.is_deleted() {
+            state.reset = None;
+            return LifecycleState::Deleting;
+        }
+
+        let Some(reset) = state.reset.as_mut() else {
+            return LifecycleState::Running;
+        };
+
         if let Err(error) = self
             .block_streams_handler
-            .stop_if_needed(state.account_id.clone(), state.function_name.clone())
+            .stop_if_needed(config.account_id.clone(), config.function_name.clone())
             .await
         {
-            warn!(?error, "Failed to stop block stream");
+            warn!(?error, "Failed to stop block stream, retrying...");
+            return LifecycleState::Resetting;
         }
 
         if let Err(error) = self
             .executors_handler
-            .stop_if_needed(state.account_id.clone(), state.function_name.clone())
+            .stop_if_needed(config.account_id.clone(), config.function_name.clone())
             .await
         {
-            warn!(?error, "Failed to stop executor");
+            warn!(?error, "Failed to stop executor, retrying...");
+            return LifecycleState::Resetting;
         }
 
-        tracing::error!("Temporarily preventing indexer deprovision due to service instability");
-        LifecycleState::Deleted
-
-        // if self.state_manager.delete_state(state).await.is_err() {
-        //     // Retry
-        //     return LifecycleState::Deleting;
-        // }
-        //
-        // info!("Clearing block stream");
-        //
-        // if self
-        //     .redis_client
-        //     .del(state.get_redis_stream_key())
-        //     .await
-        //     .is_err()
-        // {
-        //     // Retry
-        //     return LifecycleState::Deleting;
-        // }
-        //
-        // if self
-        //     .data_layer_handler
-        //     .ensure_deprovisioned(state.account_id.clone(), state.function_name.clone())
-        //     .await
-        //     .is_err()
-        // {
-        //     return LifecycleState::Deleted;
-        // }
-        //
-        // LifecycleState::Deleted
-    }
+        if !reset.block_stream_cleared {
+            info!(block_height = reset.block_height, "Resetting block stream");
 
-    pub async fn handle_transitions(&self, first_iteration: bool) -> bool {
-        let config = match self
-            .registry
-            .fetch_indexer(
-                &self.initial_config.account_id,
-                &self.initial_config.function_name,
-            )
-            .await
-        {
-            Ok(Some(config)) => config,
-            Ok(None) => {
-                warn!("No matching indexer config was found");
-                return false;
-            }
-            Err(error) => {
-                warn!(?error, "Failed to fetch config");
-                return false;
+            if let Err(error) = self.redis_client.clear_block_stream(config).await {
+                warn!(?error, "Failed to clear block stream, retrying...");
+                return LifecycleState::Resetting;
             }
-        };
 
-        let mut state = match self.state_manager.get_state(&self.initial_config).await {
-            Ok(state) => state,
-            Err(error) => {
-                warn!(?error, "Failed to get state");
-                return false;
+            // Block Streams resume from the block after the last published block
+            if let Err(error) = self
+                .redis_client
+                .set(
+                    config.get_last_published_block_key(),
+                    reset.block_height.saturating_sub(1),
+                )
+                .await
+            {
+                warn!(?error, "Failed to set last published block, retrying...");
+                return LifecycleState::Resetting;
             }
-        };
 
-        if first_iteration {
-            info!("Initial lifecycle state: {:?}", state.lifecycle_state);
+            reset.block_stream_cleared = true;
         }
 
-        let desired_lifecycle_state = match state.lifecycle_state {
-            LifecycleState::Initializing => self.handle_initializing(&config, &state).await,
-            LifecycleState::Running => self.handle_running(&config, &mut state).await,
-            LifecycleState::Suspending => self.handle_suspending(&config).await,
-            LifecycleState::Suspended => self.handle_suspended(&config, &state).await,
-            LifecycleState::Repairing => self.handle_repairing(&config, &state).await,
-            LifecycleState::Deleting => self.handle_deleting(&state).await,
-            LifecycleState::Deleted => LifecycleState::Deleted,
-        };
-
-        if desired_lifecycle_state != state.lifecycle_state {
-            info!(
-                "Transitioning lifecycle state: {:?} -> {:?}",
-                state.lifecycle_state, desired_lifecycle_state,
-            );
-        }
+        let block_height = reset.block_height;
 
-        if desired_lifecycle_state == LifecycleState::Deleted {
-            return true;
-        }
+        if reset.clear_data {
+            if !reset.data_layer_deprovisioned {
+                info!("Clearing data layer");
 
-        state.lifecycle_state = desired_lifecycle_state;
+                match self
+                    .poll_data_layer_deprovisioning(
+                        &config.account_id,
+                        &config.function_name,
+                        &mut reset.deprovisioning_task_id,
+                    )
+                    .await
+                {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        state.provisioned_state = reset
+                            .deprovisioning_task_id
+                            .clone()
+                            .map(|task_id| ProvisionedState::Deprovisioning { task_id });
+                        return LifecycleState::Resetting;
+                    }
+                    Err(error) => {
+                        warn!(?error, "Failed to deprovision data layer, retrying...");
+                        return LifecycleState::Resetting;
+                    }
+                }
 
-        loop {
-            match self
-                .state_manager
-                .set_state(&self.initial_config, state.clone())
-                .await
-            {
-                Ok(_) => break,
-                Err(e) => {
-                    warn!("Failed to set state: {:?}. Retrying...", e);
+                reset.data_layer_deprovisioned = true;
+                state.provisioned_state = Some(ProvisionedState::Unprovisioned);
+            }
 
-                    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+            match self.poll_provisioning(config, state).await {
+                Ok(true) => {}
+                Ok(false) => return LifecycleState::Resetting,
+                Err(error) => {
+                    warn!(?error, "Failed to provision data layer, retrying...");
+                    return LifecycleState::Resetting;
                 }
             }
         }
 
-        false
-    }
+        info!(block_height, "Indexer reset");
 
-    #[tracing::instrument(
-        name = "lifecycle_manager",
-        skip(self),
-        fields(
-            account_id = self.initial_config.account_id.as_str(),
-            function_name = self.initial_config.function_name.as_str()
-        )
-    )]
-    pub async fn run(&self) {
-        let mut first_iteration = true;
+        // Mark the Block Stream as synced with the current version so that it is resumed from the
+        // last published block, rather than started from the configured start block
+        state.block_stream_synced_at = Some(config.get_registry_version());
+        state.block_stream_restart = None;
+        state.executor_restart = None;
+        state.reset = None;
 
-        loop {
-            tokio::time::sleep(std::time::Duration::from_millis(LOOP_THROTTLE_MS)).await;
-            let should_exit = self.handle_transitions(first_iteration).await;
+        if state.enabled {
+            LifecycleState::Running
+        } else {
+            LifecycleState::Suspended
+        }
+    }
+
+    #[tracing::instrument(name = "migrating", skip_all)]
+    async fn handle_migrating(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> LifecycleState {
+        if config.is_deleted() {
+            state.migration = None;
+            return LifecycleState::Deleting;
+        }
+
+        if let Err(error) = self
+            .block_streams_handler
+            .stop_if_needed(config.account_id.clone(), config.function_name.clone())
+            .await
+        {
+            warn!(?error, "Failed to stop block stream, retrying...");
+            return LifecycleState::Migrating;
+        }
+
+        if let Err(error) = self
+            .executors_handler
+            .stop_if_needed(config.account_id.clone(), config.function_name.clone())
+            .await
+        {
+            warn!(?error, "Failed to stop executor, retrying...");
+            return LifecycleState::Migrating;
+        }
+
+        let previous_schema = state.provisioned_schema.clone().unwrap_or_default();
+        let strategy = state.migration_strategy;
+
+        let migration = state.migration.get_or_insert_with(|| MigrationState {
+            schema: config.schema.clone(),
+            strategy,
+            task_id: None,
+            error: None,
+        });
+
+        let Some(task_id) = migration.task_id.clone() else {
+            // Migrate to the latest schema, it may have changed since the migration was requested
+            migration.schema = config.schema.clone();
+
+            match self
+                .data_layer_handler
+                .start_migration(config, previous_schema, migration.strategy)
+                .await
+            {
+                Ok(task_id) => {
+                    info!(?task_id, strategy = ?migration.strategy, "Started migration task");
+                    migration.task_id = Some(task_id);
+                }
+                Err(error) => warn!(?error, "Failed to start migration task, retrying..."),
+            }
+
+            return LifecycleState::Migrating;
+        };
+
+        match self.data_layer_handler.get_task(task_id).await {
+            Ok(Task {
+                status: TaskStatus::Pending,
+                ..
+            }) => LifecycleState::Migrating,
+            Ok(Task {
+                status: TaskStatus::Complete,
+                ..
+            }) => {
+                info!("Schema migrated");
+
+                state.provisioned_schema = Some(migration.schema.clone());
+                state.migration = None;
+                state.block_stream_restart = None;
+                state.executor_restart = None;
+
+                if state.enabled {
+                    LifecycleState::Running
+                } else {
+                    LifecycleState::Suspended
+                }
+            }
+            Ok(Task {
+                status: TaskStatus::Failed | TaskStatus::Unspecified,
+                error,
+            }) => {
+                tracing::error!(strategy = ?migration.strategy, ?error, "Schema migration failed");
+                migration.error =
+                    Some(error.unwrap_or_else(|| {
+                        format!("{:?} migration task failed", migration.strategy)
+                    }));
+
+                LifecycleState::MigrationFailed
+            }
+            Err(error) => {
+                warn!(?error, "Failed to get migration task status, retrying...");
+                LifecycleState::Migrating
+            }
+        }
+    }
+
+    #[tracing::instrument(name = "migration_failed", skip_all)]
+    async fn handle_migration_failed(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> LifecycleState {
+        if config.is_deleted() {
+            state.migration = None;
+            return LifecycleState::Deleting;
+        }
+
+        let should_retry = match &state.migration {
+            Some(migration) => {
+                migration.schema != config.schema || migration.strategy != state.migration_strategy
+            }
+            None => true,
+        };
+
+        if should_retry {
+            info!("Retrying schema migration");
+            state.migration = None;
+            return LifecycleState::Migrating;
+        }
+
+        LifecycleState::MigrationFailed
+    }
+
+    /// Stops the shadow Block Stream and Executor, and removes the shadow Data Layer and Redis
+    /// keys. Deprovisioning is polled via `RolloutState::deprovisioning_task_id`, so this returns
+    /// whether the shadow has been fully torn down.
+    async fn teardown_shadow(
+        &self,
+        shadow_config: &IndexerConfig,
+        rollout: &mut RolloutState,
+    ) -> anyhow::Result<bool> {
+        self.block_streams_handler
+            .stop_if_needed(
+                shadow_config.account_id.clone(),
+                shadow_config.function_name.clone(),
+            )
+            .await?;
+
+        self.executors_handler
+            .stop_if_needed(
+                shadow_config.account_id.clone(),
+                shadow_config.function_name.clone(),
+            )
+            .await?;
+
+        if rollout.provisioned_state.is_some() {
+            if !self
+                .poll_data_layer_deprovisioning(
+                    &shadow_config.account_id,
+                    &shadow_config.function_name,
+                    &mut rollout.deprovisioning_task_id,
+                )
+                .await?
+            {
+                return Ok(false);
+            }
+
+            rollout.provisioned_state = None;
+        }
+
+        for key in [
+            shadow_config.get_redis_stream_key(),
+            shadow_config.get_last_published_block_key(),
+        ] {
+            self.redis_client.del(key).await?;
+        }
+
+        rollout.shadow_started = false;
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(name = "rolling_out", skip_all)]
+    async fn handle_rolling_out(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> LifecycleState {
+        let shadow_config = config.get_shadow_config();
+        let registry_version = config.get_registry_version();
+
+        if let Some(rollout) = state.rollout.as_mut() {
+            let is_failed = rollout.error.is_some();
+            let is_outdated = rollout.version != registry_version;
+            let is_abandoned = config.is_deleted()
+                || state.reset.is_some()
+                || !state.enabled
+                || state.rollout_strategy != RolloutStrategy::BlueGreen;
+
+            if is_failed || is_outdated || is_abandoned {
+                info!(version = rollout.version, "Tearing down shadow");
+
+                match self.teardown_shadow(&shadow_config, rollout).await {
+                    Ok(true) => {}
+                    Ok(false) => return LifecycleState::RollingOut,
+                    Err(error) => {
+                        warn!(?error, "Failed to tear down shadow, retrying...");
+                        return LifecycleState::RollingOut;
+                    }
+                }
+
+                // Retain the failure so that this version is rolled out in place instead
+                if is_failed && !is_outdated {
+                    return LifecycleState::Running;
+                }
+
+                state.rollout = None;
+            }
+        }
+
+        if config.is_deleted() {
+            return LifecycleState::Deleting;
+        }
+
+        if state.reset.is_some() {
+            return LifecycleState::Resetting;
+        }
+
+        if !state.enabled {
+            return LifecycleState::Suspending;
+        }
+
+        if state.rollout_strategy != RolloutStrategy::BlueGreen {
+            return LifecycleState::Running;
+        }
+
+        let rollout = state.rollout.get_or_insert_with(|| {
+            info!(version = registry_version, "Starting rollout");
+
+            RolloutState {
+                version: registry_version,
+                schema: config.schema.clone(),
+                provisioned_state: None,
+                deprovisioning_task_id: None,
+                shadow_started: false,
+                swap_task_id: None,
+                error: None,
+            }
+        });
+
+        if rollout.provisioned_state != Some(ProvisionedState::Provisioned) {
+            match self
+                .poll_data_layer_provisioning(&shadow_config, &mut rollout.provisioned_state)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => return LifecycleState::RollingOut,
+                Err(error) => {
+                    warn!(?error, "Failed to provision shadow data layer");
+                    rollout.error =
+                        Some(format!("Failed to provision shadow data layer: {error:#}"));
+                    return LifecycleState::RollingOut;
+                }
+            }
+        }
+
+        if !rollout.shadow_started {
+            info!("Starting shadow block stream and executor");
+
+            if let Err(error) = self.block_streams_handler.reconfigure(&shadow_config).await {
+                warn!(?error, "Failed to start shadow block stream, retrying...");
+                return LifecycleState::RollingOut;
+            }
+
+            if let Err(error) = self.executors_handler.restart(&shadow_config).await {
+                warn!(?error, "Failed to start shadow executor, retrying...");
+                return LifecycleState::RollingOut;
+            }
+
+            rollout.shadow_started = true;
+
+            return LifecycleState::RollingOut;
+        }
+
+        let shadow_height = match self
+            .redis_client
+            .get_last_published_block(&shadow_config)
+            .await
+        {
+            Ok(height) => height,
+            Err(error) => {
+                warn!(
+                    ?error,
+                    "Failed to get shadow last published block, retrying..."
+                );
+                return LifecycleState::RollingOut;
+            }
+        };
+
+        let live_height = match self.redis_client.get_last_published_block(config).await {
+            Ok(height) => height,
+            Err(error) => {
+                warn!(?error, "Failed to get last published block, retrying...");
+                return LifecycleState::RollingOut;
+            }
+        };
+
+        let caught_up = match (shadow_height, live_height) {
+            (Some(shadow_height), Some(live_height)) => shadow_height >= live_height,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if !caught_up {
+            tracing::debug!(?shadow_height, ?live_height, "Shadow is catching up");
+            return LifecycleState::RollingOut;
+        }
+
+        info!(?shadow_height, ?live_height, "Shadow caught up");
+
+        LifecycleState::Promoting
+    }
+
+    #[tracing::instrument(name = "promoting", skip_all)]
+    async fn handle_promoting(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> LifecycleState {
+        let shadow_config = config.get_shadow_config();
+
+        let Some(rollout) = state.rollout.as_mut() else {
+            return LifecycleState::Running;
+        };
+
+        // Once the swap has started, the promotion must be completed to leave the Data Layer in a
+        // consistent state
+        if rollout.swap_task_id.is_none()
+            && (config.is_deleted() || rollout.version != config.get_registry_version())
+        {
+            return LifecycleState::RollingOut;
+        }
+
+        if let Err(error) = self
+            .block_streams_handler
+            .stop_if_needed(
+                shadow_config.account_id.clone(),
+                shadow_config.function_name.clone(),
+            )
+            .await
+        {
+            warn!(?error, "Failed to stop shadow block stream, retrying...");
+            return LifecycleState::Promoting;
+        }
+
+        match self.redis_client.get_stream_length(&shadow_config).await {
+            Ok(0) => {}
+            Ok(length) => {
+                tracing::debug!(length, "Waiting for shadow executor to drain stream");
+                return LifecycleState::Promoting;
+            }
+            Err(error) => {
+                warn!(?error, "Failed to get shadow stream length, retrying...");
+                return LifecycleState::Promoting;
+            }
+        }
+
+        // Executors write to their schema by name, so both must be stopped before the schemas are
+        // swapped. The stop request only returns once the Executor has stopped.
+        for (account_id, function_name) in [
+            (&shadow_config.account_id, &shadow_config.function_name),
+            (&config.account_id, &config.function_name),
+        ] {
+            if let Err(error) = self
+                .executors_handler
+                .stop_if_needed(account_id.clone(), function_name.clone())
+                .await
+            {
+                warn!(
+                    ?error,
+                    function_name, "Failed to stop executor, retrying..."
+                );
+                return LifecycleState::Promoting;
+            }
+        }
+
+        if let Err(error) = self
+            .block_streams_handler
+            .stop_if_needed(config.account_id.clone(), config.function_name.clone())
+            .await
+        {
+            warn!(?error, "Failed to stop block stream, retrying...");
+            return LifecycleState::Promoting;
+        }
+
+        let Some(task_id) = rollout.swap_task_id.clone() else {
+            match self
+                .data_layer_handler
+                .start_swap(config, &shadow_config)
+                .await
+            {
+                Ok(task_id) => {
+                    info!(?task_id, "Started schema swap task");
+                    rollout.swap_task_id = Some(task_id);
+                }
+                Err(error) => warn!(?error, "Failed to start schema swap task, retrying..."),
+            }
+
+            return LifecycleState::Promoting;
+        };
+
+        match self.data_layer_handler.get_task_status(task_id).await {
+            Ok(TaskStatus::Pending) => return LifecycleState::Promoting,
+            Ok(TaskStatus::Complete) => {}
+            Ok(TaskStatus::Failed | TaskStatus::Unspecified) => {
+                warn!("Schema swap task failed, retrying...");
+                rollout.swap_task_id = None;
+                return LifecycleState::Promoting;
+            }
+            Err(error) => {
+                warn!(?error, "Failed to get schema swap task status, retrying...");
+                return LifecycleState::Promoting;
+            }
+        }
+
+        // The live Block Stream continues from where the shadow left off
+        match self
+            .redis_client
+            .get_last_published_block(&shadow_config)
+            .await
+        {
+            Ok(Some(height)) => {
+                if let Err(error) = self
+                    .redis_client
+                    .set(config.get_last_published_block_key(), height)
+                    .await
+                {
+                    warn!(?error, "Failed to set last published block, retrying...");
+                    return LifecycleState::Promoting;
+                }
+            }
+            // Already promoted on a previous attempt
+            Ok(None) => {}
+            Err(error) => {
+                warn!(
+                    ?error,
+                    "Failed to get shadow last published block, retrying..."
+                );
+                return LifecycleState::Promoting;
+            }
+        }
+
+        if let Err(error) = self.redis_client.clear_block_stream(config).await {
+            warn!(?error, "Failed to clear block stream, retrying...");
+            return LifecycleState::Promoting;
+        }
+
+        for key in [
+            shadow_config.get_redis_stream_key(),
+            shadow_config.get_last_published_block_key(),
+        ] {
+            if let Err(error) = self.redis_client.del(key).await {
+                warn!(?error, "Failed to clear shadow block stream, retrying...");
+                return LifecycleState::Promoting;
+            }
+        }
+
+        info!(version = rollout.version, "Promoted new version");
+
+        state.block_stream_synced_at = Some(rollout.version);
+        state.provisioned_schema = Some(rollout.schema.clone());
+        state.rollout = None;
+        state.block_stream_restart = None;
+        state.executor_restart = None;
+
+        if state.enabled {
+            LifecycleState::Running
+        } else {
+            LifecycleState::Suspended
+        }
+    }
+
+    #[tracing::instrument(name = "deleting", skip_all)]
+    async fn handle_deleting(
+        &self,
+        config: &IndexerConfig,
+        state: &mut IndexerState,
+    ) -> LifecycleState {
+        // History is kept as an audit log of the deleted Indexer, it is capped by its max length
+        let block_stream_keys = [
+            state.get_redis_stream_key(),
+            state.get_last_published_block_key(),
+        ];
+
+        let deletion = state.deletion.get_or_insert_with(|| DeletionState {
+            requested_at: now_ms(),
+            ..Default::default()
+        });
+
+        if let Err(error) = self
+            .block_streams_handler
+            .stop_if_needed(state.account_id.clone(), state.function_name.clone())
+            .await
+        {
+            warn!(?error, "Failed to stop block stream, retrying...");
+            return LifecycleState::Deleting;
+        }
+
+        if let Err(error) = self
+            .executors_handler
+            .stop_if_needed(state.account_id.clone(), state.function_name.clone())
+            .await
+        {
+            warn!(?error, "Failed to stop executor, retrying...");
+            return LifecycleState::Deleting;
+        }
+
+        let grace_period_ends_at =
+            deletion.requested_at + self.deletion_grace_period.as_millis() as u64;
+
+        if now_ms() < grace_period_ends_at {
+            if !config.is_deleted() {
+                info!("Indexer was restored within grace period, cancelling deletion");
+                state.deletion = None;
+                return LifecycleState::Initializing;
+            }
+
+            return LifecycleState::Deleting;
+        }
+
+        if !deletion.data_layer_deprovisioned {
+            match self
+                .poll_data_layer_deprovisioning(
+                    &state.account_id,
+                    &state.function_name,
+                    &mut deletion.deprovisioning_task_id,
+                )
+                .await
+            {
+                Ok(true) => deletion.data_layer_deprovisioned = true,
+                Ok(false) => return LifecycleState::Deleting,
+                Err(error) => {
+                    warn!(?error, "Failed to deprovision data layer, retrying...");
+                    return LifecycleState::Deleting;
+                }
+            }
+        }
+
+        if !deletion.block_stream_cleared {
+            info!("Clearing block stream");
+
+            for key in block_stream_keys {
+                if let Err(error) = self.redis_client.del(key).await {
+                    warn!(?error, "Failed to clear block stream, retrying...");
+                    return LifecycleState::Deleting;
+                }
+            }
+
+            deletion.block_stream_cleared = true;
+        }
+
+        if let Err(error) = self.state_manager.delete_state(state).await {
+            warn!(?error, "Failed to delete state, retrying...");
+            return LifecycleState::Deleting;
+        }
+
+        LifecycleState::Deleted
+    }
+
+    /// Describes why a transition occurred, based on the changes made to state by the handler
+    fn get_transition_reason(
+        &self,
+        config: &IndexerConfig,
+        previous_state: &IndexerState,
+        state: &IndexerState,
+        to: &LifecycleState,
+    ) -> String {
+        let repair_error = || {
+            state
+                .repair
+                .as_ref()
+                .map(|repair| repair.error.clone())
+                .unwrap_or_default()
+        };
+        let is_crash_looping = |restart: &Option<RestartState>| {
+            restart
+                .as_ref()
+                .is_some_and(|restart| restart.attempts >= self.restart_policy.max_restarts)
+        };
+
+        match (&previous_state.lifecycle_state, to) {
+            (_, LifecycleState::Deleting) => "Indexer was deleted".to_string(),
+            (_, LifecycleState::Repairing) => {
+                format!("Failed to provision data layer: {}", repair_error())
+            }
+            (_, LifecycleState::Failed) => {
+                format!("Repair attempts exhausted, last error: {}", repair_error())
+            }
+            (_, LifecycleState::Resetting) => match &previous_state.reset {
+                Some(reset) => format!(
+                    "Reset to block height {} requested{}",
+                    reset.block_height,
+                    if reset.clear_data {
+                        ", clearing data"
+                    } else {
+                        ""
+                    }
+                ),
+                None => "Reset requested".to_string(),
+            },
+            (LifecycleState::Resetting, _) => "Reset completed".to_string(),
+            (LifecycleState::MigrationFailed, LifecycleState::Migrating) => {
+                "Retrying schema migration".to_string()
+            }
+            (_, LifecycleState::Migrating) => format!(
+                "Schema changed in version {}",
+                config.get_registry_version()
+            ),
+            (_, LifecycleState::MigrationFailed) => format!(
+                "Schema migration failed: {}",
+                state
+                    .migration
+                    .as_ref()
+                    .and_then(|migration| migration.error.clone())
+                    .unwrap_or_default()
+            ),
+            (LifecycleState::Migrating, _) => "Schema migration completed".to_string(),
+            (LifecycleState::Promoting, LifecycleState::RollingOut) => {
+                "Rollout interrupted before schema swap".to_string()
+            }
+            (_, LifecycleState::RollingOut) => format!(
+                "Rolling out version {} alongside live version",
+                config.get_registry_version()
+            ),
+            (LifecycleState::RollingOut, LifecycleState::Promoting) => format!(
+                "Version {} caught up with live version",
+                config.get_registry_version()
+            ),
+            (LifecycleState::RollingOut, _) => match &state.rollout {
+                Some(RolloutState {
+                    error: Some(error), ..
+                }) => format!("Rollout failed, rolling out in place: {error}"),
+                _ => "Rollout abandoned".to_string(),
+            },
+            (LifecycleState::Promoting, _) => match &previous_state.rollout {
+                Some(rollout) => format!("Version {} promoted", rollout.version),
+                None => "Version promoted".to_string(),
+            },
+            (LifecycleState::Deleting, LifecycleState::Initializing) => {
+                "Indexer was restored during deletion grace period".to_string()
+            }
+            (LifecycleState::Repairing, LifecycleState::Initializing) => {
+                "Repair succeeded".to_string()
+            }
+            (LifecycleState::Initializing, LifecycleState::Running) => {
+                "Data layer provisioned".to_string()
+            }
+            (LifecycleState::Running, LifecycleState::Suspending)
+                if matches!(state.quota_hold, Some(QuotaHold::Suspended { .. })) =>
+            {
+                format!(
+                    "Account quota exceeded: {}",
+                    state
+                        .quota_hold
+                        .as_ref()
+                        .map(QuotaHold::reason)
+                        .unwrap_or_default()
+                )
+            }
+            (LifecycleState::Running, LifecycleState::Suspending)
+                if previous_state.enabled && !state.enabled =>
+            {
+                if is_crash_looping(&state.block_stream_restart) {
+                    "Block stream is crash looping".to_string()
+                } else if is_crash_looping(&state.executor_restart) {
+                    "Executor is crash looping".to_string()
+                } else {
+                    "Indexer was suspended".to_string()
+                }
+            }
+            (LifecycleState::Running, LifecycleState::Suspending) => {
+                "Indexer was disabled".to_string()
+            }
+            (LifecycleState::Suspending, LifecycleState::Suspended) => {
+                "Block stream and executor stopped".to_string()
+            }
+            (LifecycleState::Suspended, LifecycleState::Running)
+                if previous_state.quota_hold.is_some() =>
+            {
+                "Account is within quota".to_string()
+            }
+            (LifecycleState::Suspended, LifecycleState::Running) if !previous_state.enabled => {
+                format!(
+                    "Resumed after version {} was published",
+                    config.get_registry_version()
+                )
+            }
+            (LifecycleState::Suspended, LifecycleState::Running) => {
+                "Indexer was enabled".to_string()
+            }
+            (from, to) => format!("{from:?} -> {to:?}"),
+        }
+    }
+
+    async fn record_transition(
+        &self,
+        config: &IndexerConfig,
+        previous_state: &IndexerState,
+        state: &IndexerState,
+        to: LifecycleState,
+    ) {
+        metrics::LIFECYCLE_TRANSITIONS_COUNT
+            .with_label_values(&[
+                &format!("{:?}", previous_state.lifecycle_state),
+                &format!("{to:?}"),
+            ])
+            .inc();
+
+        let transition = IndexerTransition {
+            timestamp: now_ms(),
+            from: previous_state.lifecycle_state.clone(),
+            reason: self.get_transition_reason(config, previous_state, state, &to),
+            to,
+            registry_version: config.get_registry_version(),
+        };
+
+        // History is best-effort, it should never block the lifecycle
+        if let Err(error) = self
+            .state_manager
+            .record_transition(&self.initial_config, transition)
+            .await
+        {
+            warn!(?error, "Failed to record transition");
+        }
+    }
+
+    pub async fn handle_transitions(&self, first_iteration: bool) -> bool {
+        let config = match self
+            .registry
+            .fetch_indexer(
+                &self.initial_config.account_id,
+                &self.initial_config.function_name,
+            )
+            .await
+        {
+            Ok(Some(config)) => config,
+            Ok(None) => {
+                warn!("No matching indexer config was found");
+                return false;
+            }
+            Err(error) => {
+                warn!(?error, "Failed to fetch config");
+                return false;
+            }
+        };
+
+        let mut state = match self.state_manager.get_state(&self.initial_config).await {
+            Ok(state) => state,
+            Err(error) => {
+                warn!(?error, "Failed to get state");
+                return false;
+            }
+        };
+
+        if first_iteration {
+            info!("Initial lifecycle state: {:?}", state.lifecycle_state);
+        }
+
+        let previous_state = state.clone();
+
+        let desired_lifecycle_state = match state.lifecycle_state {
+            LifecycleState::Initializing => self.handle_initializing(&config, &mut state).await,
+            LifecycleState::Running => self.handle_running(&config, &mut state).await,
+            LifecycleState::Suspending => self.handle_suspending(&config, &mut state).await,
+            LifecycleState::Suspended => self.handle_suspended(&config, &mut state).await,
+            LifecycleState::Repairing => self.handle_repairing(&config, &mut state).await,
+            LifecycleState::Deleting => self.handle_deleting(&config, &mut state).await,
+            LifecycleState::Deleted => LifecycleState::Deleted,
+            LifecycleState::Failed => self.handle_failed(&config).await,
+            LifecycleState::Resetting => self.handle_resetting(&config, &mut state).await,
+            LifecycleState::Migrating => self.handle_migrating(&config, &mut state).await,
+            LifecycleState::MigrationFailed => {
+                self.handle_migration_failed(&config, &mut state).await
+            }
+            LifecycleState::RollingOut => self.handle_rolling_out(&config, &mut state).await,
+            LifecycleState::Promoting => self.handle_promoting(&config, &mut state).await,
+        };
+
+        if desired_lifecycle_state != state.lifecycle_state {
+            info!(
+                "Transitioning lifecycle state: {:?} -> {:?}",
+                state.lifecycle_state, desired_lifecycle_state,
+            );
+        }
+
+        if desired_lifecycle_state != state.lifecycle_state {
+            self.record_transition(
+                &config,
+                &previous_state,
+                &state,
+                desired_lifecycle_state.clone(),
+            )
+            .await;
+        }
+
+        if desired_lifecycle_state == LifecycleState::Deleted {
+            return true;
+        }
+
+        state.lifecycle_state = desired_lifecycle_state;
+
+        loop {
+            match self
+                .state_manager
+                .set_state(&self.initial_config, state.clone())
+                .await
+            {
+                Ok(_) => break,
+                Err(e) => {
+                    warn!("Failed to set state: {:?}. Retrying...", e);
+
+                    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+                }
+            }
+        }
+
+        false
+    }
+
+    #[tracing::instrument(
+        name = "lifecycle_manager",
+        skip(self),
+        fields(
+            account_id = self.initial_config.account_id.as_str(),
+            function_name = self.initial_config.function_name.as_str()
+        )
+    )]
+    pub async fn run(&self) {
+        let mut first_iteration = true;
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(LOOP_THROTTLE_MS)).await;
+            let should_exit = self.handle_transitions(first_iteration).await;
+
+            if should_exit {
+                break;
+            }
+
+            first_iteration = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indexer_state::MigrationStrategy;
+
+    use mockall::predicate::*;
+
+    mod initializing {
+        use super::*;
+
+        #[tokio::test]
+        async fn transitions_to_running_on_provisioning_success() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| Ok(None));
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Running
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn records_provisioning_task_without_waiting() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| Ok(Some("task_id".to_string())))
+                .once();
+            data_layer_handler.expect_get_task_status().never();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Initializing
+                            && state.provisioned_state
+                                == Some(ProvisionedState::Provisioning {
+                                    task_id: "task_id".to_string(),
+                                })
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn polls_in_flight_provisioning_task() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_provisioning().never();
+            data_layer_handler
+                .expect_get_task_status()
+                .with(eq("task_id".to_string()))
+                .returning(|_| Ok(TaskStatus::Complete))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioning {
+                        task_id: "task_id".to_string(),
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Running
+                            && state.provisioned_state == Some(ProvisionedState::Provisioned)
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_repairing_when_provisioning_task_fails() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_provisioning().never();
+            data_layer_handler
+                .expect_get_task_status()
+                .returning(|_| Ok(TaskStatus::Failed))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioning {
+                        task_id: "task_id".to_string(),
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Repairing
+                            && state.provisioned_state == Some(ProvisionedState::Failed)
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_repairing_on_provisioning_failure() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| anyhow::bail!("failed"));
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .with(
+                    always(),
+                    function(|transition: &IndexerTransition| {
+                        transition.from == LifecycleState::Initializing
+                            && transition.to == LifecycleState::Repairing
+                            && transition.reason == "Failed to provision data layer: failed"
+                            && transition.registry_version == 2
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Repairing
+                            && state.repair.as_ref().unwrap().error == "failed"
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_deleting_on_delete() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| anyhow::bail!("failed"));
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Deleting
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+    }
+
+    mod running {
+        use super::*;
+
+        #[tokio::test]
+        async fn transitions_to_deleting_on_delete() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Running,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Deleting
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_suspending_on_disabled() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Running,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    enabled: false,
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Deleting
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn ignores_active_stream() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Active));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+        }
+
+        #[tokio::test]
+        async fn restarts_unhealthy_stream() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                block_stream_restart: Some(RestartState {
+                    attempts: 0,
+                    next_restart_at: 0,
+                }),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Unhealthy));
+            block_streams_handler
+                .expect_restart()
+                .returning(|_| Ok(()))
+                .once();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+
+            assert_eq!(state.block_stream_restart.unwrap().attempts, 1);
+        }
+
+        #[tokio::test]
+        async fn schedules_restart_of_unhealthy_stream() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Unhealthy));
+            block_streams_handler.expect_restart().never();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            let lifecycle_state = lifecycle_manager.handle_running(&config, &mut state).await;
+
+            let restart = state.block_stream_restart.unwrap();
+            assert_eq!(lifecycle_state, LifecycleState::Running);
+            assert_eq!(restart.attempts, 0);
+            assert!(restart.next_restart_at > now_ms());
+        }
+
+        #[tokio::test]
+        async fn suspends_crash_looping_stream() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                block_stream_restart: Some(RestartState {
+                    attempts: RestartPolicy::default().max_restarts,
+                    next_restart_at: 0,
+                }),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Unhealthy));
+            block_streams_handler.expect_restart().never();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler.expect_get_status().never();
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            let lifecycle_state = lifecycle_manager.handle_running(&config, &mut state).await;
+
+            assert_eq!(lifecycle_state, LifecycleState::Suspending);
+            assert!(!state.enabled);
+
+            let previous_state = IndexerState {
+                enabled: true,
+                ..state.clone()
+            };
+
+            assert_eq!(
+                lifecycle_manager.get_transition_reason(
+                    &config,
+                    &previous_state,
+                    &state,
+                    &lifecycle_state
+                ),
+                "Block stream is crash looping"
+            );
+        }
+
+        #[tokio::test]
+        async fn forgets_restarts_of_stable_stream() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                block_stream_restart: Some(RestartState {
+                    attempts: 2,
+                    next_restart_at: 0,
+                }),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Active));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+
+            assert!(state.block_stream_restart.is_none());
+        }
+
+        #[tokio::test]
+        async fn resumes_inactive_streams() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Inactive));
+            block_streams_handler
+                .expect_resume()
+                .returning(|_| Ok(()))
+                .once();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+        }
+
+        #[tokio::test]
+        async fn reconfigures_outdated_streams() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Outdated));
+            block_streams_handler
+                .expect_reconfigure()
+                .returning(|_| Ok(()))
+                .once();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+        }
+
+        #[tokio::test]
+        async fn starts_new_streams() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::NotStarted));
+            block_streams_handler
+                .expect_start_new_block_stream()
+                .returning(|_| Ok(()))
+                .once();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+        }
+
+        #[tokio::test]
+        async fn ignores_active_executors() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Active));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Active));
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+        }
+
+        #[tokio::test]
+        async fn starts_inactive_executors() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Active));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Inactive));
+            executors_handler
+                .expect_start()
+                .returning(|_| Ok(()))
+                .once();
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+        }
+
+        #[tokio::test]
+        async fn restarts_unhealthy_executor() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                executor_restart: Some(RestartState {
+                    attempts: 0,
+                    next_restart_at: 0,
+                }),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Active));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Unhealthy));
+            executors_handler
+                .expect_restart()
+                .returning(|_| Ok(()))
+                .once();
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+
+            assert_eq!(state.executor_restart.unwrap().attempts, 1);
+        }
+
+        #[tokio::test]
+        async fn restarts_outdated_executor() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                ..Default::default()
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_get_status()
+                .returning(|_, _| Ok(BlockStreamStatus::Active));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_get_status()
+                .returning(|_| Ok(ExecutorStatus::Outdated));
+            executors_handler
+                .expect_restart()
+                .returning(|_| Ok(()))
+                .once();
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_running(&config, &mut state).await;
+        }
+    }
+
+    mod suspending {
+        use super::*;
+
+        #[tokio::test]
+        async fn transitions_to_deleting_on_delete() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Suspending,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Deleting
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn stops_streams_and_executors() {
+            let config = IndexerConfig::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Suspending,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Suspended
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+    }
+
+    mod suspended {
+        use super::*;
+
+        #[tokio::test]
+        async fn transitions_to_deleting_on_delete() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Suspended,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    enabled: false,
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Deleting
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_running_on_enabled() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Suspended,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Running
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_suspended() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Suspended,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    enabled: false,
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Suspended
+                            && state.suspended_at_version == Some(0)
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_running_on_update() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    updated_at_block_height: Some(10),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Suspended,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    enabled: false,
+                    suspended_at_version: Some(5),
+                    resume_policy: ResumePolicy::OnUpdate,
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Running
+                            && state.enabled
+                            && state.suspended_at_version.is_none()
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn ignores_update_with_manual_resume_policy() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    updated_at_block_height: Some(10),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Suspended,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    enabled: false,
+                    suspended_at_version: Some(5),
+                    resume_policy: ResumePolicy::Manual,
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Suspended
+                            && !state.enabled
+                            && state.suspended_at_version == Some(5)
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+    }
+
+    mod repairing {
+        use super::*;
+
+        #[tokio::test]
+        async fn transitions_to_deleting_on_delete() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Repairing,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Deleting
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn waits_for_next_attempt() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_provisioning().never();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Repairing,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    repair: Some(RepairState {
+                        error: "failed".to_string(),
+                        attempts: 1,
+                        next_attempt_at: u64::MAX,
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Repairing
+                            && state.repair.as_ref().unwrap().attempts == 1
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn polls_in_flight_attempt_before_next_attempt() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_provisioning().never();
+            data_layer_handler
+                .expect_get_task_status()
+                .returning(|_| Ok(TaskStatus::Complete))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Repairing,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioning {
+                        task_id: "task_id".to_string(),
+                    }),
+                    repair: Some(RepairState {
+                        error: "failed".to_string(),
+                        attempts: 1,
+                        next_attempt_at: u64::MAX,
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Initializing
+                            && state.repair.is_none()
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_initializing_on_repair_success() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| Ok(None))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Repairing,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    repair: Some(RepairState {
+                        error: "failed".to_string(),
+                        attempts: 1,
+                        next_attempt_at: 0,
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Initializing
+                            && state.repair.is_none()
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn schedules_next_attempt_on_repair_failure() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| anyhow::bail!("still failing"))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Repairing,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    repair: Some(RepairState {
+                        error: "failed".to_string(),
+                        attempts: 1,
+                        next_attempt_at: 0,
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        let repair = state.repair.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::Repairing
+                            && repair.error == "still failing"
+                            && repair.attempts == 2
+                            && repair.next_attempt_at > now_ms()
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_failed_when_attempts_are_exhausted() {
+            let config = IndexerConfig::default();
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| anyhow::bail!("still failing"))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Repairing,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    repair: Some(RepairState {
+                        error: "failed".to_string(),
+                        attempts: 2,
+                        next_attempt_at: 0,
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Failed
+                            && state.repair.as_ref().unwrap().attempts == 3
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            )
+            .with_repair_policy(RepairPolicy {
+                max_attempts: 3,
+                ..Default::default()
+            });
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[test]
+        fn backs_off_exponentially_with_jitter() {
+            let repair_policy = RepairPolicy {
+                max_attempts: 10,
+                initial_delay: Duration::from_secs(2),
+                max_delay: Duration::from_secs(60),
+            };
+
+            for (attempts, max_delay) in [(0, 2), (1, 4), (3, 16), (5, 60), (20, 60)] {
+                let delay = repair_policy.get_delay(attempts);
+
+                assert!(delay >= Duration::from_secs(max_delay) / 2);
+                assert!(delay <= Duration::from_secs(max_delay));
+            }
+        }
+    }
+
+    mod failed {
+        use super::*;
+
+        #[tokio::test]
+        async fn transitions_to_deleting_on_delete() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Failed,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Deleting
+                    }),
+                )
+                .returning(|_, _| Ok(()));
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+    }
+
+    mod deleting {
+        use super::*;
+
+        use std::collections::HashMap;
+
+        use near_primitives::types::AccountId;
+
+        use crate::registry::IndexerRegistry;
+
+        #[tokio::test]
+        async fn resumes_deleting_indexers_after_restart() {
+            let live_config = IndexerConfig {
+                function_name: "live".to_string(),
+                ..Default::default()
+            };
+            let deleted_config = IndexerConfig {
+                function_name: "deleted".to_string(),
+                deleted_at_block_height: Some(3),
+                ..Default::default()
+            };
+
+            let mut registry = Registry::default();
+            registry.expect_fetch().returning({
+                let live_config = live_config.clone();
+                move || {
+                    Ok(IndexerRegistry::from(&[(
+                        live_config.account_id.clone(),
+                        HashMap::from([(live_config.function_name.clone(), live_config.clone())]),
+                    )]))
+                }
+            });
+            registry
+                .expect_fetch_indexer()
+                .with(eq(deleted_config.account_id.clone()), eq("deleted"))
+                .returning({
+                    let deleted_config = deleted_config.clone();
+                    move |_, _| Ok(Some(deleted_config.clone()))
+                })
+                .once();
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager.expect_list().returning(|| {
+                Ok(vec![
+                    IndexerState {
+                        lifecycle_state: LifecycleState::Running,
+                        account_id: "morgs.near".parse().unwrap(),
+                        function_name: "live".to_string(),
+                        ..Default::default()
+                    },
+                    IndexerState {
+                        lifecycle_state: LifecycleState::Deleting,
+                        account_id: "morgs.near".parse().unwrap(),
+                        function_name: "deleted".to_string(),
+                        ..Default::default()
+                    },
+                ])
+            });
+
+            assert_eq!(
+                list_managed_indexers(&registry, &state_manager)
+                    .await
+                    .unwrap(),
+                vec![live_config, deleted_config]
+            );
+        }
+
+        #[tokio::test]
+        async fn stops_streams_and_executors_during_grace_period() {
+            let config = IndexerConfig::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_deprovisioning().never();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Deleting,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    ..Default::default()
+                })
+            });
+            state_manager.expect_delete_state().never();
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        let deletion = state.deletion.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::Deleting
+                            && deletion.requested_at > 0
+                            && !deletion.data_layer_deprovisioned
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn transitions_to_initializing_when_restored_during_grace_period() {
+            let config = IndexerConfig::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Deleting,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    deletion: Some(DeletionState {
+                        requested_at: now_ms(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Initializing
+                            && state.deletion.is_none()
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn deletes_all_resources_after_grace_period() {
+            let config = IndexerConfig::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_get_task_status()
+                .with(eq("task_id".to_string()))
+                .returning(|_| Ok(TaskStatus::Complete))
+                .once();
+            data_layer_handler.expect_start_deprovisioning().never();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .with(
+                    always(),
+                    function(|transition: &IndexerTransition| {
+                        transition.from == LifecycleState::Deleting
+                            && transition.to == LifecycleState::Deleted
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Deleting,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    deletion: Some(DeletionState {
+                        requested_at: 0,
+                        deprovisioning_task_id: Some("task_id".to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_delete_state()
+                .returning(|_| Ok(()))
+                .once();
+            state_manager.expect_set_state().never();
 
-            if should_exit {
-                break;
-            }
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_del::<String>()
+                .with(eq("near/function_name:block_stream".to_string()))
+                .returning(|_| Ok(()))
+                .once();
+            redis_client
+                .expect_del::<String>()
+                .with(eq("near/function_name:last_published_block".to_string()))
+                .returning(|_| Ok(()))
+                .once();
+            redis_client
+                .expect_del::<String>()
+                .with(eq("near/function_name:history".to_string()))
+                .never();
 
-            first_iteration = false;
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            assert!(lifecycle_manager.handle_transitions(true).await);
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        #[tokio::test]
+        async fn resumes_from_persisted_progress() {
+            let config = IndexerConfig::default();
 
-    use mockall::predicate::*;
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
 
-    mod initializing {
-        use super::*;
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_deprovisioning().never();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Deleting,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    deletion: Some(DeletionState {
+                        requested_at: 0,
+                        data_layer_deprovisioned: true,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager.expect_delete_state().never();
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        let deletion = state.deletion.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::Deleting
+                            && deletion.data_layer_deprovisioned
+                            && !deletion.block_stream_cleared
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_del::<String>()
+                .returning(|_| anyhow::bail!("failed"))
+                .once();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
 
         #[tokio::test]
-        async fn transitions_to_running_on_provisioning_success() {
+        async fn starts_deprovisioning_without_waiting_for_completion() {
             let config = IndexerConfig::default();
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
 
             let mut data_layer_handler = DataLayerHandler::default();
             data_layer_handler
-                .expect_ensure_provisioned()
-                .returning(|_| Ok(()));
+                .expect_start_deprovisioning()
+                .with(
+                    eq("near".parse::<AccountId>().unwrap()),
+                    eq("function_name".to_string()),
+                )
+                .returning(|_, _| Ok(Some("task_id".to_string())))
+                .once();
+            data_layer_handler.expect_get_task_status().never();
 
             let mut registry = Registry::default();
-            registry
-                .expect_fetch_indexer()
-                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Initializing,
+                    lifecycle_state: LifecycleState::Deleting,
                     account_id: "near".parse().unwrap(),
                     function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    deletion: Some(DeletionState {
+                        requested_at: 0,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
                 })
             });
+            state_manager.expect_delete_state().never();
             state_manager
                 .expect_set_state()
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Running
+                        let deletion = state.deletion.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::Deleting
+                            && deletion.deprovisioning_task_id == Some("task_id".to_string())
+                            && !deletion.data_layer_deprovisioned
                     }),
                 )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn restarts_deprovisioning_when_task_fails() {
+            let config = IndexerConfig::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_get_task_status()
+                .with(eq("task_id".to_string()))
+                .returning(|_| Ok(TaskStatus::Failed))
+                .once();
+            data_layer_handler.expect_start_deprovisioning().never();
+
+            let mut registry = Registry::default();
+            registry.expect_fetch_indexer().returning(move |_, _| {
+                Ok(Some(IndexerConfig {
+                    deleted_at_block_height: Some(3),
+                    ..Default::default()
+                }))
+            });
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
                 .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Deleting,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    deletion: Some(DeletionState {
+                        requested_at: 0,
+                        deprovisioning_task_id: Some("task_id".to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            });
+            state_manager.expect_delete_state().never();
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        let deletion = state.deletion.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::Deleting
+                            && deletion.deprovisioning_task_id.is_none()
+                            && !deletion.data_layer_deprovisioned
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
 
             let redis_client = RedisClient::default();
 
@@ -448,17 +4098,90 @@ mod tests {
 
             lifecycle_manager.handle_transitions(true).await;
         }
+    }
+
+    mod resetting {
+        use super::*;
+
+        use crate::indexer_state::ResetState;
 
         #[tokio::test]
-        async fn transitions_to_repairing_on_provisioning_failure() {
+        async fn transitions_to_resetting_when_reset_is_requested() {
             let config = IndexerConfig::default();
             let block_streams_handler = BlockStreamsHandler::default();
             let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Running,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    reset: Some(ResetState {
+                        block_height: 1000,
+                        clear_data: false,
+                        block_stream_cleared: false,
+                        deprovisioning_task_id: None,
+                        data_layer_deprovisioned: false,
+                    }),
+                    block_stream_synced_at: Some(2),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Resetting
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn resumes_from_requested_block_height() {
+            let config = IndexerConfig::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
 
             let mut data_layer_handler = DataLayerHandler::default();
-            data_layer_handler
-                .expect_ensure_provisioned()
-                .returning(|_| anyhow::bail!("failed"));
+            data_layer_handler.expect_start_deprovisioning().never();
+            data_layer_handler.expect_start_provisioning().never();
 
             let mut registry = Registry::default();
             registry
@@ -466,13 +4189,22 @@ mod tests {
                 .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Initializing,
+                    lifecycle_state: LifecycleState::Resetting,
                     account_id: "near".parse().unwrap(),
                     function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    reset: Some(ResetState {
+                        block_height: 1000,
+                        clear_data: false,
+                        block_stream_cleared: false,
+                        deprovisioning_task_id: None,
+                        data_layer_deprovisioned: false,
+                    }),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -480,12 +4212,27 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Repairing
+                        state.lifecycle_state == LifecycleState::Running
+                            && state.reset.is_none()
+                            && state.block_stream_synced_at == Some(2)
                     }),
                 )
-                .returning(|_, _| Ok(()));
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let redis_client = RedisClient::default();
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_clear_block_stream::<IndexerConfig>()
+                .returning(|_| Ok(()))
+                .once();
+            redis_client
+                .expect_set::<String, u64>()
+                .with(
+                    eq(IndexerConfig::default().get_last_published_block_key()),
+                    eq(999),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -501,32 +4248,48 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn transitions_to_deleting_on_delete() {
+        async fn does_not_repeat_deprovisioning_on_retry() {
             let config = IndexerConfig::default();
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
 
             let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_deprovisioning().never();
             data_layer_handler
-                .expect_ensure_provisioned()
-                .returning(|_| anyhow::bail!("failed"));
+                .expect_start_provisioning()
+                .returning(|_| anyhow::bail!("failed"))
+                .once();
 
             let mut registry = Registry::default();
-            registry.expect_fetch_indexer().returning(move |_, _| {
-                Ok(Some(IndexerConfig {
-                    deleted_at_block_height: Some(3),
-                    ..Default::default()
-                }))
-            });
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Initializing,
+                    lifecycle_state: LifecycleState::Resetting,
                     account_id: "near".parse().unwrap(),
                     function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    reset: Some(ResetState {
+                        block_height: 1000,
+                        clear_data: true,
+                        block_stream_cleared: true,
+                        deprovisioning_task_id: None,
+                        data_layer_deprovisioned: true,
+                    }),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -534,12 +4297,16 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleting
+                        state.lifecycle_state == LifecycleState::Resetting && state.reset.is_some()
                     }),
                 )
-                .returning(|_, _| Ok(()));
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let redis_client = RedisClient::default();
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_clear_block_stream::<IndexerConfig>()
+                .never();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -553,34 +4320,52 @@ mod tests {
 
             lifecycle_manager.handle_transitions(true).await;
         }
-    }
-
-    mod running {
-        use super::*;
 
         #[tokio::test]
-        async fn transitions_to_deleting_on_delete() {
+        async fn polls_deprovisioning_task_without_blocking() {
             let config = IndexerConfig::default();
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
-            let data_layer_handler = DataLayerHandler::default();
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_deprovisioning().never();
+            data_layer_handler
+                .expect_get_task_status()
+                .with(eq("task_id".to_string()))
+                .returning(|_| Ok(TaskStatus::Pending))
+                .once();
+            data_layer_handler.expect_start_provisioning().never();
 
             let mut registry = Registry::default();
-            registry.expect_fetch_indexer().returning(move |_, _| {
-                Ok(Some(IndexerConfig {
-                    deleted_at_block_height: Some(3),
-                    ..Default::default()
-                }))
-            });
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Running,
+                    lifecycle_state: LifecycleState::Resetting,
                     account_id: "near".parse().unwrap(),
                     function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    reset: Some(ResetState {
+                        block_height: 1000,
+                        clear_data: true,
+                        block_stream_cleared: true,
+                        deprovisioning_task_id: Some("task_id".to_string()),
+                        data_layer_deprovisioned: false,
+                    }),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -588,12 +4373,24 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleting
+                        let reset = state.reset.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::Resetting
+                            && reset.deprovisioning_task_id == Some("task_id".to_string())
+                            && !reset.data_layer_deprovisioned
+                            && state.provisioned_state
+                                == Some(ProvisionedState::Deprovisioning {
+                                    task_id: "task_id".to_string(),
+                                })
                     }),
                 )
-                .returning(|_, _| Ok(()));
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let redis_client = RedisClient::default();
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_clear_block_stream::<IndexerConfig>()
+                .never();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -607,30 +4404,36 @@ mod tests {
 
             lifecycle_manager.handle_transitions(true).await;
         }
+    }
+
+    mod deleted {
+        use super::*;
 
         #[tokio::test]
-        async fn transitions_to_suspending_on_disabled() {
+        async fn exits() {
+            tokio::time::pause();
+
             let config = IndexerConfig::default();
+
             let block_streams_handler = BlockStreamsHandler::default();
             let executors_handler = ExecutorsHandler::default();
             let data_layer_handler = DataLayerHandler::default();
 
             let mut registry = Registry::default();
-            registry.expect_fetch_indexer().returning(move |_, _| {
-                Ok(Some(IndexerConfig {
-                    deleted_at_block_height: Some(3),
-                    ..Default::default()
-                }))
-            });
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Running,
+                    lifecycle_state: LifecycleState::Deleted,
                     account_id: "near".parse().unwrap(),
                     function_name: "function_name".to_string(),
-                    enabled: false,
-                    block_stream_synced_at: None,
+                    ..Default::default()
                 })
             });
             state_manager
@@ -638,7 +4441,7 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleting
+                        state.lifecycle_state == LifecycleState::Deleted
                     }),
                 )
                 .returning(|_, _| Ok(()));
@@ -655,168 +4458,58 @@ mod tests {
                 &redis_client,
             );
 
-            lifecycle_manager.handle_transitions(true).await;
-        }
-
-        #[tokio::test]
-        async fn ignores_active_stream() {
-            let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
-
-            let mut block_streams_handler = BlockStreamsHandler::default();
-            block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Active));
-
-            let mut executors_handler = ExecutorsHandler::default();
-            executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Active));
-
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
-            let redis_client = RedisClient::default();
-
-            let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
-                &block_streams_handler,
-                &executors_handler,
-                &data_layer_handler,
-                &registry,
-                &state_manager,
-                &redis_client,
-            );
-
-            lifecycle_manager.handle_running(&config, &mut state).await;
+            lifecycle_manager.run().await;
         }
+    }
 
-        #[tokio::test]
-        async fn restarts_unhealthy_stream() {
-            tokio::time::pause();
-
-            let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
-
-            let mut block_streams_handler = BlockStreamsHandler::default();
-            block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Unhealthy));
-            block_streams_handler
-                .expect_restart()
-                .returning(|_| Ok(()))
-                .once();
-
-            let mut executors_handler = ExecutorsHandler::default();
-            executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Active));
-
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
-            let redis_client = RedisClient::default();
-
-            let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
-                &block_streams_handler,
-                &executors_handler,
-                &data_layer_handler,
-                &registry,
-                &state_manager,
-                &redis_client,
-            );
-
-            lifecycle_manager.handle_running(&config, &mut state).await;
-        }
+    mod migrating {
+        use super::*;
 
         #[tokio::test]
-        async fn resumes_inactive_streams() {
+        async fn transitions_to_migrating_when_schema_changes() {
             let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
-
-            let mut block_streams_handler = BlockStreamsHandler::default();
-            block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Inactive));
-            block_streams_handler
-                .expect_resume()
-                .returning(|_| Ok(()))
-                .once();
-
-            let mut executors_handler = ExecutorsHandler::default();
-            executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Active));
-
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
-            let redis_client = RedisClient::default();
-
-            let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
-                &block_streams_handler,
-                &executors_handler,
-                &data_layer_handler,
-                &registry,
-                &state_manager,
-                &redis_client,
-            );
-
-            lifecycle_manager.handle_running(&config, &mut state).await;
-        }
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
 
-        #[tokio::test]
-        async fn reconfigures_outdated_streams() {
-            let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_migration().never();
 
-            let mut block_streams_handler = BlockStreamsHandler::default();
-            block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Outdated));
-            block_streams_handler
-                .expect_reconfigure()
-                .returning(|_| Ok(()))
-                .once();
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
-            let mut executors_handler = ExecutorsHandler::default();
-            executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Active));
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Running,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("previous_schema".to_string()),
+                    migration_strategy: MigrationStrategy::Additive,
+                    block_stream_synced_at: Some(2),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Migrating
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
             let redis_client = RedisClient::default();
 
             let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
+                config,
                 &block_streams_handler,
                 &executors_handler,
                 &data_layer_handler,
@@ -825,41 +4518,75 @@ mod tests {
                 &redis_client,
             );
 
-            lifecycle_manager.handle_running(&config, &mut state).await;
+            lifecycle_manager.handle_transitions(true).await;
         }
 
         #[tokio::test]
-        async fn starts_new_streams() {
+        async fn starts_migration_task() {
             let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
-
             let mut block_streams_handler = BlockStreamsHandler::default();
             block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::NotStarted));
-            block_streams_handler
-                .expect_start_new_block_stream()
-                .returning(|_| Ok(()))
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
                 .once();
 
             let mut executors_handler = ExecutorsHandler::default();
             executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Active));
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_migration()
+                .with(
+                    always(),
+                    eq("previous_schema".to_string()),
+                    eq(MigrationStrategy::Recreate),
+                )
+                .returning(|_, _, _| Ok("task_id".to_string()))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Migrating,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("previous_schema".to_string()),
+                    migration_strategy: MigrationStrategy::Recreate,
+                    block_stream_synced_at: Some(2),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        let migration = state.migration.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::Migrating
+                            && migration.task_id == Some("task_id".to_string())
+                            && migration.strategy == MigrationStrategy::Recreate
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
             let redis_client = RedisClient::default();
 
             let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
+                config,
                 &block_streams_handler,
                 &executors_handler,
                 &data_layer_handler,
@@ -868,37 +4595,81 @@ mod tests {
                 &redis_client,
             );
 
-            lifecycle_manager.handle_running(&config, &mut state).await;
+            lifecycle_manager.handle_transitions(true).await;
         }
 
         #[tokio::test]
-        async fn ignores_active_executors() {
+        async fn transitions_to_running_when_migration_completes() {
             let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
-
             let mut block_streams_handler = BlockStreamsHandler::default();
             block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Active));
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
 
             let mut executors_handler = ExecutorsHandler::default();
             executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Active));
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_migration().never();
+            data_layer_handler
+                .expect_get_task()
+                .with(eq("task_id".to_string()))
+                .returning(|_| {
+                    Ok(Task {
+                        status: TaskStatus::Complete,
+                        error: None,
+                    })
+                })
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Migrating,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("previous_schema".to_string()),
+                    migration: Some(MigrationState {
+                        schema: "schema".to_string(),
+                        strategy: MigrationStrategy::Additive,
+                        task_id: Some("task_id".to_string()),
+                        error: None,
+                    }),
+                    migration_strategy: MigrationStrategy::Additive,
+                    block_stream_synced_at: Some(2),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Running
+                            && state.provisioned_schema == Some("schema".to_string())
+                            && state.migration.is_none()
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
             let redis_client = RedisClient::default();
 
             let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
+                config,
                 &block_streams_handler,
                 &executors_handler,
                 &data_layer_handler,
@@ -907,41 +4678,80 @@ mod tests {
                 &redis_client,
             );
 
-            lifecycle_manager.handle_running(&config, &mut state).await;
+            lifecycle_manager.handle_transitions(true).await;
         }
 
         #[tokio::test]
-        async fn starts_inactive_executors() {
+        async fn transitions_to_migration_failed_when_migration_fails() {
             let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
-
             let mut block_streams_handler = BlockStreamsHandler::default();
             block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Active));
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
 
             let mut executors_handler = ExecutorsHandler::default();
             executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Inactive));
-            executors_handler
-                .expect_start()
-                .returning(|_| Ok(()))
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_get_task()
+                .returning(|_| {
+                    Ok(Task {
+                        status: TaskStatus::Failed,
+                        error: Some("column \"id\" already exists".to_string()),
+                    })
+                })
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::Migrating,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("previous_schema".to_string()),
+                    migration: Some(MigrationState {
+                        schema: "schema".to_string(),
+                        strategy: MigrationStrategy::Additive,
+                        task_id: Some("task_id".to_string()),
+                        error: None,
+                    }),
+                    migration_strategy: MigrationStrategy::Additive,
+                    block_stream_synced_at: Some(2),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::MigrationFailed
+                            && state.provisioned_schema == Some("previous_schema".to_string())
+                            && state.migration.as_ref().unwrap().error
+                                == Some("column \"id\" already exists".to_string())
+                    }),
+                )
+                .returning(|_, _| Ok(()))
                 .once();
 
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
             let redis_client = RedisClient::default();
 
             let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
+                config,
                 &block_streams_handler,
                 &executors_handler,
                 &data_layer_handler,
@@ -950,43 +4760,60 @@ mod tests {
                 &redis_client,
             );
 
-            lifecycle_manager.handle_running(&config, &mut state).await;
+            lifecycle_manager.handle_transitions(true).await;
         }
 
         #[tokio::test]
-        async fn restarts_unhealthy_executor() {
-            tokio::time::pause();
-
+        async fn remains_failed_until_schema_or_strategy_changes() {
             let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
 
-            let mut block_streams_handler = BlockStreamsHandler::default();
-            block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Active));
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_migration().never();
 
-            let mut executors_handler = ExecutorsHandler::default();
-            executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Unhealthy));
-            executors_handler
-                .expect_restart()
-                .returning(|_| Ok(()))
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::MigrationFailed,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("previous_schema".to_string()),
+                    migration: Some(MigrationState {
+                        schema: "schema".to_string(),
+                        strategy: MigrationStrategy::Additive,
+                        task_id: Some("task_id".to_string()),
+                        error: Some("failed".to_string()),
+                    }),
+                    migration_strategy: MigrationStrategy::Additive,
+                    block_stream_synced_at: Some(2),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::MigrationFailed
+                    }),
+                )
+                .returning(|_, _| Ok(()))
                 .once();
 
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
             let redis_client = RedisClient::default();
 
             let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
+                config,
                 &block_streams_handler,
                 &executors_handler,
                 &data_layer_handler,
@@ -995,41 +4822,61 @@ mod tests {
                 &redis_client,
             );
 
-            lifecycle_manager.handle_running(&config, &mut state).await;
+            lifecycle_manager.handle_transitions(true).await;
         }
 
         #[tokio::test]
-        async fn restarts_outdated_executor() {
+        async fn retries_migration_when_strategy_changes() {
             let config = IndexerConfig::default();
-            let mut state = IndexerState {
-                lifecycle_state: LifecycleState::Running,
-                account_id: config.account_id.clone(),
-                function_name: config.function_name.clone(),
-                enabled: true,
-                block_stream_synced_at: None,
-            };
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
 
-            let mut block_streams_handler = BlockStreamsHandler::default();
-            block_streams_handler
-                .expect_get_status()
-                .returning(|_, _| Ok(BlockStreamStatus::Active));
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_migration().never();
 
-            let mut executors_handler = ExecutorsHandler::default();
-            executors_handler
-                .expect_get_status()
-                .returning(|_| Ok(ExecutorStatus::Outdated));
-            executors_handler
-                .expect_restart()
-                .returning(|_| Ok(()))
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::MigrationFailed,
+                    account_id: "near".parse().unwrap(),
+                    function_name: "function_name".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("previous_schema".to_string()),
+                    migration: Some(MigrationState {
+                        schema: "schema".to_string(),
+                        strategy: MigrationStrategy::Additive,
+                        task_id: Some("task_id".to_string()),
+                        error: Some("failed".to_string()),
+                    }),
+                    migration_strategy: MigrationStrategy::Recreate,
+                    block_stream_synced_at: Some(2),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        state.lifecycle_state == LifecycleState::Migrating
+                            && state.migration.is_none()
+                    }),
+                )
+                .returning(|_, _| Ok(()))
                 .once();
 
-            let data_layer_handler = DataLayerHandler::default();
-            let state_manager = IndexerStateManager::default();
-            let registry = Registry::default();
             let redis_client = RedisClient::default();
 
             let lifecycle_manager = LifecycleManager::new(
-                config.clone(),
+                config,
                 &block_streams_handler,
                 &executors_handler,
                 &data_layer_handler,
@@ -1038,36 +4885,40 @@ mod tests {
                 &redis_client,
             );
 
-            lifecycle_manager.handle_running(&config, &mut state).await;
+            lifecycle_manager.handle_transitions(true).await;
         }
     }
 
-    mod suspending {
+    mod rolling_out {
         use super::*;
 
         #[tokio::test]
-        async fn transitions_to_deleting_on_delete() {
+        async fn transitions_to_rolling_out_when_new_version_is_published() {
             let config = IndexerConfig::default();
             let block_streams_handler = BlockStreamsHandler::default();
             let executors_handler = ExecutorsHandler::default();
             let data_layer_handler = DataLayerHandler::default();
+            let redis_client = RedisClient::default();
 
             let mut registry = Registry::default();
-            registry.expect_fetch_indexer().returning(move |_, _| {
-                Ok(Some(IndexerConfig {
-                    deleted_at_block_height: Some(3),
-                    ..Default::default()
-                }))
-            });
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Suspending,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::Running,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1075,13 +4926,82 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleting
+                        state.lifecycle_state == LifecycleState::RollingOut
                     }),
                 )
-                .returning(|_, _| Ok(()));
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config,
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            );
+
+            lifecycle_manager.handle_transitions(true).await;
+        }
+
+        #[tokio::test]
+        async fn provisions_shadow_data_layer() {
+            let config = IndexerConfig::default();
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .with(function(|config: &IndexerConfig| {
+                    config.function_name == "test__shadow"
+                }))
+                .returning(|_| Ok(Some("task_id".to_string())))
+                .once();
 
             let redis_client = RedisClient::default();
 
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+
+            let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
+            state_manager.expect_get_state().returning(|_| {
+                Ok(IndexerState {
+                    lifecycle_state: LifecycleState::RollingOut,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
+                })
+            });
+            state_manager
+                .expect_set_state()
+                .with(
+                    always(),
+                    function(|state: &IndexerState| {
+                        let rollout = state.rollout.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::RollingOut
+                            && rollout.version == 2
+                            && rollout.provisioned_state
+                                == Some(ProvisionedState::Provisioning {
+                                    task_id: "task_id".to_string(),
+                                })
+                            && state.provisioned_state == Some(ProvisionedState::Provisioned)
+                    }),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
             let lifecycle_manager = LifecycleManager::new(
                 config,
                 &block_streams_handler,
@@ -1096,22 +5016,33 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn stops_streams_and_executors() {
+        async fn starts_shadow_from_start_block() {
             let config = IndexerConfig::default();
-
             let mut block_streams_handler = BlockStreamsHandler::default();
             block_streams_handler
-                .expect_stop_if_needed()
-                .returning(|_, _| Ok(()))
+                .expect_reconfigure()
+                .with(function(|config: &IndexerConfig| {
+                    config.function_name == "test__shadow"
+                }))
+                .returning(|_| Ok(()))
                 .once();
 
             let mut executors_handler = ExecutorsHandler::default();
             executors_handler
-                .expect_stop_if_needed()
-                .returning(|_, _| Ok(()))
+                .expect_restart()
+                .with(function(|config: &IndexerConfig| {
+                    config.function_name == "test__shadow"
+                }))
+                .returning(|_| Ok(()))
                 .once();
 
-            let data_layer_handler = DataLayerHandler::default();
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_provisioning()
+                .returning(|_| Ok(None))
+                .once();
+
+            let redis_client = RedisClient::default();
 
             let mut registry = Registry::default();
             registry
@@ -1119,13 +5050,28 @@ mod tests {
                 .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Suspending,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::RollingOut,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout: Some(RolloutState {
+                        version: 2,
+                        schema: "schema".to_string(),
+                        provisioned_state: None,
+                        deprovisioning_task_id: None,
+                        shadow_started: false,
+                        swap_task_id: None,
+                        error: None,
+                    }),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1133,12 +5079,12 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Suspended
+                        state.lifecycle_state == LifecycleState::RollingOut
+                            && state.rollout.as_ref().unwrap().shadow_started
                     }),
                 )
-                .returning(|_, _| Ok(()));
-
-            let redis_client = RedisClient::default();
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -1152,34 +5098,58 @@ mod tests {
 
             lifecycle_manager.handle_transitions(true).await;
         }
-    }
-
-    mod suspended {
-        use super::*;
 
         #[tokio::test]
-        async fn transitions_to_deleting_on_delete() {
+        async fn transitions_to_promoting_once_shadow_caught_up() {
             let config = IndexerConfig::default();
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler.expect_reconfigure().never();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler.expect_restart().never();
+
             let data_layer_handler = DataLayerHandler::default();
 
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_get_last_published_block::<IndexerConfig>()
+                .returning(|config: &IndexerConfig| {
+                    if config.function_name == "test__shadow" {
+                        Ok(Some(200))
+                    } else {
+                        Ok(Some(150))
+                    }
+                })
+                .times(2);
+
             let mut registry = Registry::default();
-            registry.expect_fetch_indexer().returning(move |_, _| {
-                Ok(Some(IndexerConfig {
-                    deleted_at_block_height: Some(3),
-                    ..Default::default()
-                }))
-            });
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Suspended,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: false,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::RollingOut,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout: Some(RolloutState {
+                        version: 2,
+                        schema: "schema".to_string(),
+                        provisioned_state: Some(ProvisionedState::Provisioned),
+                        deprovisioning_task_id: None,
+                        shadow_started: true,
+                        swap_task_id: None,
+                        error: None,
+                    }),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1187,12 +5157,11 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleting
+                        state.lifecycle_state == LifecycleState::Promoting
                     }),
                 )
-                .returning(|_, _| Ok(()));
-
-            let redis_client = RedisClient::default();
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -1208,12 +5177,51 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn transitions_to_running_on_enabled() {
+        async fn tears_down_shadow_and_rolls_out_in_place_on_failure() {
             let config = IndexerConfig::default();
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .with(
+                    eq("morgs.near".parse::<AccountId>().unwrap()),
+                    eq("test__shadow".to_string()),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
-            let data_layer_handler = DataLayerHandler::default();
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .with(
+                    eq("morgs.near".parse::<AccountId>().unwrap()),
+                    eq("test__shadow".to_string()),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_deprovisioning()
+                .with(
+                    eq("morgs.near".parse::<AccountId>().unwrap()),
+                    eq("test__shadow".to_string()),
+                )
+                .returning(|_, _| Ok(None))
+                .once();
+
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_del::<String>()
+                .with(eq("morgs.near/test__shadow:block_stream".to_string()))
+                .returning(|_| Ok(()))
+                .once();
+            redis_client
+                .expect_del::<String>()
+                .with(eq(
+                    "morgs.near/test__shadow:last_published_block".to_string()
+                ))
+                .returning(|_| Ok(()))
+                .once();
 
             let mut registry = Registry::default();
             registry
@@ -1221,13 +5229,28 @@ mod tests {
                 .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Suspended,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::RollingOut,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout: Some(RolloutState {
+                        version: 2,
+                        schema: "schema".to_string(),
+                        provisioned_state: Some(ProvisionedState::Failed),
+                        deprovisioning_task_id: None,
+                        shadow_started: false,
+                        swap_task_id: None,
+                        error: Some("Provisioning task failed".to_string()),
+                    }),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1235,12 +5258,15 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
+                        let rollout = state.rollout.as_ref().unwrap();
+
                         state.lifecycle_state == LifecycleState::Running
+                            && rollout.error.is_some()
+                            && rollout.provisioned_state.is_none()
                     }),
                 )
-                .returning(|_, _| Ok(()));
-
-            let redis_client = RedisClient::default();
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -1256,12 +5282,28 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn transitions_to_suspended() {
+        async fn polls_shadow_deprovisioning_during_teardown() {
             let config = IndexerConfig::default();
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
 
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
-            let data_layer_handler = DataLayerHandler::default();
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()));
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_deprovisioning().never();
+            data_layer_handler
+                .expect_get_task_status()
+                .with(eq("task_id".to_string()))
+                .returning(|_| Ok(TaskStatus::Pending))
+                .once();
+
+            let mut redis_client = RedisClient::default();
+            redis_client.expect_del::<String>().never();
 
             let mut registry = Registry::default();
             registry
@@ -1269,13 +5311,28 @@ mod tests {
                 .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Suspended,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: false,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::RollingOut,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout: Some(RolloutState {
+                        version: 2,
+                        schema: "schema".to_string(),
+                        provisioned_state: Some(ProvisionedState::Provisioned),
+                        deprovisioning_task_id: Some("task_id".to_string()),
+                        shadow_started: true,
+                        swap_task_id: None,
+                        error: Some("Shadow executor failed".to_string()),
+                    }),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1283,12 +5340,16 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Suspended
+                        let rollout = state.rollout.as_ref().unwrap();
+
+                        state.lifecycle_state == LifecycleState::RollingOut
+                            && rollout.deprovisioning_task_id == Some("task_id".to_string())
+                            && rollout.provisioned_state.is_some()
+                            && rollout.shadow_started
                     }),
                 )
-                .returning(|_, _| Ok(()));
-
-            let redis_client = RedisClient::default();
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -1304,32 +5365,65 @@ mod tests {
         }
     }
 
-    mod repairing {
+    mod promoting {
         use super::*;
 
         #[tokio::test]
-        async fn transitions_to_deleting_on_delete() {
+        async fn waits_for_shadow_executor_to_drain_stream() {
             let config = IndexerConfig::default();
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
-            let data_layer_handler = DataLayerHandler::default();
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .with(
+                    eq("morgs.near".parse::<AccountId>().unwrap()),
+                    eq("test__shadow".to_string()),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
 
-            let mut registry = Registry::default();
-            registry.expect_fetch_indexer().returning(move |_, _| {
-                Ok(Some(IndexerConfig {
-                    deleted_at_block_height: Some(3),
-                    ..Default::default()
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler.expect_stop_if_needed().never();
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_swap().never();
+
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_get_stream_length::<IndexerConfig>()
+                .with(function(|config: &IndexerConfig| {
+                    config.function_name == "test__shadow"
                 }))
-            });
+                .returning(|_| Ok(5))
+                .once();
+
+            let mut registry = Registry::default();
+            registry
+                .expect_fetch_indexer()
+                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Repairing,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::Promoting,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout: Some(RolloutState {
+                        version: 2,
+                        schema: "schema".to_string(),
+                        provisioned_state: Some(ProvisionedState::Provisioned),
+                        deprovisioning_task_id: None,
+                        shadow_started: true,
+                        swap_task_id: None,
+                        error: None,
+                    }),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1337,12 +5431,11 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleting
+                        state.lifecycle_state == LifecycleState::Promoting
                     }),
                 )
-                .returning(|_, _| Ok(()));
-
-            let redis_client = RedisClient::default();
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -1358,12 +5451,52 @@ mod tests {
         }
 
         #[tokio::test]
-        async fn transitions_to_repairing() {
+        async fn starts_swap_once_both_executors_are_stopped() {
             let config = IndexerConfig::default();
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler
+                .expect_stop_if_needed()
+                .returning(|_, _| Ok(()))
+                .times(2);
 
-            let block_streams_handler = BlockStreamsHandler::default();
-            let executors_handler = ExecutorsHandler::default();
-            let data_layer_handler = DataLayerHandler::default();
+            let mut sequence = mockall::Sequence::new();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler
+                .expect_stop_if_needed()
+                .with(
+                    eq("morgs.near".parse::<AccountId>().unwrap()),
+                    eq("test__shadow".to_string()),
+                )
+                .returning(|_, _| Ok(()))
+                .once()
+                .in_sequence(&mut sequence);
+            executors_handler
+                .expect_stop_if_needed()
+                .with(
+                    eq("morgs.near".parse::<AccountId>().unwrap()),
+                    eq("test".to_string()),
+                )
+                .returning(|_, _| Ok(()))
+                .once()
+                .in_sequence(&mut sequence);
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler
+                .expect_start_swap()
+                .with(
+                    always(),
+                    function(|config: &IndexerConfig| config.function_name == "test__shadow"),
+                )
+                .returning(|_, _| Ok("task_id".to_string()))
+                .once()
+                .in_sequence(&mut sequence);
+
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_get_stream_length::<IndexerConfig>()
+                .returning(|_| Ok(0))
+                .once();
 
             let mut registry = Registry::default();
             registry
@@ -1371,13 +5504,28 @@ mod tests {
                 .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Repairing,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::Promoting,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout: Some(RolloutState {
+                        version: 2,
+                        schema: "schema".to_string(),
+                        provisioned_state: Some(ProvisionedState::Provisioned),
+                        deprovisioning_task_id: None,
+                        shadow_started: true,
+                        swap_task_id: None,
+                        error: None,
+                    }),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1385,12 +5533,13 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Repairing
+                        state.lifecycle_state == LifecycleState::Promoting
+                            && state.rollout.as_ref().unwrap().swap_task_id
+                                == Some("task_id".to_string())
                     }),
                 )
-                .returning(|_, _| Ok(()));
-
-            let redis_client = RedisClient::default();
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -1404,28 +5553,69 @@ mod tests {
 
             lifecycle_manager.handle_transitions(true).await;
         }
-    }
-
-    mod deleting {
-        use super::*;
 
         #[tokio::test]
-        async fn stops_streams_and_executors() {
+        async fn resumes_live_block_stream_from_shadow_once_swapped() {
             let config = IndexerConfig::default();
-
             let mut block_streams_handler = BlockStreamsHandler::default();
             block_streams_handler
                 .expect_stop_if_needed()
                 .returning(|_, _| Ok(()))
-                .once();
+                .times(2);
 
             let mut executors_handler = ExecutorsHandler::default();
             executors_handler
                 .expect_stop_if_needed()
                 .returning(|_, _| Ok(()))
+                .times(2);
+
+            let mut data_layer_handler = DataLayerHandler::default();
+            data_layer_handler.expect_start_swap().never();
+            data_layer_handler
+                .expect_get_task_status()
+                .with(eq("task_id".to_string()))
+                .returning(|_| Ok(TaskStatus::Complete))
                 .once();
 
-            let data_layer_handler = DataLayerHandler::default();
+            let mut redis_client = RedisClient::default();
+            redis_client
+                .expect_get_stream_length::<IndexerConfig>()
+                .returning(|_| Ok(0))
+                .once();
+            redis_client
+                .expect_get_last_published_block::<IndexerConfig>()
+                .with(function(|config: &IndexerConfig| {
+                    config.function_name == "test__shadow"
+                }))
+                .returning(|_| Ok(Some(200)))
+                .once();
+            redis_client
+                .expect_set::<String, u64>()
+                .with(
+                    eq("morgs.near/test:last_published_block".to_string()),
+                    eq(200),
+                )
+                .returning(|_, _| Ok(()))
+                .once();
+            redis_client
+                .expect_clear_block_stream::<IndexerConfig>()
+                .with(function(|config: &IndexerConfig| {
+                    config.function_name == "test"
+                }))
+                .returning(|_| Ok(()))
+                .once();
+            redis_client
+                .expect_del::<String>()
+                .with(eq("morgs.near/test__shadow:block_stream".to_string()))
+                .returning(|_| Ok(()))
+                .once();
+            redis_client
+                .expect_del::<String>()
+                .with(eq(
+                    "morgs.near/test__shadow:last_published_block".to_string()
+                ))
+                .returning(|_| Ok(()))
+                .once();
 
             let mut registry = Registry::default();
             registry
@@ -1433,13 +5623,28 @@ mod tests {
                 .returning(move |_, _| Ok(Some(IndexerConfig::default())));
 
             let mut state_manager = IndexerStateManager::default();
+            state_manager
+                .expect_record_transition()
+                .returning(|_, _| Ok(()));
             state_manager.expect_get_state().returning(|_| {
                 Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Deleting,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
+                    lifecycle_state: LifecycleState::Promoting,
+                    account_id: "morgs.near".parse().unwrap(),
+                    function_name: "test".to_string(),
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    provisioned_schema: Some("schema".to_string()),
+                    rollout: Some(RolloutState {
+                        version: 2,
+                        schema: "schema".to_string(),
+                        provisioned_state: Some(ProvisionedState::Provisioned),
+                        deprovisioning_task_id: None,
+                        shadow_started: true,
+                        swap_task_id: Some("task_id".to_string()),
+                        error: None,
+                    }),
+                    rollout_strategy: RolloutStrategy::BlueGreen,
+                    block_stream_synced_at: Some(1),
+                    ..Default::default()
                 })
             });
             state_manager
@@ -1447,12 +5652,14 @@ mod tests {
                 .with(
                     always(),
                     function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleted
+                        state.lifecycle_state == LifecycleState::Running
+                            && state.block_stream_synced_at == Some(2)
+                            && state.provisioned_schema == Some("schema".to_string())
+                            && state.rollout.is_none()
                     }),
                 )
-                .returning(|_, _| Ok(()));
-
-            let redis_client = RedisClient::default();
+                .returning(|_, _| Ok(()))
+                .once();
 
             let lifecycle_manager = LifecycleManager::new(
                 config,
@@ -1468,57 +5675,209 @@ mod tests {
         }
     }
 
-    mod deleted {
+    mod quota {
         use super::*;
 
+        use std::collections::HashMap;
+
+        fn suspended_hold() -> QuotaHold {
+            QuotaHold::Suspended {
+                reason: "Account has reached its limit of 1 active indexers".to_string(),
+            }
+        }
+
         #[tokio::test]
-        async fn exits() {
-            tokio::time::pause();
+        async fn suspends_indexers_over_account_quota() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                provisioned_state: Some(ProvisionedState::Provisioned),
+                provisioned_schema: Some(config.schema.clone()),
+                ..Default::default()
+            };
+
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            )
+            .with_quota_scheduler(Arc::new(QuotaScheduler::with_holds(HashMap::from([(
+                config.get_full_name(),
+                suspended_hold(),
+            )]))));
+
+            let previous_state = state.clone();
+            let lifecycle_state = lifecycle_manager.handle_running(&config, &mut state).await;
+
+            assert_eq!(lifecycle_state, LifecycleState::Suspending);
+            assert!(state.enabled);
+            assert_eq!(state.quota_hold, Some(suspended_hold()));
+            assert_eq!(
+                lifecycle_manager.get_transition_reason(
+                    &config,
+                    &previous_state,
+                    &state,
+                    &lifecycle_state
+                ),
+                "Account quota exceeded: Account has reached its limit of 1 active indexers"
+            );
+        }
+
+        #[tokio::test]
+        async fn defers_starting_queued_backfills() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                provisioned_state: Some(ProvisionedState::Provisioned),
+                provisioned_schema: Some(config.schema.clone()),
+                ..Default::default()
+            };
+
+            let hold = QuotaHold::Queued {
+                reason: "Waiting for one of 1 backfill slots".to_string(),
+                position: 2,
+            };
+
+            let mut block_streams_handler = BlockStreamsHandler::default();
+            block_streams_handler.expect_get_status().never();
+            block_streams_handler
+                .expect_start_new_block_stream()
+                .never();
+
+            let mut executors_handler = ExecutorsHandler::default();
+            executors_handler.expect_get_status().never();
+            executors_handler.expect_start().never();
+
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
+
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            )
+            .with_quota_scheduler(Arc::new(QuotaScheduler::with_holds(HashMap::from([(
+                config.get_full_name(),
+                hold.clone(),
+            )]))));
 
+            let lifecycle_state = lifecycle_manager.handle_running(&config, &mut state).await;
+
+            assert_eq!(lifecycle_state, LifecycleState::Running);
+            assert_eq!(state.quota_hold, Some(hold));
+            assert_eq!(state.block_stream_synced_at, None);
+        }
+
+        #[tokio::test]
+        async fn remains_suspended_while_over_account_quota() {
             let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Suspended,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                provisioned_state: Some(ProvisionedState::Provisioned),
+                provisioned_schema: Some(config.schema.clone()),
+                quota_hold: Some(suspended_hold()),
+                ..Default::default()
+            };
 
             let block_streams_handler = BlockStreamsHandler::default();
             let executors_handler = ExecutorsHandler::default();
             let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
+            let redis_client = RedisClient::default();
 
-            let mut registry = Registry::default();
-            registry
-                .expect_fetch_indexer()
-                .returning(move |_, _| Ok(Some(IndexerConfig::default())));
+            let lifecycle_manager = LifecycleManager::new(
+                config.clone(),
+                &block_streams_handler,
+                &executors_handler,
+                &data_layer_handler,
+                &registry,
+                &state_manager,
+                &redis_client,
+            )
+            .with_quota_scheduler(Arc::new(QuotaScheduler::with_holds(HashMap::from([(
+                config.get_full_name(),
+                suspended_hold(),
+            )]))));
 
-            let mut state_manager = IndexerStateManager::default();
-            state_manager.expect_get_state().returning(|_| {
-                Ok(IndexerState {
-                    lifecycle_state: LifecycleState::Deleted,
-                    account_id: "near".parse().unwrap(),
-                    function_name: "function_name".to_string(),
-                    enabled: true,
-                    block_stream_synced_at: None,
-                })
-            });
-            state_manager
-                .expect_set_state()
-                .with(
-                    always(),
-                    function(|state: &IndexerState| {
-                        state.lifecycle_state == LifecycleState::Deleted
-                    }),
-                )
-                .returning(|_, _| Ok(()));
+            let lifecycle_state = lifecycle_manager
+                .handle_suspended(&config, &mut state)
+                .await;
+
+            assert_eq!(lifecycle_state, LifecycleState::Suspended);
+            assert_eq!(state.quota_hold, Some(suspended_hold()));
+        }
+
+        #[tokio::test]
+        async fn resumes_once_within_account_quota() {
+            let config = IndexerConfig::default();
+            let mut state = IndexerState {
+                lifecycle_state: LifecycleState::Suspended,
+                account_id: config.account_id.clone(),
+                function_name: config.function_name.clone(),
+                provisioned_state: Some(ProvisionedState::Provisioned),
+                provisioned_schema: Some(config.schema.clone()),
+                quota_hold: Some(suspended_hold()),
+                ..Default::default()
+            };
 
+            let block_streams_handler = BlockStreamsHandler::default();
+            let executors_handler = ExecutorsHandler::default();
+            let data_layer_handler = DataLayerHandler::default();
+            let state_manager = IndexerStateManager::default();
+            let registry = Registry::default();
             let redis_client = RedisClient::default();
 
             let lifecycle_manager = LifecycleManager::new(
-                config,
+                config.clone(),
                 &block_streams_handler,
                 &executors_handler,
                 &data_layer_handler,
                 &registry,
                 &state_manager,
                 &redis_client,
+            )
+            .with_quota_scheduler(Arc::new(QuotaScheduler::with_holds(HashMap::new())));
+
+            let previous_state = state.clone();
+            let lifecycle_state = lifecycle_manager
+                .handle_suspended(&config, &mut state)
+                .await;
+
+            assert_eq!(lifecycle_state, LifecycleState::Running);
+            assert_eq!(state.quota_hold, None);
+            assert_eq!(
+                lifecycle_manager.get_transition_reason(
+                    &config,
+                    &previous_state,
+                    &state,
+                    &lifecycle_state
+                ),
+                "Account is within quota"
             );
-
-            lifecycle_manager.run().await;
         }
     }
 }
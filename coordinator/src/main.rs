@@ -9,21 +9,35 @@ use tracing_subscriber::prelude::*;
 use crate::handlers::block_streams::BlockStreamsHandler;
 use crate::handlers::data_layer::DataLayerHandler;
 use crate::handlers::executors::ExecutorsHandler;
+use crate::health::HealthChecker;
 use crate::indexer_state::IndexerStateManager;
-use crate::lifecycle::LifecycleManager;
-use crate::redis::RedisClient;
+use crate::leader_election::LeaderElection;
+use crate::lifecycle::{
+    list_managed_indexers, LifecycleManager, RepairPolicy, DELETION_GRACE_PERIOD,
+};
+use crate::quota::{QuotaPolicy, QuotaScheduler};
+use crate::redis::{KeyProvider, RedisClient};
 use crate::registry::Registry;
+use crate::sharding::{Membership, Ownership};
 
 mod handlers;
+mod health;
 mod indexer_config;
 mod indexer_state;
+mod leader_election;
 mod lifecycle;
+mod metrics;
+mod quota;
 mod redis;
 mod registry;
 mod server;
+mod sharding;
 mod utils;
 
 const LOOP_THROTTLE_SECONDS: Duration = Duration::from_secs(1);
+const REGISTRY_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+const LIFECYCLE_STATE_METRICS_INTERVAL: Duration = Duration::from_secs(15);
+const DEFAULT_METRICS_PORT: u16 = 9180;
 
 async fn sleep(duration: Duration) -> anyhow::Result<()> {
     tokio::time::sleep(duration).await;
@@ -54,39 +68,243 @@ async fn main() -> anyhow::Result<()> {
         std::env::var("BLOCK_STREAMER_URL").expect("BLOCK_STREAMER_URL is not set");
     let runner_url = std::env::var("RUNNER_URL").expect("RUNNER_URL is not set");
     let grpc_port = std::env::var("GRPC_PORT").expect("GRPC_PORT is not set");
+    let metrics_port = match std::env::var("METRICS_PORT") {
+        Ok(port) => port.parse().expect("METRICS_PORT is not a valid number"),
+        Err(_) => DEFAULT_METRICS_PORT,
+    };
+    let chain_id = match std::env::var("CHAIN_ID").as_deref() {
+        Ok("mainnet") | Err(_) => block_streamer::ChainId::Mainnet,
+        Ok("testnet") => block_streamer::ChainId::Testnet,
+        Ok(chain_id) => panic!("CHAIN_ID {chain_id} is not a valid chain"),
+    };
+    let repair_policy = match std::env::var("MAX_REPAIR_ATTEMPTS") {
+        Ok(max_attempts) => RepairPolicy {
+            max_attempts: max_attempts
+                .parse()
+                .expect("MAX_REPAIR_ATTEMPTS is not a valid number"),
+            ..Default::default()
+        },
+        Err(_) => RepairPolicy::default(),
+    };
+    let deletion_grace_period = match std::env::var("DELETION_GRACE_PERIOD_SECONDS") {
+        Ok(seconds) => Duration::from_secs(
+            seconds
+                .parse()
+                .expect("DELETION_GRACE_PERIOD_SECONDS is not a valid number"),
+        ),
+        Err(_) => DELETION_GRACE_PERIOD,
+    };
+    let sharding_enabled = std::env::var("SHARDING_ENABLED").is_ok();
+    let quota_policy = QuotaPolicy {
+        max_active_indexers_per_account: std::env::var("MAX_ACTIVE_INDEXERS_PER_ACCOUNT").ok().map(
+            |max| {
+                max.parse()
+                    .expect("MAX_ACTIVE_INDEXERS_PER_ACCOUNT is not a valid number")
+            },
+        ),
+        max_backfilling_streams: std::env::var("MAX_BACKFILLING_STREAMS").ok().map(|max| {
+            max.parse()
+                .expect("MAX_BACKFILLING_STREAMS is not a valid number")
+        }),
+        backfill_threshold: match std::env::var("BACKFILL_THRESHOLD_BLOCKS") {
+            Ok(blocks) => blocks
+                .parse()
+                .expect("BACKFILL_THRESHOLD_BLOCKS is not a valid number"),
+            Err(_) => quota::DEFAULT_BACKFILL_THRESHOLD,
+        },
+        priority_tiers: match std::env::var("ACCOUNT_PRIORITY_TIERS") {
+            Ok(tiers) => {
+                quota::parse_priority_tiers(&tiers).expect("ACCOUNT_PRIORITY_TIERS is not valid")
+            }
+            Err(_) => HashMap::new(),
+        },
+    };
 
     tracing::info!(
         rpc_url,
+        chain_id = chain_id.as_str_name(),
         registry_contract_id = registry_contract_id.as_str(),
         block_streamer_url,
         runner_url,
         redis_url,
+        metrics_port,
+        max_repair_attempts = repair_policy.max_attempts,
+        sharding_enabled,
+        max_active_indexers_per_account = quota_policy.max_active_indexers_per_account,
+        max_backfilling_streams = quota_policy.max_backfilling_streams,
         "Starting Coordinator"
     );
 
     let registry = Arc::new(Registry::connect(registry_contract_id.clone(), &rpc_url));
     let redis_client = RedisClient::connect(&redis_url).await?;
     let block_streams_handler =
-        BlockStreamsHandler::connect(&block_streamer_url, redis_client.clone())?;
+        BlockStreamsHandler::connect(&block_streamer_url, redis_client.clone(), chain_id)?;
     let executors_handler = ExecutorsHandler::connect(&runner_url)?;
     let data_layer_handler = DataLayerHandler::connect(&runner_url)?;
     let indexer_state_manager = Arc::new(IndexerStateManager::new(redis_client.clone()));
+    let instance_id = std::env::var("HOSTNAME")
+        .unwrap_or_else(|_| format!("coordinator-{}", rand::random::<u32>()));
+    let ownership = Arc::new(if sharding_enabled {
+        Ownership::Sharded(Membership::new(redis_client.clone(), instance_id))
+    } else {
+        Ownership::Leader(LeaderElection::new(redis_client.clone(), instance_id))
+    });
+
+    let quota_scheduler = Arc::new(QuotaScheduler::new(quota_policy));
+
+    let health_checker = Arc::new(HealthChecker::new(
+        redis_client.clone(),
+        registry.clone(),
+        block_streams_handler.clone(),
+        executors_handler.clone(),
+    ));
+
+    tokio::spawn({
+        let ownership = ownership.clone();
+        async move { ownership.run().await }
+    });
+
+    tokio::spawn({
+        let health_checker = health_checker.clone();
+        async move { health_checker.run().await }
+    });
+
+    tokio::spawn({
+        let quota_scheduler = quota_scheduler.clone();
+        let registry = registry.clone();
+        let indexer_state_manager = indexer_state_manager.clone();
+        let redis_client = redis_client.clone();
+        async move {
+            quota_scheduler
+                .run(&registry, &indexer_state_manager, &redis_client)
+                .await
+        }
+    });
+
+    tokio::spawn(
+        metrics::init_server(metrics_port, health_checker).expect("Failed to start metrics server"),
+    );
+
+    tokio::spawn({
+        let indexer_state_manager = indexer_state_manager.clone();
+        let ownership = ownership.clone();
+        async move {
+            loop {
+                match indexer_state_manager.list().await {
+                    Ok(states) => {
+                        let mut counts = HashMap::<String, i64>::new();
+
+                        for state in states
+                            .iter()
+                            .filter(|state| ownership.owns(&state.prefix()))
+                        {
+                            *counts
+                                .entry(format!("{:?}", state.lifecycle_state))
+                                .or_default() += 1;
+                        }
+
+                        metrics::INDEXERS_BY_LIFECYCLE_STATE.reset();
+
+                        for (lifecycle_state, count) in counts {
+                            metrics::INDEXERS_BY_LIFECYCLE_STATE
+                                .with_label_values(&[&lifecycle_state])
+                                .set(count);
+                        }
+                    }
+                    Err(error) => tracing::warn!(?error, "Failed to list indexer states"),
+                }
+
+                tokio::time::sleep(LIFECYCLE_STATE_METRICS_INTERVAL).await;
+            }
+        }
+    });
 
     tokio::spawn({
         let indexer_state_manager = indexer_state_manager.clone();
         let registry = registry.clone();
-        async move { server::init(grpc_port, indexer_state_manager, registry).await }
+        let block_streams_handler = block_streams_handler.clone();
+        let executors_handler = executors_handler.clone();
+        let redis_client = redis_client.clone();
+        let quota_scheduler = quota_scheduler.clone();
+        async move {
+            server::init(
+                grpc_port,
+                indexer_state_manager,
+                registry,
+                block_streams_handler,
+                executors_handler,
+                redis_client,
+                quota_scheduler,
+            )
+            .await
+        }
+    });
+
+    tokio::spawn({
+        let registry = registry.clone();
+        async move {
+            loop {
+                if let Err(error) = registry.sync().await {
+                    tracing::warn!(?error, "Failed to sync registry");
+                }
+
+                tokio::time::sleep(REGISTRY_SYNC_INTERVAL).await;
+            }
+        }
     });
 
     indexer_state_manager.migrate().await?;
 
     let mut lifecycle_tasks = HashMap::<String, JoinHandle<()>>::new();
+    let mut fencing_token = 0;
 
     loop {
-        let indexer_registry = registry.fetch().await?;
+        if !ownership.is_active() {
+            if !lifecycle_tasks.is_empty() {
+                tracing::warn!("No longer managing Indexers, stopping all lifecycle managers");
+
+                for (_, task) in lifecycle_tasks.drain() {
+                    task.abort();
+                }
+            }
+
+            sleep(LOOP_THROTTLE_SECONDS).await?;
+            continue;
+        }
 
-        for config in indexer_registry.iter() {
-            if lifecycle_tasks.contains_key(&config.get_full_name()) {
+        // Indexers were claimed with the previous token and must be claimed again, in case they
+        // were taken over in the meantime
+        if ownership.get_fencing_token() != fencing_token {
+            fencing_token = ownership.get_fencing_token();
+
+            for (_, task) in lifecycle_tasks.drain() {
+                task.abort();
+            }
+        }
+
+        let reassigned_tasks: Vec<String> = lifecycle_tasks
+            .keys()
+            .filter(|name| !ownership.owns(name))
+            .cloned()
+            .collect();
+
+        for indexer_name in reassigned_tasks {
+            tracing::info!(
+                indexer_name,
+                "Indexer reassigned, stopping lifecycle manager"
+            );
+
+            if let Some(task) = lifecycle_tasks.remove(&indexer_name) {
+                task.abort();
+            }
+        }
+
+        let indexers = list_managed_indexers(&registry, &indexer_state_manager).await?;
+
+        for config in indexers.iter() {
+            if lifecycle_tasks.contains_key(&config.get_full_name())
+                || !ownership.can_start(&config.get_full_name())
+            {
                 continue;
             }
 
@@ -104,8 +322,15 @@ async fn main() -> anyhow::Result<()> {
                 let block_streams_handler = block_streams_handler.clone();
                 let data_layer_handler = data_layer_handler.clone();
                 let executors_handler = executors_handler.clone();
+                let repair_policy = repair_policy.clone();
+                let quota_scheduler = quota_scheduler.clone();
 
                 async move {
+                    if let Err(error) = redis_client.claim_indexer(&config).await {
+                        tracing::warn!(?error, "Failed to claim indexer");
+                        return;
+                    }
+
                     let lifecycle_manager = LifecycleManager::new(
                         config,
                         &block_streams_handler,
@@ -114,7 +339,10 @@ async fn main() -> anyhow::Result<()> {
                         &registry,
                         &indexer_state_manager,
                         &redis_client,
-                    );
+                    )
+                    .with_repair_policy(repair_policy)
+                    .with_deletion_grace_period(deletion_grace_period)
+                    .with_quota_scheduler(quota_scheduler);
 
                     lifecycle_manager.run().await
                 }
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
+};
+
+use crate::health::HealthChecker;
+
+lazy_static! {
+    pub static ref REGISTRY_STALENESS_SECONDS: IntGauge = register_int_gauge!(
+        "queryapi_coordinator_registry_staleness_seconds",
+        "Seconds since the registry snapshot was last successfully synced",
+    )
+    .unwrap();
+    pub static ref REGISTRY_BLOCK_HEIGHT: IntGauge = register_int_gauge!(
+        "queryapi_coordinator_registry_block_height",
+        "Block height reflected by the registry snapshot",
+    )
+    .unwrap();
+    pub static ref REGISTRY_FETCH_LATENCY_SECONDS: HistogramVec = register_histogram_vec!(
+        "queryapi_coordinator_registry_fetch_latency_seconds",
+        "Time taken to fetch Indexers from the registry contract",
+        &["method"]
+    )
+    .unwrap();
+    pub static ref INDEXERS_BY_LIFECYCLE_STATE: IntGaugeVec = register_int_gauge_vec!(
+        "queryapi_coordinator_indexers_by_lifecycle_state",
+        "Number of Indexers managed by this Coordinator in each lifecycle state",
+        &["state"]
+    )
+    .unwrap();
+    pub static ref INDEXERS_HELD_BY_QUOTA: IntGaugeVec = register_int_gauge_vec!(
+        "queryapi_coordinator_indexers_held_by_quota",
+        "Number of Indexers held back by the quota policy, by type of hold",
+        &["hold"]
+    )
+    .unwrap();
+    pub static ref LIFECYCLE_TRANSITIONS_COUNT: IntCounterVec = register_int_counter_vec!(
+        "queryapi_coordinator_lifecycle_transitions_count",
+        "Number of lifecycle state transitions",
+        &["from", "to"]
+    )
+    .unwrap();
+    pub static ref BLOCK_STREAM_SYNC_FAILURES_COUNT: IntCounterVec = register_int_counter_vec!(
+        "queryapi_coordinator_block_stream_sync_failures_count",
+        "Number of failed attempts to synchronise a Block Stream",
+        &["indexer"]
+    )
+    .unwrap();
+    pub static ref EXECUTOR_SYNC_FAILURES_COUNT: IntCounterVec = register_int_counter_vec!(
+        "queryapi_coordinator_executor_sync_failures_count",
+        "Number of failed attempts to synchronise an Executor",
+        &["indexer"]
+    )
+    .unwrap();
+}
+
+#[get("/metrics")]
+async fn get_metrics() -> impl Responder {
+    let mut buffer = Vec::<u8>::new();
+    let encoder = prometheus::TextEncoder::new();
+    loop {
+        match encoder.encode(&prometheus::gather(), &mut buffer) {
+            Ok(_) => break,
+            Err(err) => {
+                tracing::error!("Error encoding metrics: {}", err);
+            }
+        }
+    }
+    String::from_utf8(buffer).unwrap()
+}
+
+/// Liveness, always succeeds while the server is up. Dependency status is included for
+/// visibility, but an unreachable dependency should not cause the Coordinator to be restarted.
+#[get("/healthz")]
+async fn get_health(health_checker: web::Data<Arc<HealthChecker>>) -> impl Responder {
+    HttpResponse::Ok().json(health_checker.get_status())
+}
+
+/// Readiness, fails if any dependency is unreachable
+#[get("/readyz")]
+async fn get_readiness(health_checker: web::Data<Arc<HealthChecker>>) -> impl Responder {
+    let status = health_checker.get_status();
+
+    if status.is_ready() {
+        HttpResponse::Ok().json(status)
+    } else {
+        HttpResponse::ServiceUnavailable().json(status)
+    }
+}
+
+pub(crate) fn init_server(
+    port: u16,
+    health_checker: Arc<HealthChecker>,
+) -> anyhow::Result<actix_web::dev::Server> {
+    tracing::info!("Starting metrics server on 0.0.0.0:{port}");
+
+    Ok(HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(health_checker.clone()))
+            .service(get_metrics)
+            .service(get_health)
+            .service(get_readiness)
+    })
+    .bind(("0.0.0.0", port))?
+    .disable_signals()
+    .workers(1)
+    .run())
+}
@@ -0,0 +1,756 @@
+#![cfg_attr(test, allow(dead_code))]
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use near_primitives::types::AccountId;
+use registry_types::StartBlock;
+
+use crate::indexer_config::IndexerConfig;
+use crate::indexer_state::{IndexerState, IndexerStateManager, QuotaHold};
+use crate::lifecycle::LifecycleState;
+use crate::metrics;
+use crate::redis::{KeyProvider, RedisClient};
+use crate::registry::Registry;
+
+const EVALUATION_INTERVAL: Duration = Duration::from_secs(5);
+/// Roughly 20 minutes of blocks
+pub const DEFAULT_BACKFILL_THRESHOLD: u64 = 1000;
+
+/// Priority of an account when capacity is limited, higher tiers are admitted first
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PriorityTier {
+    High,
+    #[default]
+    Standard,
+    Low,
+}
+
+impl FromStr for PriorityTier {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "high" => Ok(Self::High),
+            "standard" => Ok(Self::Standard),
+            "low" => Ok(Self::Low),
+            _ => anyhow::bail!("Invalid priority tier: {value}"),
+        }
+    }
+}
+
+/// Parses priority tiers in the form `account.near=high,other.near=low`
+pub fn parse_priority_tiers(value: &str) -> anyhow::Result<HashMap<AccountId, PriorityTier>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (account_id, tier) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Expected <account_id>=<tier>, got: {entry}"))?;
+
+            Ok((account_id.trim().parse()?, tier.trim().parse()?))
+        })
+        .collect()
+}
+
+/// Limits the resources each account can consume, so that busy accounts cannot starve others
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaPolicy {
+    /// Number of enabled Indexers per account, beyond which the newest ones are suspended
+    pub max_active_indexers_per_account: Option<usize>,
+    /// Number of Block Streams which can backfill at once, across all accounts. Further
+    /// backfills are queued until a slot is available.
+    pub max_backfilling_streams: Option<usize>,
+    /// Number of blocks behind the chain a Block Stream must be to be considered backfilling
+    pub backfill_threshold: u64,
+    pub priority_tiers: HashMap<AccountId, PriorityTier>,
+}
+
+impl Default for QuotaPolicy {
+    fn default() -> Self {
+        Self {
+            max_active_indexers_per_account: None,
+            max_backfilling_streams: None,
+            backfill_threshold: DEFAULT_BACKFILL_THRESHOLD,
+            priority_tiers: HashMap::new(),
+        }
+    }
+}
+
+/// Point-in-time view of an Indexer, used to evaluate the `QuotaPolicy`
+#[derive(Debug, Clone)]
+pub struct IndexerSnapshot {
+    pub config: IndexerConfig,
+    /// `None` if the lifecycle manager has not yet created state
+    pub state: Option<IndexerState>,
+    pub last_published_block: Option<u64>,
+}
+
+impl IndexerSnapshot {
+    fn is_active(&self) -> bool {
+        match &self.state {
+            None => true,
+            Some(state) => {
+                state.enabled
+                    && !matches!(
+                        state.lifecycle_state,
+                        LifecycleState::Failed | LifecycleState::Deleting | LifecycleState::Deleted
+                    )
+            }
+        }
+    }
+
+    /// Whether the Block Stream has been started for the current version
+    fn is_synced(&self) -> bool {
+        self.state.as_ref().is_some_and(|state| {
+            state.block_stream_synced_at == Some(self.config.get_registry_version())
+        })
+    }
+
+    /// Whether the Block Stream and Executor of some version have been started and not since
+    /// suspended
+    fn is_running(&self) -> bool {
+        self.state.as_ref().is_some_and(|state| {
+            !matches!(
+                state.lifecycle_state,
+                LifecycleState::Initializing
+                    | LifecycleState::Suspending
+                    | LifecycleState::Suspended
+            )
+        })
+    }
+
+    /// Whether a shadow version is catching up, see `LifecycleState::RollingOut`
+    fn is_rolling_out(&self) -> bool {
+        self.state.as_ref().is_some_and(|state| {
+            matches!(
+                state.lifecycle_state,
+                LifecycleState::RollingOut | LifecycleState::Promoting
+            )
+        })
+    }
+
+    /// Block height the Block Stream is at, or will start from once started
+    fn get_block_height(&self) -> Option<u64> {
+        let start_block_height = match self.config.start_block {
+            StartBlock::Height(height) => Some(height),
+            StartBlock::Latest | StartBlock::Continue => None,
+        };
+
+        if self.is_synced() {
+            return self.last_published_block.or(start_block_height);
+        }
+
+        match self.config.start_block {
+            StartBlock::Continue => self.last_published_block,
+            _ => start_block_height,
+        }
+    }
+}
+
+impl QuotaPolicy {
+    pub fn is_unrestricted(&self) -> bool {
+        self.max_active_indexers_per_account.is_none() && self.max_backfilling_streams.is_none()
+    }
+
+    pub fn get_priority_tier(&self, account_id: &AccountId) -> PriorityTier {
+        self.priority_tiers
+            .get(account_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Determines which Indexers must be held back to comply with the policy, keyed by full name.
+    ///
+    /// Decisions only depend on the provided snapshots and previous holds, so all Coordinator
+    /// instances reach the same decisions without coordinating. Indexers which are already running
+    /// or backfilling are never preempted, so that newly registered or re-enabled Indexers cannot
+    /// disrupt existing ones.
+    pub fn evaluate(
+        &self,
+        indexers: &[IndexerSnapshot],
+        chain_block_height: Option<u64>,
+        previous_holds: &HashMap<String, QuotaHold>,
+    ) -> HashMap<String, QuotaHold> {
+        let mut holds = HashMap::new();
+
+        let active_indexers: Vec<&IndexerSnapshot> = indexers
+            .iter()
+            .filter(|indexer| indexer.is_active())
+            .collect();
+
+        if let Some(max_active_indexers) = self.max_active_indexers_per_account {
+            let mut indexers_by_account = HashMap::<&AccountId, Vec<&IndexerSnapshot>>::new();
+
+            for indexer in &active_indexers {
+                indexers_by_account
+                    .entry(&indexer.config.account_id)
+                    .or_default()
+                    .push(indexer);
+            }
+
+            for account_indexers in indexers_by_account.values_mut() {
+                account_indexers.sort_by_key(|indexer| {
+                    (
+                        !indexer.is_running(),
+                        indexer.config.created_at_block_height,
+                        indexer.config.function_name.clone(),
+                    )
+                });
+
+                for indexer in account_indexers.iter().skip(max_active_indexers) {
+                    holds.insert(
+                        indexer.config.get_full_name(),
+                        QuotaHold::Suspended {
+                            reason: format!(
+                                "Account has reached its limit of {max_active_indexers} active indexers"
+                            ),
+                        },
+                    );
+                }
+            }
+        }
+
+        let Some(max_backfilling_streams) = self.max_backfilling_streams else {
+            return holds;
+        };
+
+        // Progress cannot be determined without the chain height, so the previous queue is
+        // retained rather than admitting every backfill at once
+        let Some(chain_block_height) = chain_block_height else {
+            for indexer in active_indexers {
+                let name = indexer.config.get_full_name();
+
+                if indexer.is_synced() || holds.contains_key(&name) {
+                    continue;
+                }
+
+                if let Some(hold @ QuotaHold::Queued { .. }) = previous_holds.get(&name) {
+                    holds.insert(name, hold.clone());
+                }
+            }
+
+            return holds;
+        };
+
+        let is_behind = |indexer: &IndexerSnapshot| {
+            indexer.get_block_height().is_some_and(|block_height| {
+                chain_block_height.saturating_sub(block_height) > self.backfill_threshold
+            })
+        };
+
+        let mut backfilling_streams = 0;
+        let mut slots_by_account = HashMap::<&AccountId, usize>::new();
+        let mut pending = Vec::new();
+
+        for indexer in active_indexers
+            .into_iter()
+            .filter(|indexer| !holds.contains_key(&indexer.config.get_full_name()))
+        {
+            if indexer.is_rolling_out() || (indexer.is_synced() && is_behind(indexer)) {
+                backfilling_streams += 1;
+                *slots_by_account
+                    .entry(&indexer.config.account_id)
+                    .or_default() += 1;
+            } else if !indexer.is_synced() && is_behind(indexer) {
+                pending.push(indexer);
+            }
+        }
+
+        pending.sort_by_key(|indexer| {
+            (
+                self.get_priority_tier(&indexer.config.account_id),
+                indexer.config.get_registry_version(),
+                indexer.config.get_full_name(),
+            )
+        });
+
+        // Within a tier, accounts take turns based on the slots they already hold, so that a
+        // single account cannot claim all slots. The sort is stable, so each account's own
+        // backfills remain in the order they were published.
+        let mut queue: Vec<_> = pending
+            .into_iter()
+            .map(|indexer| {
+                let slots = slots_by_account
+                    .entry(&indexer.config.account_id)
+                    .or_default();
+                let rank = (self.get_priority_tier(&indexer.config.account_id), *slots);
+                *slots += 1;
+
+                (rank, indexer)
+            })
+            .collect();
+        queue.sort_by_key(|(rank, _)| *rank);
+
+        let available_slots = max_backfilling_streams.saturating_sub(backfilling_streams);
+
+        for (index, (_, indexer)) in queue.into_iter().skip(available_slots).enumerate() {
+            holds.insert(
+                indexer.config.get_full_name(),
+                QuotaHold::Queued {
+                    reason: format!("Waiting for one of {max_backfilling_streams} backfill slots"),
+                    position: index + 1,
+                },
+            );
+        }
+
+        holds
+    }
+}
+
+/// Periodically evaluates the `QuotaPolicy` across all Indexers, so that lifecycle managers can
+/// enforce it without making requests themselves
+pub struct QuotaScheduler {
+    policy: QuotaPolicy,
+    /// `None` until the policy has been successfully evaluated
+    holds: RwLock<Option<HashMap<String, QuotaHold>>>,
+}
+
+impl QuotaScheduler {
+    pub fn new(policy: QuotaPolicy) -> Self {
+        Self {
+            policy,
+            holds: RwLock::new(None),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_holds(holds: HashMap<String, QuotaHold>) -> Self {
+        Self {
+            policy: QuotaPolicy::default(),
+            holds: RwLock::new(Some(holds)),
+        }
+    }
+
+    pub fn get_policy(&self) -> &QuotaPolicy {
+        &self.policy
+    }
+
+    /// Hold on the Indexer as of the last evaluation, `None` if it is within quota.
+    ///
+    /// Until the first evaluation, backfills are queued rather than admitted, as admitting them
+    /// could exceed the available slots.
+    pub fn get_hold(
+        &self,
+        indexer_config: &IndexerConfig,
+        indexer_state: &IndexerState,
+    ) -> Option<QuotaHold> {
+        match self.holds.read().unwrap().as_ref() {
+            Some(holds) => holds.get(&indexer_config.get_full_name()).cloned(),
+            None => (self.policy.max_backfilling_streams.is_some()
+                && Self::may_backfill(indexer_config, indexer_state))
+            .then(|| QuotaHold::Queued {
+                reason: "Waiting for backfill slots to be evaluated".to_string(),
+                position: 0,
+            }),
+        }
+    }
+
+    /// Whether starting the current version may require backfilling, i.e. it has not been started
+    /// yet and does not start from the latest block
+    fn may_backfill(indexer_config: &IndexerConfig, indexer_state: &IndexerState) -> bool {
+        indexer_state.block_stream_synced_at != Some(indexer_config.get_registry_version())
+            && !matches!(indexer_config.start_block, StartBlock::Latest)
+    }
+
+    pub async fn refresh(
+        &self,
+        registry: &Registry,
+        state_manager: &IndexerStateManager,
+        redis_client: &RedisClient,
+    ) -> anyhow::Result<()> {
+        let chain_block_height = registry.get_block_height();
+
+        // Backfills remain queued until they can first be evaluated, see `get_hold`
+        if chain_block_height.is_none()
+            && self.policy.max_backfilling_streams.is_some()
+            && self.holds.read().unwrap().is_none()
+        {
+            anyhow::bail!("Chain block height is unknown, backfills cannot be evaluated");
+        }
+
+        let indexer_registry = registry.fetch().await?;
+
+        let mut states: HashMap<String, IndexerState> = state_manager
+            .list()
+            .await?
+            .into_iter()
+            .map(|state| (state.prefix(), state))
+            .collect();
+
+        let configs: Vec<IndexerConfig> = indexer_registry.iter().cloned().collect();
+
+        // Progress is only needed to determine whether Block Streams are backfilling
+        let last_published_blocks = if self.policy.max_backfilling_streams.is_some() {
+            redis_client.get_last_published_blocks(&configs).await?
+        } else {
+            vec![None; configs.len()]
+        };
+
+        let indexers: Vec<IndexerSnapshot> = configs
+            .into_iter()
+            .zip(last_published_blocks)
+            .map(|(config, last_published_block)| IndexerSnapshot {
+                state: states.remove(&config.get_full_name()),
+                config,
+                last_published_block,
+            })
+            .collect();
+
+        let previous_holds = self.holds.read().unwrap().clone().unwrap_or_default();
+        let holds = self
+            .policy
+            .evaluate(&indexers, chain_block_height, &previous_holds);
+
+        metrics::INDEXERS_HELD_BY_QUOTA.reset();
+
+        for hold in holds.values() {
+            let label = match hold {
+                QuotaHold::Suspended { .. } => "Suspended",
+                QuotaHold::Queued { .. } => "Queued",
+            };
+
+            metrics::INDEXERS_HELD_BY_QUOTA
+                .with_label_values(&[label])
+                .inc();
+        }
+
+        *self.holds.write().unwrap() = Some(holds);
+
+        Ok(())
+    }
+
+    pub async fn run(
+        &self,
+        registry: &Registry,
+        state_manager: &IndexerStateManager,
+        redis_client: &RedisClient,
+    ) {
+        if self.policy.is_unrestricted() {
+            tracing::info!("Quota policy is unrestricted, nothing to enforce");
+            return;
+        }
+
+        loop {
+            if let Err(error) = self.refresh(registry, state_manager, redis_client).await {
+                tracing::warn!(?error, "Failed to evaluate quota policy");
+            }
+
+            tokio::time::sleep(EVALUATION_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::registry::IndexerRegistry;
+
+    fn indexer(
+        account_id: &str,
+        function_name: &str,
+        created_at_block_height: u64,
+        start_block: StartBlock,
+    ) -> IndexerSnapshot {
+        IndexerSnapshot {
+            config: IndexerConfig {
+                account_id: account_id.parse().unwrap(),
+                function_name: function_name.to_string(),
+                start_block,
+                created_at_block_height,
+                updated_at_block_height: None,
+                ..Default::default()
+            },
+            state: None,
+            last_published_block: None,
+        }
+    }
+
+    fn with_state(mut indexer: IndexerSnapshot, synced: bool) -> IndexerSnapshot {
+        indexer.state = Some(IndexerState {
+            account_id: indexer.config.account_id.clone(),
+            function_name: indexer.config.function_name.clone(),
+            block_stream_synced_at: synced.then(|| indexer.config.get_registry_version()),
+            lifecycle_state: LifecycleState::Running,
+            ..Default::default()
+        });
+        indexer
+    }
+
+    #[test]
+    fn parses_priority_tiers() {
+        let tiers = parse_priority_tiers("morgs.near=high, flatirons.near=LOW,").unwrap();
+
+        assert_eq!(
+            tiers,
+            HashMap::from([
+                ("morgs.near".parse().unwrap(), PriorityTier::High),
+                ("flatirons.near".parse().unwrap(), PriorityTier::Low),
+            ])
+        );
+        assert!(parse_priority_tiers("morgs.near").is_err());
+        assert!(parse_priority_tiers("morgs.near=urgent").is_err());
+    }
+
+    #[test]
+    fn suspends_newest_indexers_over_account_limit() {
+        let policy = QuotaPolicy {
+            max_active_indexers_per_account: Some(2),
+            ..Default::default()
+        };
+
+        let mut disabled = with_state(
+            indexer("morgs.near", "disabled", 1, StartBlock::Latest),
+            true,
+        );
+        disabled.state.as_mut().unwrap().enabled = false;
+
+        let indexers = vec![
+            indexer("morgs.near", "newest", 30, StartBlock::Latest),
+            indexer("morgs.near", "oldest", 10, StartBlock::Latest),
+            indexer("morgs.near", "middle", 20, StartBlock::Latest),
+            disabled,
+            indexer("flatirons.near", "other", 40, StartBlock::Latest),
+        ];
+
+        let holds = policy.evaluate(&indexers, Some(100), &HashMap::new());
+
+        assert_eq!(
+            holds,
+            HashMap::from([(
+                "morgs.near/newest".to_string(),
+                QuotaHold::Suspended {
+                    reason: "Account has reached its limit of 2 active indexers".to_string()
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn queues_backfills_once_slots_are_taken() {
+        let policy = QuotaPolicy {
+            max_backfilling_streams: Some(1),
+            backfill_threshold: 10,
+            ..Default::default()
+        };
+
+        let mut backfilling = with_state(
+            indexer("morgs.near", "backfilling", 1, StartBlock::Height(0)),
+            true,
+        );
+        backfilling.last_published_block = Some(50);
+
+        let indexers = vec![
+            backfilling,
+            indexer("morgs.near", "pending", 2, StartBlock::Height(0)),
+            indexer("morgs.near", "recent", 3, StartBlock::Height(95)),
+            indexer("morgs.near", "latest", 4, StartBlock::Latest),
+        ];
+
+        let holds = policy.evaluate(&indexers, Some(100), &HashMap::new());
+
+        assert_eq!(
+            holds,
+            HashMap::from([(
+                "morgs.near/pending".to_string(),
+                QuotaHold::Queued {
+                    reason: "Waiting for one of 1 backfill slots".to_string(),
+                    position: 1
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn admits_backfills_by_tier_then_shares_slots_between_accounts() {
+        let policy = QuotaPolicy {
+            max_backfilling_streams: Some(2),
+            backfill_threshold: 10,
+            priority_tiers: HashMap::from([("priority.near".parse().unwrap(), PriorityTier::High)]),
+            ..Default::default()
+        };
+
+        let indexers = vec![
+            indexer("busy.near", "first", 1, StartBlock::Height(0)),
+            indexer("busy.near", "second", 2, StartBlock::Height(0)),
+            indexer("quiet.near", "first", 3, StartBlock::Height(0)),
+            indexer("priority.near", "first", 4, StartBlock::Height(0)),
+        ];
+
+        let holds = policy.evaluate(&indexers, Some(100), &HashMap::new());
+
+        let mut queued: Vec<_> = holds
+            .iter()
+            .map(|(name, hold)| match hold {
+                QuotaHold::Queued { position, .. } => (*position, name.clone()),
+                QuotaHold::Suspended { .. } => panic!("unexpected suspension of {name}"),
+            })
+            .collect();
+        queued.sort();
+
+        assert_eq!(
+            queued,
+            vec![
+                (1, "quiet.near/first".to_string()),
+                (2, "busy.near/second".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn retains_queued_backfills_without_chain_height() {
+        let policy = QuotaPolicy {
+            max_backfilling_streams: Some(0),
+            ..Default::default()
+        };
+
+        let indexers = vec![
+            indexer("morgs.near", "queued", 1, StartBlock::Height(0)),
+            indexer("morgs.near", "unknown", 2, StartBlock::Height(0)),
+            with_state(
+                indexer("morgs.near", "synced", 3, StartBlock::Height(0)),
+                true,
+            ),
+        ];
+
+        let queued = QuotaHold::Queued {
+            reason: "Waiting for one of 0 backfill slots".to_string(),
+            position: 1,
+        };
+
+        let holds = policy.evaluate(
+            &indexers,
+            None,
+            &HashMap::from([
+                ("morgs.near/queued".to_string(), queued.clone()),
+                ("morgs.near/synced".to_string(), queued.clone()),
+            ]),
+        );
+
+        assert_eq!(
+            holds,
+            HashMap::from([("morgs.near/queued".to_string(), queued)])
+        );
+    }
+
+    #[test]
+    fn does_not_preempt_running_indexers_over_account_limit() {
+        let policy = QuotaPolicy {
+            max_active_indexers_per_account: Some(1),
+            ..Default::default()
+        };
+
+        let mut reenabled =
+            with_state(indexer("morgs.near", "older", 10, StartBlock::Latest), true);
+        reenabled.state.as_mut().unwrap().lifecycle_state = LifecycleState::Suspended;
+
+        let indexers = vec![
+            reenabled,
+            with_state(indexer("morgs.near", "newer", 20, StartBlock::Latest), true),
+        ];
+
+        let holds = policy.evaluate(&indexers, Some(100), &HashMap::new());
+
+        assert_eq!(
+            holds,
+            HashMap::from([(
+                "morgs.near/older".to_string(),
+                QuotaHold::Suspended {
+                    reason: "Account has reached its limit of 1 active indexers".to_string()
+                }
+            )])
+        );
+    }
+
+    #[tokio::test]
+    async fn refreshes_holds() {
+        let config = IndexerConfig::default();
+
+        let mut registry = Registry::default();
+        registry.expect_fetch().returning({
+            let config = config.clone();
+            move || {
+                Ok(IndexerRegistry::from(&[(
+                    config.account_id.clone(),
+                    HashMap::from([(config.function_name.clone(), config.clone())]),
+                )]))
+            }
+        });
+        registry
+            .expect_get_block_height()
+            .returning(|| Some(100_000));
+
+        let mut state_manager = IndexerStateManager::default();
+        state_manager.expect_list().returning(|| Ok(vec![]));
+
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_get_last_published_blocks::<IndexerConfig>()
+            .returning(|configs| Ok(vec![None; configs.len()]))
+            .once();
+
+        let scheduler = QuotaScheduler::new(QuotaPolicy {
+            max_backfilling_streams: Some(0),
+            ..Default::default()
+        });
+
+        scheduler
+            .refresh(&registry, &state_manager, &redis_client)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            scheduler.get_hold(&config, &IndexerState::default()),
+            Some(QuotaHold::Queued {
+                reason: "Waiting for one of 0 backfill slots".to_string(),
+                position: 1
+            })
+        );
+    }
+
+    #[test]
+    fn queues_backfills_until_evaluated() {
+        let config = IndexerConfig {
+            start_block: StartBlock::Height(100),
+            ..Default::default()
+        };
+
+        let scheduler = QuotaScheduler::new(QuotaPolicy {
+            max_backfilling_streams: Some(1),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            scheduler.get_hold(&config, &IndexerState::default()),
+            Some(QuotaHold::Queued {
+                reason: "Waiting for backfill slots to be evaluated".to_string(),
+                position: 0
+            })
+        );
+        assert_eq!(
+            scheduler.get_hold(
+                &config,
+                &IndexerState {
+                    block_stream_synced_at: Some(config.get_registry_version()),
+                    ..Default::default()
+                }
+            ),
+            None
+        );
+        assert_eq!(
+            scheduler.get_hold(
+                &IndexerConfig {
+                    start_block: StartBlock::Latest,
+                    ..Default::default()
+                },
+                &IndexerState::default()
+            ),
+            None
+        );
+    }
+}
@@ -1,6 +1,9 @@
 #![cfg_attr(test, allow(dead_code))]
 
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use anyhow::Context;
 use redis::{aio::ConnectionManager, FromRedisValue, ToRedisArgs};
@@ -24,6 +27,18 @@ pub trait KeyProvider {
     fn get_state_key(&self) -> String {
         format!("{}:state", self.prefix())
     }
+
+    fn get_history_stream_key(&self) -> String {
+        format!("{}:history", self.prefix())
+    }
+
+    fn get_requests_key(&self) -> String {
+        format!("{}:requests", self.prefix())
+    }
+
+    fn get_owner_key(&self) -> String {
+        format!("{}:owner", self.prefix())
+    }
 }
 
 #[cfg(test)]
@@ -34,10 +49,16 @@ pub use RedisClientImpl as RedisClient;
 #[derive(Clone)]
 pub struct RedisClientImpl {
     connection: ConnectionManager,
+    /// Lease held by this Coordinator and the fencing token it was issued with, shared across
+    /// clones. State writes are rejected unless the lease is still held with this token, and the
+    /// Indexer was last claimed with it, see `claim_indexer`, `LeaderElection` and `Membership`
+    fence: Arc<RwLock<Option<(String, u64)>>>,
 }
 
 impl RedisClientImpl {
     const INDEXER_STATES_SET: &'static str = "indexer_states";
+    const FENCING_TOKEN_KEY: &'static str = "coordinator:fencing_token";
+    const HISTORY_ENTRY_FIELD: &'static str = "entry";
 
     pub async fn connect(redis_url: &str) -> anyhow::Result<Self> {
         let connection = redis::Client::open(redis_url)?
@@ -45,7 +66,124 @@ impl RedisClientImpl {
             .await
             .context("Unable to connect to Redis")?;
 
-        Ok(Self { connection })
+        Ok(Self {
+            connection,
+            fence: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    /// Sets the lease and fencing token used to guard state writes, `None` prevents all writes
+    pub fn set_fence(&self, fence: Option<(String, u64)>) {
+        *self.fence.write().unwrap() = fence;
+    }
+
+    fn get_fence(&self) -> (String, u64) {
+        self.fence
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or((Self::FENCING_TOKEN_KEY.to_string(), 0))
+    }
+
+    /// Identifies the holder of a fence as the owner of an Indexer, unique across Coordinators as
+    /// fencing tokens are never reused
+    fn get_owner(lease_key: &str, fencing_token: u64) -> String {
+        format!("{lease_key}#{fencing_token}")
+    }
+
+    /// Claims the Indexer for the current fence, so that state writes from any previous owner
+    /// are rejected, even if it still holds a valid lease
+    pub async fn claim_indexer<P>(&self, key_provider: &P) -> anyhow::Result<()>
+    where
+        P: KeyProvider + 'static,
+    {
+        let script = redis::Script::new(
+            r"
+            if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+                return redis.error_reply('stale fencing token')
+            end
+            redis.call('SET', KEYS[2], ARGV[2])
+            return redis.status_reply('OK')
+            ",
+        );
+
+        let (lease_key, fencing_token) = self.get_fence();
+
+        tracing::debug!(
+            "CLAIM {:?} {lease_key}#{fencing_token}",
+            key_provider.get_owner_key()
+        );
+
+        script
+            .key(&lease_key)
+            .key(key_provider.get_owner_key())
+            .arg(fencing_token)
+            .arg(Self::get_owner(&lease_key, fencing_token))
+            .invoke_async(&mut self.connection.clone())
+            .await
+            .context(format!(
+                "Failed to claim indexer: {}",
+                key_provider.get_owner_key()
+            ))
+    }
+
+    /// Attempts to acquire the lease if it is not already held, returning a new fencing token on
+    /// success. The token is stored as the value of the lease.
+    pub async fn acquire_lease(&self, key: &str, ttl: Duration) -> anyhow::Result<Option<u64>> {
+        let script = redis::Script::new(
+            r"
+            if redis.call('EXISTS', KEYS[1]) == 0 then
+                local fencing_token = redis.call('INCR', KEYS[2])
+                redis.call('SET', KEYS[1], fencing_token, 'PX', ARGV[1])
+                return fencing_token
+            end
+            return 0
+            ",
+        );
+
+        let fencing_token: u64 = script
+            .key(key)
+            .key(Self::FENCING_TOKEN_KEY)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.connection.clone())
+            .await
+            .context(format!("Failed to acquire lease: {key}"))?;
+
+        Ok((fencing_token > 0).then_some(fencing_token))
+    }
+
+    /// Extends the lease, returning `false` if it is no longer held with `fencing_token`
+    pub async fn renew_lease(
+        &self,
+        key: &str,
+        fencing_token: u64,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let script = redis::Script::new(
+            r"
+            if redis.call('GET', KEYS[1]) == ARGV[1] then
+                return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+            end
+            return 0
+            ",
+        );
+
+        let renewed: u64 = script
+            .key(key)
+            .arg(fencing_token)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.connection.clone())
+            .await
+            .context(format!("Failed to renew lease: {key}"))?;
+
+        Ok(renewed == 1)
+    }
+
+    pub async fn ping(&self) -> anyhow::Result<()> {
+        redis::cmd("PING")
+            .query_async(&mut self.connection.clone())
+            .await
+            .context("PING")
     }
 
     pub async fn get<T, U>(&self, key: T) -> anyhow::Result<Option<U>>
@@ -138,6 +276,53 @@ impl RedisClientImpl {
             .context(format!("SADD {set:?} {member:?}"))
     }
 
+    pub async fn zadd<S, M>(&self, set: S, member: M, score: u64) -> anyhow::Result<()>
+    where
+        S: ToRedisArgs + Debug + Send + Sync + 'static,
+        M: ToRedisArgs + Debug + Send + Sync + 'static,
+    {
+        tracing::debug!("ZADD {set:?} {score} {member:?}");
+
+        redis::cmd("ZADD")
+            .arg(&set)
+            .arg(score)
+            .arg(&member)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("ZADD {set:?} {score} {member:?}"))
+    }
+
+    /// Removes all members with a score less than or equal to `max_score`
+    pub async fn zremrangebyscore<S>(&self, set: S, max_score: u64) -> anyhow::Result<()>
+    where
+        S: ToRedisArgs + Debug + Send + Sync + 'static,
+    {
+        tracing::debug!("ZREMRANGEBYSCORE {set:?} -inf {max_score}");
+
+        redis::cmd("ZREMRANGEBYSCORE")
+            .arg(&set)
+            .arg("-inf")
+            .arg(max_score)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("ZREMRANGEBYSCORE {set:?} -inf {max_score}"))
+    }
+
+    pub async fn zrange<S>(&self, set: S) -> anyhow::Result<Vec<String>>
+    where
+        S: ToRedisArgs + Debug + Send + Sync + 'static,
+    {
+        tracing::debug!("ZRANGE {set:?} 0 -1");
+
+        redis::cmd("ZRANGE")
+            .arg(&set)
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("ZRANGE {set:?} 0 -1"))
+    }
+
     pub async fn get_last_published_block<P>(&self, key_provider: &P) -> anyhow::Result<Option<u64>>
     where
         P: KeyProvider + 'static,
@@ -146,6 +331,48 @@ impl RedisClientImpl {
             .await
     }
 
+    /// Fetches the last published block of each Indexer in a single request. Values which
+    /// cannot be parsed are treated as unknown, rather than failing the whole batch.
+    pub async fn get_last_published_blocks<P>(
+        &self,
+        key_providers: &[P],
+    ) -> anyhow::Result<Vec<Option<u64>>>
+    where
+        P: KeyProvider + 'static,
+    {
+        if key_providers.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let keys: Vec<String> = key_providers
+            .iter()
+            .map(|key_provider| key_provider.get_last_published_block_key())
+            .collect();
+
+        tracing::debug!("MGET {} keys", keys.len());
+
+        let values: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("MGET {} keys", keys.len()))?;
+
+        Ok(keys
+            .iter()
+            .zip(values)
+            .map(|(key, value)| {
+                value.and_then(|value| {
+                    value
+                        .parse()
+                        .map_err(|error| {
+                            tracing::warn!(?error, key, value, "Invalid last published block")
+                        })
+                        .ok()
+                })
+            })
+            .collect())
+    }
+
     pub async fn clear_block_stream<P>(&self, key_provider: &P) -> anyhow::Result<()>
     where
         P: KeyProvider + 'static,
@@ -156,6 +383,23 @@ impl RedisClientImpl {
             .context(format!("Failed to clear Redis Stream: {}", stream_key))
     }
 
+    /// Number of messages in the Redis Stream which are yet to be processed, as Executors delete
+    /// messages once processed
+    pub async fn get_stream_length<P>(&self, key_provider: &P) -> anyhow::Result<u64>
+    where
+        P: KeyProvider + 'static,
+    {
+        let stream_key = key_provider.get_redis_stream_key();
+
+        tracing::debug!("XLEN {stream_key:?}");
+
+        redis::cmd("XLEN")
+            .arg(&stream_key)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("XLEN {stream_key:?}"))
+    }
+
     pub async fn get_indexer_state<P>(&self, key_provider: &P) -> anyhow::Result<Option<String>>
     where
         P: KeyProvider + 'static,
@@ -163,24 +407,193 @@ impl RedisClientImpl {
         self.get(key_provider.get_state_key()).await
     }
 
-    pub async fn set_indexer_state<P>(&self, key_provider: &P, state: String) -> anyhow::Result<()>
+    /// Writes the state, guarded by the fencing token and ownership of the Indexer, and
+    /// acknowledges the requests which were merged in to it. Requests are only removed if they have not been replaced since.
+    pub async fn set_indexer_state<P>(
+        &self,
+        key_provider: &P,
+        state: String,
+        acknowledged_requests: Vec<(String, String)>,
+    ) -> anyhow::Result<()>
     where
         P: KeyProvider + 'static,
     {
-        self.set(key_provider.get_state_key(), state).await?;
+        let script = redis::Script::new(
+            r"
+            if redis.call('GET', KEYS[3]) ~= ARGV[2] or redis.call('GET', KEYS[5]) ~= ARGV[3] then
+                return redis.error_reply('stale fencing token')
+            end
+            redis.call('SET', KEYS[1], ARGV[1])
+            redis.call('SADD', KEYS[2], KEYS[1])
+            for i = 4, #ARGV, 2 do
+                if redis.call('HGET', KEYS[4], ARGV[i]) == ARGV[i + 1] then
+                    redis.call('HDEL', KEYS[4], ARGV[i])
+                end
+            end
+            return redis.status_reply('OK')
+            ",
+        );
+
+        tracing::debug!("SET: {:?}, {:?}", key_provider.get_state_key(), state);
+
+        let (lease_key, fencing_token) = self.get_fence();
+
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(key_provider.get_state_key())
+            .key(Self::INDEXER_STATES_SET)
+            .key(&lease_key)
+            .key(key_provider.get_requests_key())
+            .key(key_provider.get_owner_key())
+            .arg(state)
+            .arg(fencing_token)
+            .arg(Self::get_owner(&lease_key, fencing_token));
+
+        for (field, value) in acknowledged_requests {
+            invocation.arg(field).arg(value);
+        }
 
-        self.sadd(Self::INDEXER_STATES_SET, key_provider.get_state_key())
+        invocation
+            .invoke_async(&mut self.connection.clone())
             .await
+            .context(format!(
+                "Failed to set state: {}",
+                key_provider.get_state_key()
+            ))
     }
 
     pub async fn delete_indexer_state<P>(&self, key_provider: &P) -> anyhow::Result<()>
     where
         P: KeyProvider + 'static,
     {
-        self.del(key_provider.get_state_key()).await?;
+        let script = redis::Script::new(
+            r"
+            if redis.call('GET', KEYS[3]) ~= ARGV[1] or redis.call('GET', KEYS[5]) ~= ARGV[2] then
+                return redis.error_reply('stale fencing token')
+            end
+            redis.call('DEL', KEYS[1], KEYS[4], KEYS[5])
+            redis.call('SREM', KEYS[2], KEYS[1])
+            return redis.status_reply('OK')
+            ",
+        );
+
+        tracing::debug!("DEL {:?}", key_provider.get_state_key());
+
+        let (lease_key, fencing_token) = self.get_fence();
+
+        script
+            .key(key_provider.get_state_key())
+            .key(Self::INDEXER_STATES_SET)
+            .key(&lease_key)
+            .key(key_provider.get_requests_key())
+            .key(key_provider.get_owner_key())
+            .arg(fencing_token)
+            .arg(Self::get_owner(&lease_key, fencing_token))
+            .invoke_async(&mut self.connection.clone())
+            .await
+            .context(format!(
+                "Failed to delete state: {}",
+                key_provider.get_state_key()
+            ))
+    }
+
+    /// Returns pending requests, keyed by field, see `IndexerRequest`
+    pub async fn get_indexer_requests<P>(
+        &self,
+        key_provider: &P,
+    ) -> anyhow::Result<HashMap<String, String>>
+    where
+        P: KeyProvider + 'static,
+    {
+        let requests_key = key_provider.get_requests_key();
+
+        tracing::debug!("HGETALL {requests_key:?}");
+
+        redis::cmd("HGETALL")
+            .arg(&requests_key)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("HGETALL {requests_key:?}"))
+    }
+
+    /// Stores a request, replacing any pending request for the same field. Requests are not
+    /// fenced, as they are only ever applied by the lifecycle.
+    pub async fn set_indexer_request<P>(
+        &self,
+        key_provider: &P,
+        field: &str,
+        value: String,
+    ) -> anyhow::Result<()>
+    where
+        P: KeyProvider + 'static,
+    {
+        let requests_key = key_provider.get_requests_key();
 
-        self.srem(Self::INDEXER_STATES_SET, key_provider.get_state_key())
+        tracing::debug!("HSET {requests_key:?} {field:?} {value:?}");
+
+        redis::cmd("HSET")
+            .arg(&requests_key)
+            .arg(field)
+            .arg(&value)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("HSET {requests_key:?} {field:?} {value:?}"))
+    }
+
+    /// Appends an entry to the history stream, trimming it to roughly `max_len` entries
+    pub async fn append_indexer_history<P>(
+        &self,
+        key_provider: &P,
+        entry: String,
+        max_len: usize,
+    ) -> anyhow::Result<()>
+    where
+        P: KeyProvider + 'static,
+    {
+        let stream_key = key_provider.get_history_stream_key();
+
+        tracing::debug!("XADD {stream_key:?} MAXLEN ~ {max_len} * {entry:?}");
+
+        redis::cmd("XADD")
+            .arg(&stream_key)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(max_len)
+            .arg("*")
+            .arg(Self::HISTORY_ENTRY_FIELD)
+            .arg(&entry)
+            .query_async(&mut self.connection.clone())
+            .await
+            .context(format!("Failed to append history: {stream_key}"))
+    }
+
+    /// Returns up to `count` entries from the history stream, most recent first
+    pub async fn get_indexer_history<P>(
+        &self,
+        key_provider: &P,
+        count: usize,
+    ) -> anyhow::Result<Vec<String>>
+    where
+        P: KeyProvider + 'static,
+    {
+        let stream_key = key_provider.get_history_stream_key();
+
+        tracing::debug!("XREVRANGE {stream_key:?} + - COUNT {count}");
+
+        let entries: Vec<(String, HashMap<String, String>)> = redis::cmd("XREVRANGE")
+            .arg(&stream_key)
+            .arg("+")
+            .arg("-")
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut self.connection.clone())
             .await
+            .context(format!("Failed to get history: {stream_key}"))?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(_, mut fields)| fields.remove(Self::HISTORY_ENTRY_FIELD))
+            .collect())
     }
 
     pub async fn list_indexer_states(&self) -> anyhow::Result<Vec<String>> {
@@ -216,6 +629,21 @@ mockall::mock! {
             &self,
             key_provider: &P,
             state: String,
+            acknowledged_requests: Vec<(String, String)>,
+        ) -> anyhow::Result<()>
+            where P: KeyProvider + 'static;
+
+        pub async fn get_indexer_requests<P>(
+            &self,
+            key_provider: &P,
+        ) -> anyhow::Result<HashMap<String, String>>
+            where P: KeyProvider + 'static;
+
+        pub async fn set_indexer_request<P>(
+            &self,
+            key_provider: &P,
+            field: &str,
+            value: String,
         ) -> anyhow::Result<()>
             where P: KeyProvider + 'static;
 
@@ -225,9 +653,35 @@ mockall::mock! {
         ) -> anyhow::Result<Option<u64>>
             where P: KeyProvider + 'static;
 
+        pub async fn get_last_published_blocks<P>(
+            &self,
+            key_providers: &[P],
+        ) -> anyhow::Result<Vec<Option<u64>>>
+            where P: KeyProvider + 'static;
+
         pub async fn clear_block_stream<P>(&self, key_provider: &P) -> anyhow::Result<()>
             where P: KeyProvider + 'static;
 
+        pub async fn get_stream_length<P>(&self, key_provider: &P) -> anyhow::Result<u64>
+            where P: KeyProvider + 'static;
+
+        pub async fn append_indexer_history<P>(
+            &self,
+            key_provider: &P,
+            entry: String,
+            max_len: usize,
+        ) -> anyhow::Result<()>
+            where P: KeyProvider + 'static;
+
+        pub async fn get_indexer_history<P>(
+            &self,
+            key_provider: &P,
+            count: usize,
+        ) -> anyhow::Result<Vec<String>>
+            where P: KeyProvider + 'static;
+
+        pub async fn ping(&self) -> anyhow::Result<()>;
+
         pub async fn get<T, U>(&self, key: T) -> anyhow::Result<Option<U>>
             where
                 T: ToRedisArgs + Debug + Send + Sync + 'static,
@@ -253,6 +707,33 @@ mockall::mock! {
 
         pub async fn delete_indexer_state<P>(&self, key_provider: &P) -> anyhow::Result<()>
             where P: KeyProvider + 'static;
+
+        pub fn set_fence(&self, fence: Option<(String, u64)>);
+
+        pub async fn claim_indexer<P>(&self, key_provider: &P) -> anyhow::Result<()>
+            where P: KeyProvider + 'static;
+
+        pub async fn acquire_lease(&self, key: &str, ttl: Duration) -> anyhow::Result<Option<u64>>;
+
+        pub async fn renew_lease(
+            &self,
+            key: &str,
+            fencing_token: u64,
+            ttl: Duration,
+        ) -> anyhow::Result<bool>;
+
+        pub async fn zadd<S, M>(&self, set: S, member: M, score: u64) -> anyhow::Result<()>
+            where
+                S: ToRedisArgs + Debug + Send + Sync + 'static,
+                M: ToRedisArgs + Debug + Send + Sync + 'static;
+
+        pub async fn zremrangebyscore<S>(&self, set: S, max_score: u64) -> anyhow::Result<()>
+            where
+                S: ToRedisArgs + Debug + Send + Sync + 'static;
+
+        pub async fn zrange<S>(&self, set: S) -> anyhow::Result<Vec<String>>
+            where
+                S: ToRedisArgs + Debug + Send + Sync + 'static;
     }
 
     impl Clone for RedisClientImpl {
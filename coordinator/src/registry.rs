@@ -4,6 +4,8 @@ use anyhow::Context;
 use serde_json::Value;
 use std::collections::hash_map::Iter;
 use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 use near_jsonrpc_client::methods::query::RpcQueryRequest;
 use near_jsonrpc_client::JsonRpcClient;
@@ -13,6 +15,7 @@ use near_primitives::views::QueryRequest;
 use registry_types::AllIndexers;
 
 use crate::indexer_config::IndexerConfig;
+use crate::metrics;
 use crate::utils::exponential_retry;
 
 #[derive(Clone)]
@@ -116,15 +119,27 @@ pub use MockRegistryImpl as Registry;
 #[cfg(not(test))]
 pub use RegistryImpl as Registry;
 
+/// Locally cached copy of the registry, including deleted Indexers, which is kept up to date by
+/// `RegistryImpl::sync`
+#[derive(Default)]
+struct RegistrySnapshot {
+    indexers: HashMap<AccountId, HashMap<String, IndexerConfig>>,
+    /// Block height the snapshot reflects, `None` until the first successful sync
+    block_height: Option<u64>,
+    synced_at: Option<Instant>,
+}
+
 pub struct RegistryImpl {
     json_rpc_client: JsonRpcClientWrapper,
     registry_contract_id: AccountId,
+    snapshot: RwLock<RegistrySnapshot>,
 }
 
 #[cfg_attr(test, mockall::automock)]
 impl RegistryImpl {
     const LIST_METHOD: &'static str = "list_all";
     const GET_METHOD: &'static str = "read_indexer_function";
+    const LIST_CHANGES_METHOD: &'static str = "list_changed_since";
 
     #[cfg(test)]
     pub fn new(
@@ -134,6 +149,7 @@ impl RegistryImpl {
         Self {
             registry_contract_id,
             json_rpc_client: json_rpc_client_wrapper,
+            snapshot: RwLock::new(RegistrySnapshot::default()),
         }
     }
 
@@ -143,6 +159,7 @@ impl RegistryImpl {
         Self {
             registry_contract_id,
             json_rpc_client: JsonRpcClientWrapper::new(json_rpc_client),
+            snapshot: RwLock::new(RegistrySnapshot::default()),
         }
     }
 
@@ -180,8 +197,42 @@ impl RegistryImpl {
         )
     }
 
+    /// Returns all non-deleted Indexers, from the local snapshot if it has been synced
     pub async fn fetch(&self) -> anyhow::Result<IndexerRegistry> {
+        {
+            let snapshot = self.snapshot.read().unwrap();
+
+            if snapshot.block_height.is_some() {
+                return Ok(IndexerRegistry(
+                    snapshot
+                        .indexers
+                        .iter()
+                        .map(|(account_id, indexers)| {
+                            let indexers = indexers
+                                .iter()
+                                .filter(|(_, config)| !config.is_deleted())
+                                .map(|(function_name, config)| {
+                                    (function_name.clone(), config.clone())
+                                })
+                                .collect::<HashMap<_, _>>();
+
+                            (account_id.clone(), indexers)
+                        })
+                        .filter(|(_, indexers)| !indexers.is_empty())
+                        .collect(),
+                ));
+            }
+        }
+
+        self.list_all().await
+    }
+
+    async fn list_all(&self) -> anyhow::Result<IndexerRegistry> {
         exponential_retry(|| async {
+            let timer = metrics::REGISTRY_FETCH_LATENCY_SECONDS
+                .with_label_values(&[Self::LIST_METHOD])
+                .start_timer();
+
             let response = self
                 .json_rpc_client
                 .call(RpcQueryRequest {
@@ -195,6 +246,8 @@ impl RegistryImpl {
                 .await
                 .context("Failed to list registry contract")?;
 
+            timer.observe_duration();
+
             if let QueryResponseKind::CallResult(call_result) = response.kind {
                 let all_indexers: AllIndexers = serde_json::from_slice(&call_result.result)?;
 
@@ -206,11 +259,37 @@ impl RegistryImpl {
         .await
     }
 
+    /// Returns the specified Indexer, including if deleted, from the local snapshot if it has been
+    /// synced
     pub async fn fetch_indexer(
         &self,
         account_id: &AccountId,
         function_name: &str,
     ) -> anyhow::Result<Option<IndexerConfig>> {
+        {
+            let snapshot = self.snapshot.read().unwrap();
+
+            if snapshot.block_height.is_some() {
+                return Ok(snapshot
+                    .indexers
+                    .get(account_id)
+                    .and_then(|indexers| indexers.get(function_name))
+                    .cloned());
+            }
+        }
+
+        self.read_indexer(account_id, function_name).await
+    }
+
+    async fn read_indexer(
+        &self,
+        account_id: &AccountId,
+        function_name: &str,
+    ) -> anyhow::Result<Option<IndexerConfig>> {
+        let timer = metrics::REGISTRY_FETCH_LATENCY_SECONDS
+            .with_label_values(&[Self::GET_METHOD])
+            .start_timer();
+
         let response = self
             .json_rpc_client
             .call(RpcQueryRequest {
@@ -232,6 +311,8 @@ impl RegistryImpl {
             .await
             .context("Failed to fetch indexer")?;
 
+        timer.observe_duration();
+
         if let QueryResponseKind::CallResult(call_result) = response.kind {
             let config: Option<registry_types::IndexerConfig> =
                 serde_json::from_slice(&call_result.result)
@@ -263,6 +344,89 @@ impl RegistryImpl {
 
         anyhow::bail!("Invalid registry response")
     }
+
+    /// Time since the local snapshot was last synced, `None` if it has never been synced
+    pub fn get_staleness(&self) -> Option<Duration> {
+        self.snapshot
+            .read()
+            .unwrap()
+            .synced_at
+            .map(|synced_at| synced_at.elapsed())
+    }
+
+    /// Block height the local snapshot reflects, `None` if it has never been synced
+    pub fn get_block_height(&self) -> Option<u64> {
+        self.snapshot.read().unwrap().block_height
+    }
+
+    /// Applies all registry changes since the last sync to the local snapshot
+    pub async fn sync(&self) -> anyhow::Result<()> {
+        let result = self.apply_changes().await;
+
+        if let Some(synced_at) = self.snapshot.read().unwrap().synced_at {
+            metrics::REGISTRY_STALENESS_SECONDS.set(synced_at.elapsed().as_secs() as i64);
+        }
+
+        result
+    }
+
+    async fn apply_changes(&self) -> anyhow::Result<()> {
+        let since_block_height = self.snapshot.read().unwrap().block_height.unwrap_or(0);
+
+        let timer = metrics::REGISTRY_FETCH_LATENCY_SECONDS
+            .with_label_values(&[Self::LIST_CHANGES_METHOD])
+            .start_timer();
+
+        let response = self
+            .json_rpc_client
+            .call(RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::CallFunction {
+                    method_name: Self::LIST_CHANGES_METHOD.to_string(),
+                    account_id: self.registry_contract_id.clone(),
+                    args: FunctionArgs::from(
+                        serde_json::json!({ "block_height": since_block_height })
+                            .to_string()
+                            .as_bytes()
+                            .to_vec(),
+                    ),
+                },
+            })
+            .await
+            .context("Failed to list registry changes")?;
+
+        timer.observe_duration();
+
+        let QueryResponseKind::CallResult(call_result) = response.kind else {
+            anyhow::bail!("Invalid registry response")
+        };
+
+        let changes: AllIndexers = serde_json::from_slice(&call_result.result)?;
+        let changes = self.enrich_indexer_registry(changes);
+
+        let mut snapshot = self.snapshot.write().unwrap();
+
+        for config in changes.iter() {
+            tracing::debug!(
+                account_id = config.account_id.as_str(),
+                function_name = config.function_name.as_str(),
+                "Applying registry change"
+            );
+
+            snapshot
+                .indexers
+                .entry(config.account_id.clone())
+                .or_default()
+                .insert(config.function_name.clone(), config.clone());
+        }
+
+        snapshot.block_height = Some(response.block_height);
+        snapshot.synced_at = Some(Instant::now());
+
+        metrics::REGISTRY_BLOCK_HEIGHT.set(response.block_height as i64);
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -381,4 +545,98 @@ mod tests {
 
         assert!(parse_result.is_err());
     }
+
+    #[tokio::test]
+    async fn syncs_registry_incrementally() {
+        let config = serde_json::json!({
+            "code": "code",
+            "schema": "schema",
+            "rule": { "kind": "ACTION_ANY", "affected_account_id": "queryapi.dataplatform.near", "status": "ANY" },
+            "start_block": "LATEST",
+            "updated_at_block_height": null,
+            "created_at_block_height": 5,
+            "deleted_at_block_height": null
+        });
+
+        let mut mock_json_rpc_client = JsonRpcClientWrapper::default();
+        let mut calls = 0;
+        mock_json_rpc_client
+            .expect_call::<RpcQueryRequest>()
+            .returning(move |request| {
+                calls += 1;
+
+                let QueryRequest::CallFunction {
+                    method_name, args, ..
+                } = request.request
+                else {
+                    panic!("Unexpected request")
+                };
+                assert_eq!(method_name, "list_changed_since");
+
+                let (since_block_height, changes, block_height) = if calls == 1 {
+                    (
+                        0,
+                        serde_json::json!({ "morgs.near": { "test": config } }),
+                        10,
+                    )
+                } else {
+                    let mut deleted_config = config.clone();
+                    deleted_config["deleted_at_block_height"] = serde_json::json!(15);
+
+                    (
+                        10,
+                        serde_json::json!({ "morgs.near": { "test": deleted_config } }),
+                        20,
+                    )
+                };
+
+                assert_eq!(
+                    args,
+                    FunctionArgs::from(
+                        serde_json::json!({ "block_height": since_block_height })
+                            .to_string()
+                            .as_bytes()
+                            .to_vec()
+                    )
+                );
+
+                Ok(near_jsonrpc_client::methods::query::RpcQueryResponse {
+                    kind: QueryResponseKind::CallResult(near_primitives::views::CallResult {
+                        result: changes.to_string().as_bytes().to_vec(),
+                        logs: vec![],
+                    }),
+                    block_height,
+                    block_hash: Default::default(),
+                })
+            })
+            .times(2);
+
+        let registry = RegistryImpl::new("registry".parse().unwrap(), mock_json_rpc_client);
+        let account_id: AccountId = "morgs.near".parse().unwrap();
+
+        registry.sync().await.unwrap();
+
+        assert!(registry
+            .fetch()
+            .await
+            .unwrap()
+            .get(&account_id, "test")
+            .is_some());
+        assert!(!registry
+            .fetch_indexer(&account_id, "test")
+            .await
+            .unwrap()
+            .unwrap()
+            .is_deleted());
+
+        registry.sync().await.unwrap();
+
+        assert!(registry.fetch().await.unwrap().is_empty());
+        assert!(registry
+            .fetch_indexer(&account_id, "test")
+            .await
+            .unwrap()
+            .unwrap()
+            .is_deleted());
+    }
 }
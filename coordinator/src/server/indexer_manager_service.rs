@@ -1,21 +1,330 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use tonic::{Request, Response, Status};
 
-use crate::indexer_state::IndexerStateManager;
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+
+use crate::handlers::block_streams::{BlockStreamsHandler, StreamInfo};
+use crate::handlers::executors::{ExecutorInfo, ExecutorsHandler};
+use crate::indexer_config::IndexerConfig;
+use crate::indexer_state::{
+    IndexerState, IndexerStateManager, MigrationStrategy, ProvisionedState, QuotaHold,
+    ResumePolicy, RolloutStrategy,
+};
+use crate::lifecycle::LifecycleState;
+use crate::quota::{PriorityTier, QuotaScheduler};
+use crate::redis::{KeyProvider, RedisClient};
 use crate::registry::Registry;
 use crate::server::indexer_manager;
 
 pub struct IndexerManagerService {
     indexer_state_manager: Arc<IndexerStateManager>,
     registry: Arc<Registry>,
+    block_streams_handler: BlockStreamsHandler,
+    executors_handler: ExecutorsHandler,
+    redis_client: RedisClient,
+    quota_scheduler: Arc<QuotaScheduler>,
 }
 
 impl IndexerManagerService {
-    pub fn new(indexer_state_manager: Arc<IndexerStateManager>, registry: Arc<Registry>) -> Self {
+    pub fn new(
+        indexer_state_manager: Arc<IndexerStateManager>,
+        registry: Arc<Registry>,
+        block_streams_handler: BlockStreamsHandler,
+        executors_handler: ExecutorsHandler,
+        redis_client: RedisClient,
+        quota_scheduler: Arc<QuotaScheduler>,
+    ) -> Self {
         Self {
             indexer_state_manager,
             registry,
+            block_streams_handler,
+            executors_handler,
+            redis_client,
+            quota_scheduler,
+        }
+    }
+
+    /// Combines persisted state with the live state of the Block Stream and Executor. Failures to
+    /// fetch live state are logged and omitted, so that the persisted state is still returned.
+    async fn get_indexer_state(
+        &self,
+        indexer_config: &IndexerConfig,
+    ) -> Result<indexer_manager::IndexerState, Status> {
+        let state = self
+            .indexer_state_manager
+            .get_state(indexer_config)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer state"))?;
+
+        let block_stream = self
+            .block_streams_handler
+            .get(
+                indexer_config.account_id.clone(),
+                indexer_config.function_name.clone(),
+            )
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(?error, "Failed to fetch block stream");
+                None
+            });
+
+        let executor = self
+            .executors_handler
+            .get(
+                indexer_config.account_id.clone(),
+                indexer_config.function_name.clone(),
+            )
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(?error, "Failed to fetch executor");
+                None
+            });
+
+        let last_published_block = self
+            .redis_client
+            .get_last_published_block(indexer_config)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(?error, "Failed to fetch last published block");
+                None
+            });
+
+        Ok(Self::build_indexer_state(
+            indexer_config,
+            state,
+            block_stream,
+            executor,
+            last_published_block,
+        ))
+    }
+
+    /// Same as `get_indexer_state`, but for all Indexers at once, fetching live state in bulk
+    /// rather than per Indexer
+    async fn list_indexer_states(
+        &self,
+        indexer_configs: &[IndexerConfig],
+    ) -> Result<Vec<indexer_manager::IndexerState>, Status> {
+        let mut states: HashMap<String, IndexerState> = self
+            .indexer_state_manager
+            .list()
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer states"))?
+            .into_iter()
+            .map(|state| (state.prefix(), state))
+            .collect();
+
+        let mut block_streams: HashMap<String, StreamInfo> = self
+            .block_streams_handler
+            .list()
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(?error, "Failed to list block streams");
+                vec![]
+            })
+            .into_iter()
+            .map(|stream| {
+                (
+                    format!("{}/{}", stream.account_id, stream.function_name),
+                    stream,
+                )
+            })
+            .collect();
+
+        let mut executors: HashMap<String, ExecutorInfo> = self
+            .executors_handler
+            .list()
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(?error, "Failed to list executors");
+                vec![]
+            })
+            .into_iter()
+            .map(|executor| {
+                (
+                    format!("{}/{}", executor.account_id, executor.function_name),
+                    executor,
+                )
+            })
+            .collect();
+
+        let last_published_blocks = self
+            .redis_client
+            .get_last_published_blocks(indexer_configs)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(?error, "Failed to fetch last published blocks");
+                vec![None; indexer_configs.len()]
+            });
+
+        Ok(indexer_configs
+            .iter()
+            .zip(last_published_blocks)
+            .map(|(indexer_config, last_published_block)| {
+                let full_name = indexer_config.get_full_name();
+
+                Self::build_indexer_state(
+                    indexer_config,
+                    states
+                        .remove(&full_name)
+                        .unwrap_or_else(|| IndexerState::new(indexer_config)),
+                    block_streams.remove(&full_name),
+                    executors.remove(&full_name),
+                    last_published_block,
+                )
+            })
+            .collect())
+    }
+
+    fn build_indexer_state(
+        indexer_config: &IndexerConfig,
+        state: IndexerState,
+        block_stream: Option<StreamInfo>,
+        executor: Option<ExecutorInfo>,
+        last_published_block: Option<u64>,
+    ) -> indexer_manager::IndexerState {
+        let block_stream = block_stream.map(|stream| indexer_manager::BlockStreamInfo {
+            stream_id: stream.stream_id,
+            version: stream.version,
+            processing_state: stream
+                .health
+                .as_ref()
+                .and_then(|health| {
+                    block_streamer::ProcessingState::try_from(health.processing_state).ok()
+                })
+                .unwrap_or(block_streamer::ProcessingState::Unspecified)
+                .as_str_name()
+                .to_string(),
+            health_updated_at_timestamp_secs: stream
+                .health
+                .map(|health| health.updated_at_timestamp_secs)
+                .unwrap_or_default(),
+        });
+
+        let executor = executor.map(|executor| indexer_manager::ExecutorInfo {
+            executor_id: executor.executor_id,
+            version: executor.version,
+            execution_state: executor
+                .health
+                .and_then(|health| runner::ExecutionState::try_from(health.execution_state).ok())
+                .unwrap_or(runner::ExecutionState::Unspecified)
+                .as_str_name()
+                .to_string(),
+        });
+
+        indexer_manager::IndexerState {
+            account_id: indexer_config.account_id.to_string(),
+            function_name: indexer_config.function_name.clone(),
+            enabled: state.enabled,
+            lifecycle_state: Self::get_lifecycle_state(&state.lifecycle_state).into(),
+            provisioned_state: Self::get_provisioned_state(&state).into(),
+            block_stream_synced_at: state.block_stream_synced_at,
+            registry_version: indexer_config.get_registry_version(),
+            block_stream,
+            executor,
+            last_published_block,
+            migration_strategy: match state.migration_strategy {
+                MigrationStrategy::Additive => indexer_manager::MigrationStrategy::Additive,
+                MigrationStrategy::Recreate => indexer_manager::MigrationStrategy::Recreate,
+            }
+            .into(),
+            migration_error: state.migration.and_then(|migration| migration.error),
+            rollout_strategy: match state.rollout_strategy {
+                RolloutStrategy::InPlace => indexer_manager::RolloutStrategy::InPlace,
+                RolloutStrategy::BlueGreen => indexer_manager::RolloutStrategy::BlueGreen,
+            }
+            .into(),
+            rollout_version: state.rollout.as_ref().map(|rollout| rollout.version),
+            rollout_error: state.rollout.and_then(|rollout| rollout.error),
+            quota_hold: state.quota_hold.map(|hold| match hold {
+                QuotaHold::Suspended { reason } => indexer_manager::QuotaHold {
+                    kind: indexer_manager::QuotaHoldKind::Suspended.into(),
+                    reason,
+                    queue_position: None,
+                },
+                QuotaHold::Queued { reason, position } => indexer_manager::QuotaHold {
+                    kind: indexer_manager::QuotaHoldKind::Queued.into(),
+                    reason,
+                    queue_position: Some(position as u32),
+                },
+            }),
+        }
+    }
+
+    fn get_lifecycle_state(lifecycle_state: &LifecycleState) -> indexer_manager::LifecycleState {
+        match lifecycle_state {
+            LifecycleState::Initializing => indexer_manager::LifecycleState::Initializing,
+            LifecycleState::Running => indexer_manager::LifecycleState::Running,
+            LifecycleState::Suspending => indexer_manager::LifecycleState::Suspending,
+            LifecycleState::Suspended => indexer_manager::LifecycleState::Suspended,
+            LifecycleState::Repairing => indexer_manager::LifecycleState::Repairing,
+            LifecycleState::Deleting => indexer_manager::LifecycleState::Deleting,
+            LifecycleState::Deleted => indexer_manager::LifecycleState::Deleted,
+            LifecycleState::Failed => indexer_manager::LifecycleState::Failed,
+            LifecycleState::Resetting => indexer_manager::LifecycleState::Resetting,
+            LifecycleState::Migrating => indexer_manager::LifecycleState::Migrating,
+            LifecycleState::MigrationFailed => indexer_manager::LifecycleState::MigrationFailed,
+            LifecycleState::RollingOut => indexer_manager::LifecycleState::RollingOut,
+            LifecycleState::Promoting => indexer_manager::LifecycleState::Promoting,
+        }
+    }
+
+    /// Uses the recorded provisioning progress where available, otherwise derives it from the
+    /// lifecycle, which drives provisioning. Deletion and migration are only tracked by the
+    /// lifecycle, so take precedence.
+    fn get_provisioned_state(state: &IndexerState) -> indexer_manager::ProvisionedState {
+        let derive_from_lifecycle = matches!(
+            state.lifecycle_state,
+            LifecycleState::Deleting
+                | LifecycleState::Deleted
+                | LifecycleState::Migrating
+                | LifecycleState::MigrationFailed
+        );
+
+        if let Some(provisioned_state) = state
+            .provisioned_state
+            .as_ref()
+            .filter(|_| !derive_from_lifecycle)
+        {
+            return match provisioned_state {
+                ProvisionedState::Unprovisioned => indexer_manager::ProvisionedState::Unprovisioned,
+                ProvisionedState::Provisioning { .. } => {
+                    indexer_manager::ProvisionedState::Provisioning
+                }
+                ProvisionedState::Provisioned => indexer_manager::ProvisionedState::Provisioned,
+                ProvisionedState::Deprovisioning { .. } => {
+                    indexer_manager::ProvisionedState::Deprovisioning
+                }
+                ProvisionedState::Failed => indexer_manager::ProvisionedState::Failed,
+            };
+        }
+
+        match state.lifecycle_state {
+            LifecycleState::Initializing => indexer_manager::ProvisionedState::Provisioning,
+            LifecycleState::Running
+            | LifecycleState::Suspending
+            | LifecycleState::Suspended
+            | LifecycleState::RollingOut
+            | LifecycleState::Promoting => indexer_manager::ProvisionedState::Provisioned,
+            LifecycleState::Repairing | LifecycleState::Failed => {
+                indexer_manager::ProvisionedState::Failed
+            }
+            LifecycleState::Deleting => match &state.deletion {
+                Some(deletion) if deletion.data_layer_deprovisioned => {
+                    indexer_manager::ProvisionedState::Unprovisioned
+                }
+                _ => indexer_manager::ProvisionedState::Deprovisioning,
+            },
+            LifecycleState::Deleted => indexer_manager::ProvisionedState::Unprovisioned,
+            LifecycleState::Migrating => indexer_manager::ProvisionedState::Provisioning,
+            LifecycleState::MigrationFailed => indexer_manager::ProvisionedState::Failed,
+            LifecycleState::Resetting => match &state.reset {
+                Some(reset) if reset.data_layer_deprovisioned => {
+                    indexer_manager::ProvisionedState::Provisioning
+                }
+                _ => indexer_manager::ProvisionedState::Provisioned,
+            },
         }
     }
 }
@@ -96,6 +405,310 @@ impl indexer_manager::indexer_manager_server::IndexerManager for IndexerManagerS
         }))
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            account_id = request.get_ref().account_id,
+            function_name = request.get_ref().function_name
+        )
+    )]
+    async fn set_resume_policy(
+        &self,
+        request: Request<indexer_manager::SetResumePolicyRequest>,
+    ) -> Result<Response<indexer_manager::SetResumePolicyResponse>, Status> {
+        let request = request.into_inner();
+
+        let account_id = request
+            .account_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid account ID"))?;
+
+        let resume_policy = match indexer_manager::ResumePolicy::try_from(request.resume_policy) {
+            Ok(indexer_manager::ResumePolicy::OnUpdate) => ResumePolicy::OnUpdate,
+            Ok(indexer_manager::ResumePolicy::Manual) => ResumePolicy::Manual,
+            Ok(indexer_manager::ResumePolicy::Unspecified) | Err(_) => {
+                return Err(Status::invalid_argument("Invalid resume policy"))
+            }
+        };
+
+        let indexer_config = self
+            .registry
+            .fetch_indexer(&account_id, &request.function_name)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer"))?
+            .ok_or(Status::not_found("Indexer not found"))?;
+
+        self.indexer_state_manager
+            .set_resume_policy(&indexer_config, resume_policy)
+            .await
+            .map_err(|_| Status::internal("Failed to set resume policy"))?;
+
+        tracing::info!(?resume_policy, "Set resume policy");
+
+        Ok(Response::new(indexer_manager::SetResumePolicyResponse {
+            success: true,
+        }))
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            account_id = request.get_ref().account_id,
+            function_name = request.get_ref().function_name
+        )
+    )]
+    async fn set_migration_strategy(
+        &self,
+        request: Request<indexer_manager::SetMigrationStrategyRequest>,
+    ) -> Result<Response<indexer_manager::SetMigrationStrategyResponse>, Status> {
+        let request = request.into_inner();
+
+        let account_id = request
+            .account_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid account ID"))?;
+
+        let migration_strategy =
+            match indexer_manager::MigrationStrategy::try_from(request.migration_strategy) {
+                Ok(indexer_manager::MigrationStrategy::Additive) => MigrationStrategy::Additive,
+                Ok(indexer_manager::MigrationStrategy::Recreate) => MigrationStrategy::Recreate,
+                Ok(indexer_manager::MigrationStrategy::Unspecified) | Err(_) => {
+                    return Err(Status::invalid_argument("Invalid migration strategy"))
+                }
+            };
+
+        let indexer_config = self
+            .registry
+            .fetch_indexer(&account_id, &request.function_name)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer"))?
+            .ok_or(Status::not_found("Indexer not found"))?;
+
+        self.indexer_state_manager
+            .set_migration_strategy(&indexer_config, migration_strategy)
+            .await
+            .map_err(|_| Status::internal("Failed to set migration strategy"))?;
+
+        tracing::info!(?migration_strategy, "Set migration strategy");
+
+        Ok(Response::new(
+            indexer_manager::SetMigrationStrategyResponse { success: true },
+        ))
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            account_id = request.get_ref().account_id,
+            function_name = request.get_ref().function_name
+        )
+    )]
+    async fn set_rollout_strategy(
+        &self,
+        request: Request<indexer_manager::SetRolloutStrategyRequest>,
+    ) -> Result<Response<indexer_manager::SetRolloutStrategyResponse>, Status> {
+        let request = request.into_inner();
+
+        let account_id = request
+            .account_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid account ID"))?;
+
+        let rollout_strategy =
+            match indexer_manager::RolloutStrategy::try_from(request.rollout_strategy) {
+                Ok(indexer_manager::RolloutStrategy::InPlace) => RolloutStrategy::InPlace,
+                Ok(indexer_manager::RolloutStrategy::BlueGreen) => RolloutStrategy::BlueGreen,
+                Ok(indexer_manager::RolloutStrategy::Unspecified) | Err(_) => {
+                    return Err(Status::invalid_argument("Invalid rollout strategy"))
+                }
+            };
+
+        let indexer_config = self
+            .registry
+            .fetch_indexer(&account_id, &request.function_name)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer"))?
+            .ok_or(Status::not_found("Indexer not found"))?;
+
+        self.indexer_state_manager
+            .set_rollout_strategy(&indexer_config, rollout_strategy)
+            .await
+            .map_err(|_| Status::internal("Failed to set rollout strategy"))?;
+
+        tracing::info!(?rollout_strategy, "Set rollout strategy");
+
+        Ok(Response::new(indexer_manager::SetRolloutStrategyResponse {
+            success: true,
+        }))
+    }
+
+    #[tracing::instrument(skip_all, fields(account_id = request.get_ref().account_id))]
+    async fn get_account_quota(
+        &self,
+        request: Request<indexer_manager::GetAccountQuotaRequest>,
+    ) -> Result<Response<indexer_manager::AccountQuota>, Status> {
+        let request = request.into_inner();
+
+        let account_id = request
+            .account_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid account ID"))?;
+
+        let states = self
+            .indexer_state_manager
+            .list()
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer states"))?;
+
+        let policy = self.quota_scheduler.get_policy();
+
+        let mut quota = indexer_manager::AccountQuota {
+            priority_tier: match policy.get_priority_tier(&account_id) {
+                PriorityTier::High => indexer_manager::PriorityTier::High,
+                PriorityTier::Standard => indexer_manager::PriorityTier::Standard,
+                PriorityTier::Low => indexer_manager::PriorityTier::Low,
+            }
+            .into(),
+            max_active_indexers: policy.max_active_indexers_per_account.map(|max| max as u32),
+            max_backfilling_streams: policy.max_backfilling_streams.map(|max| max as u32),
+            ..Default::default()
+        };
+
+        for state in states
+            .iter()
+            .filter(|state| state.account_id == account_id && state.enabled)
+        {
+            match state.quota_hold {
+                Some(QuotaHold::Suspended { .. }) => quota.suspended_indexers += 1,
+                Some(QuotaHold::Queued { .. }) => {
+                    quota.active_indexers += 1;
+                    quota.queued_indexers += 1;
+                }
+                None if !matches!(
+                    state.lifecycle_state,
+                    LifecycleState::Failed | LifecycleState::Deleting | LifecycleState::Deleted
+                ) =>
+                {
+                    quota.active_indexers += 1
+                }
+                None => {}
+            }
+        }
+
+        Ok(Response::new(quota))
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            account_id = request.get_ref().account_id,
+            function_name = request.get_ref().function_name,
+            block_height = request.get_ref().block_height,
+            clear_data = request.get_ref().clear_data
+        )
+    )]
+    async fn reset_indexer(
+        &self,
+        request: Request<indexer_manager::ResetIndexerRequest>,
+    ) -> Result<Response<indexer_manager::ResetIndexerResponse>, Status> {
+        let request = request.into_inner();
+
+        let account_id = request
+            .account_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid account ID"))?;
+
+        let indexer_config = self
+            .registry
+            .fetch_indexer(&account_id, &request.function_name)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer"))?
+            .ok_or(Status::not_found("Indexer not found"))?;
+
+        let state = self
+            .indexer_state_manager
+            .get_state(&indexer_config)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer state"))?;
+
+        if state.reset.is_some() {
+            return Err(Status::failed_precondition(
+                "Indexer is already being reset",
+            ));
+        }
+
+        if !matches!(
+            state.lifecycle_state,
+            LifecycleState::Running | LifecycleState::Suspended
+        ) {
+            return Err(Status::failed_precondition(format!(
+                "Indexer cannot be reset while {:?}",
+                state.lifecycle_state
+            )));
+        }
+
+        self.indexer_state_manager
+            .request_reset(&indexer_config, request.block_height, request.clear_data)
+            .await
+            .map_err(|_| Status::internal("Failed to reset indexer"))?;
+
+        tracing::info!("Requested indexer reset");
+
+        Ok(Response::new(indexer_manager::ResetIndexerResponse {
+            success: true,
+        }))
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            account_id = request.get_ref().account_id,
+            function_name = request.get_ref().function_name
+        )
+    )]
+    async fn get_indexer_history(
+        &self,
+        request: Request<indexer_manager::GetIndexerHistoryRequest>,
+    ) -> Result<Response<indexer_manager::GetIndexerHistoryResponse>, Status> {
+        let request = request.into_inner();
+
+        let account_id = request
+            .account_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid account ID"))?;
+
+        let indexer_config = self
+            .registry
+            .fetch_indexer(&account_id, &request.function_name)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer"))?
+            .ok_or(Status::not_found("Indexer not found"))?;
+
+        let limit = match request.limit {
+            0 => DEFAULT_HISTORY_LIMIT,
+            limit => limit,
+        };
+
+        let transitions = self
+            .indexer_state_manager
+            .get_history(&indexer_config, limit as usize)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer history"))?
+            .into_iter()
+            .map(|transition| indexer_manager::IndexerTransition {
+                timestamp: transition.timestamp,
+                from: Self::get_lifecycle_state(&transition.from).into(),
+                to: Self::get_lifecycle_state(&transition.to).into(),
+                reason: transition.reason,
+                registry_version: transition.registry_version,
+            })
+            .collect();
+
+        Ok(Response::new(indexer_manager::GetIndexerHistoryResponse {
+            transitions,
+        }))
+    }
+
     async fn list(
         &self,
         _request: Request<indexer_manager::Empty>,
@@ -106,24 +719,403 @@ impl indexer_manager::indexer_manager_server::IndexerManager for IndexerManagerS
             .await
             .map_err(|_| Status::internal("Failed to fetch registry"))?;
 
-        let mut indexers = vec![];
+        let indexer_configs: Vec<IndexerConfig> = regsitry.iter().cloned().collect();
 
-        for indexer_config in regsitry.iter() {
-            let state = self
-                .indexer_state_manager
-                .get_state(indexer_config)
-                .await
-                .unwrap();
-
-            indexers.push(indexer_manager::IndexerState {
-                account_id: indexer_config.account_id.to_string(),
-                function_name: indexer_config.function_name.clone(),
-                enabled: state.enabled,
-            });
-        }
+        let indexers = self.list_indexer_states(&indexer_configs).await?;
 
         Ok(Response::new(indexer_manager::ListIndexersResponse {
             indexers,
         }))
     }
+
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            account_id = request.get_ref().account_id,
+            function_name = request.get_ref().function_name
+        )
+    )]
+    async fn get_indexer(
+        &self,
+        request: Request<indexer_manager::IndexerRequest>,
+    ) -> Result<Response<indexer_manager::IndexerState>, Status> {
+        let request = request.into_inner();
+
+        let account_id = request
+            .account_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid account ID"))?;
+
+        let indexer_config = self
+            .registry
+            .fetch_indexer(&account_id, &request.function_name)
+            .await
+            .map_err(|_| Status::internal("Failed to fetch indexer"))?
+            .ok_or(Status::not_found("Indexer not found"))?;
+
+        let indexer_state = self.get_indexer_state(&indexer_config).await?;
+
+        Ok(Response::new(indexer_state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indexer_state::{DeletionState, ResetState};
+    use crate::quota::QuotaPolicy;
+    use crate::registry::IndexerRegistry;
+    use indexer_manager::indexer_manager_server::IndexerManager;
+
+    fn service(
+        indexer_state_manager: IndexerStateManager,
+        registry: Registry,
+        block_streams_handler: BlockStreamsHandler,
+        executors_handler: ExecutorsHandler,
+        redis_client: RedisClient,
+    ) -> IndexerManagerService {
+        IndexerManagerService::new(
+            Arc::new(indexer_state_manager),
+            Arc::new(registry),
+            block_streams_handler,
+            executors_handler,
+            redis_client,
+            Arc::new(QuotaScheduler::new(QuotaPolicy::default())),
+        )
+    }
+
+    #[tokio::test]
+    async fn gets_indexer_with_live_state() {
+        let config = IndexerConfig::default();
+
+        let mut registry = Registry::default();
+        registry.expect_fetch_indexer().returning({
+            let config = config.clone();
+            move |_, _| Ok(Some(config.clone()))
+        });
+
+        let mut state_manager = IndexerStateManager::default();
+        state_manager.expect_get_state().returning(|_| {
+            Ok(IndexerState {
+                lifecycle_state: LifecycleState::Running,
+                block_stream_synced_at: Some(2),
+                ..Default::default()
+            })
+        });
+
+        let mut block_streams_handler = BlockStreamsHandler::default();
+        block_streams_handler.expect_get().returning(|_, _| {
+            Ok(Some(StreamInfo {
+                stream_id: "stream_id".to_string(),
+                version: 2,
+                health: Some(block_streamer::Health {
+                    processing_state: block_streamer::ProcessingState::Running.into(),
+                    updated_at_timestamp_secs: 1000,
+                }),
+                ..Default::default()
+            }))
+        });
+
+        let mut executors_handler = ExecutorsHandler::default();
+        executors_handler
+            .expect_get()
+            .returning(|_, _| anyhow::bail!("unavailable"));
+
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_get_last_published_block::<IndexerConfig>()
+            .returning(|_| Ok(Some(100)));
+
+        let indexer = service(
+            state_manager,
+            registry,
+            block_streams_handler,
+            executors_handler,
+            redis_client,
+        )
+        .get_indexer(Request::new(indexer_manager::IndexerRequest {
+            account_id: config.account_id.to_string(),
+            function_name: config.function_name.clone(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+        assert_eq!(
+            indexer.lifecycle_state(),
+            indexer_manager::LifecycleState::Running
+        );
+        assert_eq!(
+            indexer.provisioned_state(),
+            indexer_manager::ProvisionedState::Provisioned
+        );
+        assert_eq!(indexer.block_stream_synced_at, Some(2));
+        assert_eq!(indexer.last_published_block, Some(100));
+        assert_eq!(
+            indexer.block_stream,
+            Some(indexer_manager::BlockStreamInfo {
+                stream_id: "stream_id".to_string(),
+                version: 2,
+                processing_state: "RUNNING".to_string(),
+                health_updated_at_timestamp_secs: 1000,
+            })
+        );
+        assert_eq!(indexer.executor, None);
+    }
+
+    #[tokio::test]
+    async fn lists_indexers_with_bulk_requests() {
+        let running = IndexerConfig {
+            function_name: "running".to_string(),
+            ..Default::default()
+        };
+        let new = IndexerConfig {
+            function_name: "new".to_string(),
+            ..Default::default()
+        };
+
+        let mut registry = Registry::default();
+        registry.expect_fetch().returning({
+            let running = running.clone();
+            let new = new.clone();
+            move || {
+                Ok(IndexerRegistry::from(&[(
+                    running.account_id.clone(),
+                    HashMap::from([
+                        (running.function_name.clone(), running.clone()),
+                        (new.function_name.clone(), new.clone()),
+                    ]),
+                )]))
+            }
+        });
+
+        let mut state_manager = IndexerStateManager::default();
+        state_manager.expect_get_state().never();
+        state_manager.expect_list().returning({
+            let running = running.clone();
+            move || {
+                Ok(vec![IndexerState {
+                    lifecycle_state: LifecycleState::Running,
+                    ..IndexerState::new(&running)
+                }])
+            }
+        });
+
+        let mut block_streams_handler = BlockStreamsHandler::default();
+        block_streams_handler.expect_get().never();
+        block_streams_handler
+            .expect_list()
+            .returning(|| anyhow::bail!("unavailable"))
+            .once();
+
+        let mut executors_handler = ExecutorsHandler::default();
+        executors_handler.expect_get().never();
+        executors_handler
+            .expect_list()
+            .returning({
+                let running = running.clone();
+                move || {
+                    Ok(vec![ExecutorInfo {
+                        executor_id: "executor_id".to_string(),
+                        account_id: running.account_id.to_string(),
+                        function_name: running.function_name.clone(),
+                        version: 2,
+                        health: None,
+                    }])
+                }
+            })
+            .once();
+
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_get_last_published_block::<IndexerConfig>()
+            .never();
+        redis_client
+            .expect_get_last_published_blocks::<IndexerConfig>()
+            .returning(|configs| {
+                Ok(configs
+                    .iter()
+                    .map(|config| (config.function_name == "running").then_some(100))
+                    .collect())
+            })
+            .once();
+
+        let indexers = service(
+            state_manager,
+            registry,
+            block_streams_handler,
+            executors_handler,
+            redis_client,
+        )
+        .list(Request::new(indexer_manager::Empty {}))
+        .await
+        .unwrap()
+        .into_inner()
+        .indexers;
+
+        assert_eq!(indexers.len(), 2);
+
+        let running = indexers
+            .iter()
+            .find(|indexer| indexer.function_name == "running")
+            .unwrap();
+
+        assert_eq!(
+            running.lifecycle_state(),
+            indexer_manager::LifecycleState::Running
+        );
+        assert_eq!(running.last_published_block, Some(100));
+        assert_eq!(running.block_stream, None);
+        assert_eq!(
+            running
+                .executor
+                .as_ref()
+                .map(|executor| &executor.executor_id),
+            Some(&"executor_id".to_string())
+        );
+
+        let new = indexers
+            .iter()
+            .find(|indexer| indexer.function_name == "new")
+            .unwrap();
+
+        assert_eq!(
+            new.lifecycle_state(),
+            indexer_manager::LifecycleState::Initializing
+        );
+        assert_eq!(new.last_published_block, None);
+        assert_eq!(new.executor, None);
+    }
+
+    #[test]
+    fn derives_provisioned_state() {
+        let cases = [
+            (
+                IndexerState {
+                    lifecycle_state: LifecycleState::Running,
+                    ..Default::default()
+                },
+                indexer_manager::ProvisionedState::Provisioned,
+            ),
+            (
+                IndexerState {
+                    lifecycle_state: LifecycleState::Initializing,
+                    provisioned_state: Some(ProvisionedState::Failed),
+                    ..Default::default()
+                },
+                indexer_manager::ProvisionedState::Failed,
+            ),
+            (
+                IndexerState {
+                    lifecycle_state: LifecycleState::Migrating,
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    ..Default::default()
+                },
+                indexer_manager::ProvisionedState::Provisioning,
+            ),
+            (
+                IndexerState {
+                    lifecycle_state: LifecycleState::Deleting,
+                    provisioned_state: Some(ProvisionedState::Provisioned),
+                    deletion: Some(DeletionState {
+                        data_layer_deprovisioned: true,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                indexer_manager::ProvisionedState::Unprovisioned,
+            ),
+            (
+                IndexerState {
+                    lifecycle_state: LifecycleState::Deleting,
+                    ..Default::default()
+                },
+                indexer_manager::ProvisionedState::Deprovisioning,
+            ),
+            (
+                IndexerState {
+                    lifecycle_state: LifecycleState::Resetting,
+                    reset: Some(ResetState {
+                        block_height: 100,
+                        clear_data: true,
+                        block_stream_cleared: true,
+                        deprovisioning_task_id: None,
+                        data_layer_deprovisioned: true,
+                    }),
+                    ..Default::default()
+                },
+                indexer_manager::ProvisionedState::Provisioning,
+            ),
+        ];
+
+        for (state, expected) in cases {
+            assert_eq!(
+                IndexerManagerService::get_provisioned_state(&state),
+                expected,
+                "{:?}",
+                state.lifecycle_state
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_unspecified_resume_policy() {
+        let status = service(
+            IndexerStateManager::default(),
+            Registry::default(),
+            BlockStreamsHandler::default(),
+            ExecutorsHandler::default(),
+            RedisClient::default(),
+        )
+        .set_resume_policy(Request::new(indexer_manager::SetResumePolicyRequest {
+            account_id: "morgs.near".to_string(),
+            function_name: "test".to_string(),
+            resume_policy: indexer_manager::ResumePolicy::Unspecified.into(),
+        }))
+        .await
+        .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn rejects_unspecified_migration_strategy() {
+        let status = service(
+            IndexerStateManager::default(),
+            Registry::default(),
+            BlockStreamsHandler::default(),
+            ExecutorsHandler::default(),
+            RedisClient::default(),
+        )
+        .set_migration_strategy(Request::new(indexer_manager::SetMigrationStrategyRequest {
+            account_id: "morgs.near".to_string(),
+            function_name: "test".to_string(),
+            migration_strategy: indexer_manager::MigrationStrategy::Unspecified.into(),
+        }))
+        .await
+        .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn rejects_unspecified_rollout_strategy() {
+        let status = service(
+            IndexerStateManager::default(),
+            Registry::default(),
+            BlockStreamsHandler::default(),
+            ExecutorsHandler::default(),
+            RedisClient::default(),
+        )
+        .set_rollout_strategy(Request::new(indexer_manager::SetRolloutStrategyRequest {
+            account_id: "morgs.near".to_string(),
+            function_name: "test".to_string(),
+            rollout_strategy: indexer_manager::RolloutStrategy::Unspecified.into(),
+        }))
+        .await
+        .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
 }
@@ -1,6 +1,10 @@
 use std::sync::Arc;
 
+use crate::handlers::block_streams::BlockStreamsHandler;
+use crate::handlers::executors::ExecutorsHandler;
 use crate::indexer_state::IndexerStateManager;
+use crate::quota::QuotaScheduler;
+use crate::redis::RedisClient;
 use crate::registry::Registry;
 
 mod indexer_manager_service;
@@ -13,13 +17,23 @@ pub async fn init(
     port: String,
     indexer_state_manager: Arc<IndexerStateManager>,
     registry: Arc<Registry>,
+    block_streams_handler: BlockStreamsHandler,
+    executors_handler: ExecutorsHandler,
+    redis_client: RedisClient,
+    quota_scheduler: Arc<QuotaScheduler>,
 ) -> anyhow::Result<()> {
     let addr = format!("0.0.0.0:{}", port).parse()?;
 
     tracing::info!("Starting gRPC server on {}", addr);
 
-    let indexer_manager_service =
-        indexer_manager_service::IndexerManagerService::new(indexer_state_manager, registry);
+    let indexer_manager_service = indexer_manager_service::IndexerManagerService::new(
+        indexer_state_manager,
+        registry,
+        block_streams_handler,
+        executors_handler,
+        redis_client,
+        quota_scheduler,
+    );
 
     let indexer_manager_server =
         indexer_manager::indexer_manager_server::IndexerManagerServer::new(indexer_manager_service);
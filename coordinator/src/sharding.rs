@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::leader_election::LeaderElection;
+use crate::redis::RedisClient;
+
+const MEMBERS_SET: &str = "coordinator:members";
+const MEMBER_TTL: Duration = Duration::from_secs(10);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(3);
+/// Time to wait after the ring changes before starting newly assigned Indexers, giving the
+/// previous owner a chance to observe the same change and stop them first
+const REBALANCE_DELAY: Duration = Duration::from_secs(10);
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// FNV-1a, followed by a finalizer to spread similar inputs across the ring. The hash must be
+/// stable across builds and instances so that all Coordinators agree on ownership.
+fn hash(value: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in value.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^= hash >> 33;
+
+    hash
+}
+
+/// Consistent hash ring mapping Indexers to Coordinator instances. Each member is placed on the
+/// ring multiple times so that load is evenly spread, and so that only the Indexers belonging to a
+/// member which joins/leaves are reassigned.
+#[derive(Debug, Default)]
+pub struct HashRing {
+    nodes: BTreeMap<u64, String>,
+    members: Vec<String>,
+}
+
+impl HashRing {
+    const VIRTUAL_NODES: u32 = 64;
+
+    pub fn new(mut members: Vec<String>) -> Self {
+        members.sort();
+        members.dedup();
+
+        let nodes = members
+            .iter()
+            .flat_map(|member| {
+                (0..Self::VIRTUAL_NODES)
+                    .map(move |index| (hash(&format!("{member}#{index}")), member.clone()))
+            })
+            .collect();
+
+        Self { nodes, members }
+    }
+
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    pub fn owner(&self, key: &str) -> Option<&str> {
+        self.nodes
+            .range(hash(key)..)
+            .next()
+            .or_else(|| self.nodes.iter().next())
+            .map(|(_, member)| member.as_str())
+    }
+}
+
+/// Tracks the set of live Coordinators in Redis and determines which Indexers this instance is
+/// responsible for. Each member holds its own lease and periodically heartbeats into a shared
+/// sorted set from which the ring is built. Membership alone does not fence state writes, as a
+/// member may still hold a valid lease for Indexers which have been reassigned, so Indexers are
+/// also claimed by their new owner, see `RedisClientImpl::claim_indexer`.
+pub struct Membership {
+    redis_client: RedisClient,
+    member_id: String,
+    /// Fencing token of the member lease, 0 when not part of the ring
+    fencing_token: AtomicU64,
+    ring: RwLock<HashRing>,
+    ring_changed_at: RwLock<Instant>,
+}
+
+impl Membership {
+    pub fn new(redis_client: RedisClient, member_id: String) -> Self {
+        Self {
+            redis_client,
+            member_id,
+            fencing_token: AtomicU64::new(0),
+            ring: RwLock::new(HashRing::default()),
+            ring_changed_at: RwLock::new(Instant::now()),
+        }
+    }
+
+    fn lease_key(&self) -> String {
+        format!("coordinator:member:{}", self.member_id)
+    }
+
+    pub fn is_member(&self) -> bool {
+        self.fencing_token.load(Ordering::SeqCst) > 0
+    }
+
+    /// Fencing token of the member lease, 0 when not part of the ring
+    pub fn get_fencing_token(&self) -> u64 {
+        self.fencing_token.load(Ordering::SeqCst)
+    }
+
+    /// Whether the Indexer is assigned to this instance
+    pub fn owns(&self, full_name: &str) -> bool {
+        self.is_member()
+            && self.ring.read().unwrap().owner(full_name) == Some(self.member_id.as_str())
+    }
+
+    /// Whether enough time has passed since the last rebalance to safely start Indexers
+    pub fn is_stable(&self) -> bool {
+        self.ring_changed_at.read().unwrap().elapsed() >= REBALANCE_DELAY
+    }
+
+    async fn join(&self) -> anyhow::Result<bool> {
+        let fencing_token = self
+            .redis_client
+            .acquire_lease(&self.lease_key(), MEMBER_TTL)
+            .await?;
+
+        if let Some(fencing_token) = fencing_token {
+            tracing::info!(member_id = self.member_id, fencing_token, "Joined ring");
+
+            self.redis_client
+                .set_fence(Some((self.lease_key(), fencing_token)));
+            self.fencing_token.store(fencing_token, Ordering::SeqCst);
+        }
+
+        Ok(fencing_token.is_some())
+    }
+
+    fn leave(&self) {
+        tracing::warn!(member_id = self.member_id, "Left ring");
+
+        self.redis_client.set_fence(None);
+        self.fencing_token.store(0, Ordering::SeqCst);
+        self.update_ring(vec![]);
+    }
+
+    fn update_ring(&self, members: Vec<String>) {
+        let ring = HashRing::new(members);
+
+        if ring.members() == self.ring.read().unwrap().members() {
+            return;
+        }
+
+        tracing::info!(members = ?ring.members(), "Rebalancing ring");
+
+        *self.ring.write().unwrap() = ring;
+        *self.ring_changed_at.write().unwrap() = Instant::now();
+    }
+
+    async fn heartbeat(&self) -> anyhow::Result<()> {
+        if !self.is_member() {
+            if !self.join().await? {
+                return Ok(());
+            }
+        } else {
+            let renewed = self
+                .redis_client
+                .renew_lease(
+                    &self.lease_key(),
+                    self.fencing_token.load(Ordering::SeqCst),
+                    MEMBER_TTL,
+                )
+                .await?;
+
+            if !renewed {
+                self.leave();
+
+                return Ok(());
+            }
+        }
+
+        let now = now_ms();
+
+        self.redis_client
+            .zadd(MEMBERS_SET, self.member_id.clone(), now)
+            .await?;
+        self.redis_client
+            .zremrangebyscore(
+                MEMBERS_SET,
+                now.saturating_sub(MEMBER_TTL.as_millis() as u64),
+            )
+            .await?;
+
+        let members = self.redis_client.zrange(MEMBERS_SET).await?;
+
+        self.update_ring(members);
+
+        Ok(())
+    }
+
+    /// Continuously heartbeats membership and keeps the ring up to date
+    pub async fn run(&self) {
+        loop {
+            if let Err(error) = self.heartbeat().await {
+                tracing::warn!(?error, "Failed to heartbeat membership");
+
+                // Other Coordinators will drop this member once its heartbeat expires, so leave
+                // rather than risk managing Indexers which have been reassigned
+                if self.is_member() {
+                    self.leave();
+                }
+            }
+
+            tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+        }
+    }
+}
+
+/// Determines which Indexers this Coordinator should manage, either all of them while holding
+/// leadership, or only those within its shard
+pub enum Ownership {
+    Leader(LeaderElection),
+    Sharded(Membership),
+}
+
+impl Ownership {
+    pub fn is_active(&self) -> bool {
+        match self {
+            Ownership::Leader(leader_election) => leader_election.is_leader(),
+            Ownership::Sharded(membership) => membership.is_member(),
+        }
+    }
+
+    /// Fencing token Indexers are currently claimed with, which changes whenever the lease is
+    /// re-acquired
+    pub fn get_fencing_token(&self) -> u64 {
+        match self {
+            Ownership::Leader(leader_election) => leader_election.get_fencing_token(),
+            Ownership::Sharded(membership) => membership.get_fencing_token(),
+        }
+    }
+
+    pub fn owns(&self, full_name: &str) -> bool {
+        match self {
+            Ownership::Leader(leader_election) => leader_election.is_leader(),
+            Ownership::Sharded(membership) => membership.owns(full_name),
+        }
+    }
+
+    /// Whether newly owned Indexers can be started
+    pub fn can_start(&self, full_name: &str) -> bool {
+        match self {
+            Ownership::Leader(leader_election) => leader_election.is_leader(),
+            Ownership::Sharded(membership) => membership.owns(full_name) && membership.is_stable(),
+        }
+    }
+
+    pub async fn run(&self) {
+        match self {
+            Ownership::Leader(leader_election) => leader_election.run().await,
+            Ownership::Sharded(membership) => membership.run().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockall::predicate::*;
+
+    fn indexer_names() -> Vec<String> {
+        (0..1000)
+            .map(|index| format!("account-{index}.near/indexer_{index}"))
+            .collect()
+    }
+
+    #[test]
+    fn distributes_indexers_across_members() {
+        let members = vec![
+            "coordinator-0".to_string(),
+            "coordinator-1".to_string(),
+            "coordinator-2".to_string(),
+        ];
+        let ring = HashRing::new(members.clone());
+
+        let names = indexer_names();
+
+        for member in members {
+            let owned = names
+                .iter()
+                .filter(|name| ring.owner(name) == Some(member.as_str()))
+                .count();
+
+            assert!(owned > 200, "{member} owns {owned} of {}", names.len());
+        }
+    }
+
+    #[test]
+    fn only_reassigns_indexers_of_removed_member() {
+        let ring = HashRing::new(vec![
+            "coordinator-0".to_string(),
+            "coordinator-1".to_string(),
+            "coordinator-2".to_string(),
+        ]);
+        let rebalanced_ring = HashRing::new(vec![
+            "coordinator-0".to_string(),
+            "coordinator-1".to_string(),
+        ]);
+
+        for name in indexer_names() {
+            let owner = ring.owner(&name).unwrap();
+
+            if owner != "coordinator-2" {
+                assert_eq!(rebalanced_ring.owner(&name), Some(owner));
+            }
+        }
+    }
+
+    #[test]
+    fn empty_ring_has_no_owner() {
+        let ring = HashRing::new(vec![]);
+
+        assert_eq!(ring.owner("morgs.near/test"), None);
+    }
+
+    #[tokio::test]
+    async fn joins_ring_and_owns_indexers() {
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_acquire_lease()
+            .with(eq("coordinator:member:coordinator-1"), eq(MEMBER_TTL))
+            .returning(|_, _| Ok(Some(3)))
+            .once();
+        redis_client
+            .expect_set_fence()
+            .with(eq(Some((
+                "coordinator:member:coordinator-1".to_string(),
+                3,
+            ))))
+            .return_const(())
+            .once();
+        redis_client
+            .expect_zadd::<&str, String>()
+            .with(eq(MEMBERS_SET), eq("coordinator-1".to_string()), always())
+            .returning(|_, _, _| Ok(()))
+            .once();
+        redis_client
+            .expect_zremrangebyscore::<&str>()
+            .returning(|_, _| Ok(()))
+            .once();
+        redis_client
+            .expect_zrange::<&str>()
+            .with(eq(MEMBERS_SET))
+            .returning(|_| Ok(vec!["coordinator-1".to_string()]))
+            .once();
+
+        let membership = Membership::new(redis_client, "coordinator-1".to_string());
+
+        membership.heartbeat().await.unwrap();
+
+        assert!(membership.is_member());
+        assert_eq!(membership.get_fencing_token(), 3);
+        assert!(membership.owns("morgs.near/test"));
+        assert!(!membership.is_stable());
+    }
+
+    #[tokio::test]
+    async fn leaves_ring_when_lease_is_lost() {
+        let mut redis_client = RedisClient::default();
+        redis_client
+            .expect_acquire_lease()
+            .returning(|_, _| Ok(Some(3)));
+        redis_client
+            .expect_renew_lease()
+            .with(
+                eq("coordinator:member:coordinator-1"),
+                eq(3),
+                eq(MEMBER_TTL),
+            )
+            .returning(|_, _, _| Ok(false))
+            .once();
+        redis_client
+            .expect_set_fence()
+            .with(eq(Some((
+                "coordinator:member:coordinator-1".to_string(),
+                3,
+            ))))
+            .return_const(());
+        redis_client
+            .expect_set_fence()
+            .with(eq(None))
+            .return_const(())
+            .once();
+        redis_client
+            .expect_zadd::<&str, String>()
+            .returning(|_, _, _| Ok(()))
+            .once();
+        redis_client
+            .expect_zremrangebyscore::<&str>()
+            .returning(|_, _| Ok(()))
+            .once();
+        redis_client
+            .expect_zrange::<&str>()
+            .returning(|_| Ok(vec!["coordinator-1".to_string()]))
+            .once();
+
+        let membership = Membership::new(redis_client, "coordinator-1".to_string());
+
+        membership.heartbeat().await.unwrap();
+        membership.heartbeat().await.unwrap();
+
+        assert!(!membership.is_member());
+        assert_eq!(membership.get_fencing_token(), 0);
+        assert!(!membership.owns("morgs.near/test"));
+    }
+}
@@ -5,8 +5,8 @@ use near_sdk::store::UnorderedMap;
 use near_sdk::{env, log, near_bindgen, serde_json, AccountId, BorshStorageKey, CryptoHash};
 
 use registry_types::{
-    AccountIndexers, AccountOrAllIndexers, AllIndexers, IndexerConfig, IndexerIdentity,
-    OldIndexerConfig, Rule, StartBlock, Status,
+    AccountIndexers, AccountOrAllIndexers, ActionKind, AllIndexers, IndexerConfig, IndexerIdentity,
+    OldIndexerConfig, Rule, StartBlock, StateChangeKind, Status, SHADOW_FUNCTION_NAME_SUFFIX,
 };
 
 type FunctionName = String;
@@ -252,22 +252,11 @@ impl Contract {
             &account_id
         );
 
-        match &rule {
-            Rule::ActionAny {
-                affected_account_id,
-                ..
-            }
-            | Rule::ActionFunctionCall {
-                affected_account_id,
-                ..
-            } => {
-                if affected_account_id.split(',').any(|account_id| {
-                    ["*", "*.near", "*.kaiching", "*.tg"].contains(&account_id.trim())
-                }) {
-                    self.assert_roles(vec![Role::Owner]);
-                }
-            }
-            _ => {}
+        assert_valid_function_name(&function_name);
+        assert_valid_rule(&rule);
+
+        if !is_restricted_rule(&rule) {
+            self.assert_roles(vec![Role::Owner]);
         }
 
         let account_indexers =
@@ -381,6 +370,133 @@ impl Contract {
             })
             .collect()
     }
+
+    /// Lists indexers which were created, updated or deleted after the specified block height,
+    /// including deleted indexers, so that consumers can incrementally sync the registry
+    pub fn list_changed_since(&self, block_height: u64) -> AllIndexers {
+        self.registry
+            .iter()
+            .map(|(account_id, account_indexers)| {
+                (
+                    account_id.clone(),
+                    account_indexers
+                        .iter()
+                        .filter(|(_, config)| get_last_changed_block_height(config) > block_height)
+                        .map(|(function_name, config)| (function_name.clone(), config.clone()))
+                        .collect::<AccountIndexers>(),
+                )
+            })
+            .filter(|(_, account_indexers)| !account_indexers.is_empty())
+            .collect()
+    }
+}
+
+fn get_last_changed_block_height(config: &IndexerConfig) -> u64 {
+    config
+        .created_at_block_height
+        .max(config.updated_at_block_height.unwrap_or_default())
+        .max(config.deleted_at_block_height.unwrap_or_default())
+}
+
+fn assert_valid_function_name(function_name: &str) {
+    if function_name.ends_with(SHADOW_FUNCTION_NAME_SUFFIX) {
+        env::panic_str(&format!(
+            "Function name {} must not end with reserved suffix {}",
+            function_name, SHADOW_FUNCTION_NAME_SUFFIX
+        ));
+    }
+}
+
+fn assert_valid_rule(rule: &Rule) {
+    match rule {
+        Rule::Any { rules } | Rule::All { rules } => {
+            if rules.is_empty() {
+                env::panic_str("Composite rules must contain at least one rule");
+            }
+
+            rules.iter().for_each(assert_valid_nested_rule);
+        }
+        Rule::Not { rule } => assert_valid_nested_rule(rule),
+        Rule::ActionFunctionCallArgs {
+            args, min_deposit, ..
+        } => {
+            if let Some(min_deposit) = min_deposit {
+                if min_deposit.parse::<u128>().is_err() {
+                    env::panic_str(&format!("Minimum deposit {} is invalid", min_deposit));
+                }
+            }
+
+            if let Some(condition) = args.iter().find(|condition| {
+                !condition.pointer.is_empty() && !condition.pointer.starts_with('/')
+            }) {
+                env::panic_str(&format!("JSON pointer {} is invalid", condition.pointer));
+            }
+        }
+        Rule::ActionKind { action, .. } => match action {
+            ActionKind::Transfer {
+                min_deposit: Some(min_deposit),
+            } if min_deposit.parse::<u128>().is_err() => {
+                env::panic_str(&format!("Minimum deposit {} is invalid", min_deposit));
+            }
+            ActionKind::Stake {
+                min_stake: Some(min_stake),
+            } if min_stake.parse::<u128>().is_err() => {
+                env::panic_str(&format!("Minimum stake {} is invalid", min_stake));
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+fn assert_valid_nested_rule(rule: &Rule) {
+    match rule {
+        Rule::StateChange { .. } => {
+            env::panic_str("State change rules cannot be nested within composite rules")
+        }
+        Rule::TransactionSigner { .. } => {
+            env::panic_str("Transaction rules cannot be nested within composite rules")
+        }
+        _ => {}
+    }
+
+    assert_valid_rule(rule);
+}
+
+/// Whether the rule only matches a limited set of accounts. Rules which can match against all
+/// accounts, i.e. via broad wildcards or negation, are reserved for Owners.
+fn is_restricted_rule(rule: &Rule) -> bool {
+    match rule {
+        Rule::ActionAny {
+            affected_account_id,
+            ..
+        }
+        | Rule::ActionFunctionCall {
+            affected_account_id,
+            ..
+        }
+        | Rule::ActionFunctionCallArgs {
+            affected_account_id,
+            ..
+        }
+        | Rule::ActionKind {
+            affected_account_id,
+            ..
+        }
+        | Rule::StateChange {
+            affected_account_id,
+            ..
+        }
+        | Rule::TransactionSigner {
+            signer_account_id: affected_account_id,
+        } => !affected_account_id
+            .split(',')
+            .any(|account_id| ["*", "*.near", "*.kaiching", "*.tg"].contains(&account_id.trim())),
+        Rule::Event { .. } => true,
+        Rule::Any { rules } => rules.iter().all(is_restricted_rule),
+        Rule::All { rules } => rules.iter().any(is_restricted_rule),
+        Rule::Not { .. } => false,
+    }
 }
 
 #[cfg(test)]
@@ -389,6 +505,8 @@ mod tests {
 
     use std::collections::HashMap;
 
+    use registry_types::{ArgsCondition, ArgsOperator};
+
     #[test]
     fn migrate() {
         let mut registry = OldIndexersByAccount::new(StorageKeys::RegistryV4);
@@ -985,6 +1103,33 @@ mod tests {
         assert_eq!(indexer_config.deleted_at_block_height, None);
     }
 
+    #[test]
+    #[should_panic(
+        expected = "Function name test__shadow must not end with reserved suffix __shadow"
+    )]
+    fn cannot_register_functions_with_shadow_suffix() {
+        let mut contract = Contract {
+            registry: IndexersByAccount::new(StorageKeys::Registry),
+            account_roles: vec![AccountRole {
+                account_id: "bob.near".parse().unwrap(),
+                role: Role::User,
+            }],
+        };
+
+        contract.register(
+            "test__shadow".to_string(),
+            None,
+            "var x= 1;".to_string(),
+            String::new(),
+            Rule::ActionAny {
+                affected_account_id: String::from("social.near"),
+                status: Status::Success,
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
     #[test]
     fn register_indexer_function_with_filter_function_call() {
         let mut contract = Contract {
@@ -1178,6 +1323,354 @@ mod tests {
         assert_eq!(contract.registry.len(), 1);
     }
 
+    #[test]
+    fn users_can_register_composite_rules() {
+        let mut contract = Contract::default();
+        contract.account_roles.push(AccountRole {
+            account_id: "bob.near".parse().unwrap(),
+            role: Role::User,
+        });
+
+        let rule = Rule::Any {
+            rules: vec![
+                Rule::ActionFunctionCall {
+                    affected_account_id: String::from("app.nearcrowd.near"),
+                    status: Status::Success,
+                    function: String::from("submit_review"),
+                },
+                Rule::All {
+                    rules: vec![
+                        Rule::Event {
+                            contract_account_id: String::from("token.sweat"),
+                            standard: String::from("nep141"),
+                            version: String::from("*"),
+                            event: String::from("ft_transfer"),
+                        },
+                        Rule::Not {
+                            rule: Box::new(Rule::ActionFunctionCall {
+                                affected_account_id: String::from("*"),
+                                status: Status::Any,
+                                function: String::from("ft_transfer_call"),
+                            }),
+                        },
+                    ],
+                },
+            ],
+        };
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            rule.clone(),
+            StartBlock::Latest,
+            None,
+        );
+
+        assert_eq!(
+            contract
+                .read_indexer_function("test_function".to_string(), None)
+                .unwrap()
+                .rule,
+            rule
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Composite rules must contain at least one rule")]
+    fn prevents_empty_composite_rules() {
+        let mut contract = Contract::default();
+        contract.account_roles.push(AccountRole {
+            account_id: "bob.near".parse().unwrap(),
+            role: Role::Owner,
+        });
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            Rule::All {
+                rules: vec![Rule::Any { rules: vec![] }],
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Account bob.near does not have one of required roles [Owner]")]
+    fn prevents_non_owners_from_using_unrestricted_composite_rules() {
+        let mut contract = Contract::default();
+        contract.account_roles.push(AccountRole {
+            account_id: "bob.near".parse().unwrap(),
+            role: Role::User,
+        });
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            Rule::Any {
+                rules: vec![
+                    Rule::ActionAny {
+                        affected_account_id: String::from("bob.near"),
+                        status: Status::Success,
+                    },
+                    Rule::Not {
+                        rule: Box::new(Rule::ActionAny {
+                            affected_account_id: String::from("bob.near"),
+                            status: Status::Success,
+                        }),
+                    },
+                ],
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
+    #[test]
+    fn register_indexer_function_with_filter_function_call_args() {
+        let mut contract = Contract::default();
+
+        let rule = Rule::ActionFunctionCallArgs {
+            affected_account_id: String::from("token.sweat"),
+            status: Status::Success,
+            function: String::from("ft_transfer"),
+            args: vec![ArgsCondition {
+                pointer: String::from("/receiver_id"),
+                operator: ArgsOperator::Equals,
+                value: String::from("\"bob.near\""),
+            }],
+            min_deposit: Some(String::from("1")),
+            min_gas: None,
+        };
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            rule.clone(),
+            StartBlock::Latest,
+            None,
+        );
+
+        assert_eq!(
+            contract
+                .read_indexer_function("test_function".to_string(), None)
+                .unwrap()
+                .rule,
+            rule
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Minimum deposit 1.5 is invalid")]
+    fn prevents_invalid_minimum_deposit() {
+        let mut contract = Contract::default();
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            Rule::ActionFunctionCallArgs {
+                affected_account_id: String::from("token.sweat"),
+                status: Status::Success,
+                function: String::from("ft_transfer"),
+                args: vec![],
+                min_deposit: Some(String::from("1.5")),
+                min_gas: None,
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "JSON pointer receiver_id is invalid")]
+    fn prevents_invalid_args_pointer() {
+        let mut contract = Contract::default();
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            Rule::ActionFunctionCallArgs {
+                affected_account_id: String::from("token.sweat"),
+                status: Status::Success,
+                function: String::from("ft_transfer"),
+                args: vec![ArgsCondition {
+                    pointer: String::from("receiver_id"),
+                    operator: ArgsOperator::Equals,
+                    value: String::from("bob.near"),
+                }],
+                min_deposit: None,
+                min_gas: None,
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
+    #[test]
+    fn register_indexer_function_with_filter_action_kind() {
+        let mut contract = Contract::default();
+
+        let rule = Rule::ActionKind {
+            affected_account_id: String::from("bob.near"),
+            status: Status::Success,
+            action: ActionKind::AddKey {
+                full_access: Some(true),
+            },
+        };
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            rule.clone(),
+            StartBlock::Latest,
+            None,
+        );
+
+        assert_eq!(
+            contract
+                .read_indexer_function("test_function".to_string(), None)
+                .unwrap()
+                .rule,
+            rule
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Minimum stake lots is invalid")]
+    fn prevents_invalid_minimum_stake() {
+        let mut contract = Contract::default();
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            Rule::ActionKind {
+                affected_account_id: String::from("bob.near"),
+                status: Status::Success,
+                action: ActionKind::Stake {
+                    min_stake: Some(String::from("lots")),
+                },
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
+    #[test]
+    fn register_indexer_function_with_filter_state_change() {
+        let mut contract = Contract::default();
+
+        let rule = Rule::StateChange {
+            affected_account_id: String::from("bob.near"),
+            change: StateChangeKind::DataUpdate {
+                key_prefix: Some(String::from("STATE")),
+            },
+        };
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            rule.clone(),
+            StartBlock::Latest,
+            None,
+        );
+
+        assert_eq!(
+            contract
+                .read_indexer_function("test_function".to_string(), None)
+                .unwrap()
+                .rule,
+            rule
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "State change rules cannot be nested within composite rules")]
+    fn prevents_nested_state_change_rules() {
+        let mut contract = Contract::default();
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            Rule::Any {
+                rules: vec![Rule::StateChange {
+                    affected_account_id: String::from("bob.near"),
+                    change: StateChangeKind::AccountUpdate,
+                }],
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
+    #[test]
+    fn register_indexer_function_with_filter_transaction_signer() {
+        let mut contract = Contract::default();
+
+        let rule = Rule::TransactionSigner {
+            signer_account_id: String::from("bob.near"),
+        };
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            rule.clone(),
+            StartBlock::Latest,
+            None,
+        );
+
+        assert_eq!(
+            contract
+                .read_indexer_function("test_function".to_string(), None)
+                .unwrap()
+                .rule,
+            rule
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Account bob.near does not have one of required roles [Owner]")]
+    fn prevents_non_owners_from_using_wildcard_transaction_signer() {
+        let mut contract = Contract::default();
+        contract.account_roles.push(AccountRole {
+            account_id: "bob.near".parse().unwrap(),
+            role: Role::User,
+        });
+
+        contract.register(
+            "test_function".to_string(),
+            None,
+            String::new(),
+            String::new(),
+            Rule::TransactionSigner {
+                signer_account_id: String::from("*.near"),
+            },
+            StartBlock::Latest,
+            None,
+        );
+    }
+
     #[test]
     fn users_can_remove_their_own_functions() {
         let account_id = "bob.near".parse::<AccountId>().unwrap();
@@ -1842,6 +2335,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn list_indexers_changed_since_block_height() {
+        let account_id = "bob.near".parse::<AccountId>().unwrap();
+        let config = IndexerConfig {
+            start_block: StartBlock::Latest,
+            code: String::from("code"),
+            schema: String::from("schema"),
+            rule: Rule::ActionAny {
+                affected_account_id: String::from("social.near"),
+                status: Status::Any,
+            },
+            updated_at_block_height: None,
+            created_at_block_height: 10,
+            deleted_at_block_height: None,
+            forked_from: None,
+        };
+        let updated_config = IndexerConfig {
+            updated_at_block_height: Some(30),
+            ..config.clone()
+        };
+        let deleted_config = IndexerConfig {
+            deleted_at_block_height: Some(40),
+            ..config.clone()
+        };
+        let mut account_indexers = IndexerConfigByFunctionName::new(StorageKeys::AccountV5(
+            env::sha256_array(account_id.as_bytes()),
+        ));
+        account_indexers.insert("unchanged".to_string(), config);
+        account_indexers.insert("updated".to_string(), updated_config.clone());
+        account_indexers.insert("deleted".to_string(), deleted_config.clone());
+        let mut registry = IndexersByAccount::new(StorageKeys::Registry);
+        registry.insert(account_id.clone(), account_indexers);
+        let contract = Contract {
+            registry,
+            account_roles: vec![],
+        };
+
+        assert_eq!(
+            contract.list_changed_since(20),
+            HashMap::from([(
+                account_id.clone(),
+                HashMap::from([
+                    (String::from("updated"), updated_config),
+                    (String::from("deleted"), deleted_config.clone()),
+                ])
+            )])
+        );
+        assert_eq!(
+            contract.list_changed_since(30),
+            HashMap::from([(
+                account_id,
+                HashMap::from([(String::from("deleted"), deleted_config)])
+            )])
+        );
+        assert_eq!(contract.list_changed_since(40), HashMap::new());
+    }
+
     #[test]
     fn list_only_deleted_account_indexers() {
         let mut contract = Contract::default();
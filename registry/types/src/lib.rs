@@ -14,6 +14,10 @@ use serde::{Deserialize, Serialize};
 
 type FunctionName = String;
 
+/// Suffix appended to the function name of an Indexer to identify its shadow during a
+/// blue/green rollout, reserved so that registered Indexers can never collide with a shadow
+pub const SHADOW_FUNCTION_NAME_SUFFIX: &str = "__shadow";
+
 #[derive(Clone, Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Status {
@@ -22,6 +26,72 @@ pub enum Status {
     Fail,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ArgsOperator {
+    /// Value at the pointer is equal to the expected value
+    Equals,
+    /// Value at the pointer contains the expected value, i.e. a substring, an array element, or an
+    /// object key
+    Contains,
+}
+
+/// Condition applied to the decoded JSON arguments of a function call
+#[derive(Clone, Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct ArgsCondition {
+    /// JSON pointer to the value within the arguments, e.g. `/receiver_id`
+    pub pointer: String,
+    pub operator: ArgsOperator,
+    /// JSON encoded value to compare against, non-JSON values are treated as strings
+    pub value: String,
+}
+
+/// Kind of action to match, along with optional constraints specific to that kind
+#[derive(Clone, Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActionKind {
+    CreateAccount,
+    DeployContract,
+    FunctionCall,
+    Transfer {
+        /// Minimum deposit in yoctoNEAR
+        min_deposit: Option<String>,
+    },
+    Stake {
+        /// Minimum stake in yoctoNEAR
+        min_stake: Option<String>,
+    },
+    AddKey {
+        /// Only match full access keys when `true`, or function call keys when `false`
+        full_access: Option<bool>,
+    },
+    DeleteKey,
+    DeleteAccount,
+    Delegate,
+}
+
+/// Kind of state change to match, along with optional constraints specific to that kind
+#[derive(Clone, Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StateChangeKind {
+    AccountUpdate,
+    AccountDeletion,
+    AccessKeyUpdate,
+    AccessKeyDeletion,
+    DataUpdate {
+        /// Prefix of the raw storage key, e.g. `STATE`
+        key_prefix: Option<String>,
+    },
+    DataDeletion {
+        /// Prefix of the raw storage key, e.g. `STATE`
+        key_prefix: Option<String>,
+    },
+    ContractCodeUpdate,
+    ContractCodeDeletion,
+}
+
+/// Rules are persisted in contract storage with Borsh, which encodes variants by index, so new
+/// variants must only ever be appended
 #[derive(Clone, Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 #[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Rule {
@@ -40,6 +110,36 @@ pub enum Rule {
         version: String,
         event: String,
     },
+    /// Matches when at least one of the nested rules match
+    Any { rules: Vec<Rule> },
+    /// Matches when all of the nested rules match
+    All { rules: Vec<Rule> },
+    /// Matches when the nested rule does not match
+    Not { rule: Box<Rule> },
+    ActionFunctionCallArgs {
+        affected_account_id: String,
+        status: Status,
+        function: String,
+        args: Vec<ArgsCondition>,
+        /// Minimum attached deposit in yoctoNEAR
+        min_deposit: Option<String>,
+        min_gas: Option<u64>,
+    },
+    /// Matches receipts containing at least one action of the specified kind
+    ActionKind {
+        affected_account_id: String,
+        status: Status,
+        action: ActionKind,
+    },
+    /// Matches changes to the state of the specified accounts. Cannot be nested within composite
+    /// rules, as state changes are not tied to a single receipt
+    StateChange {
+        affected_account_id: String,
+        change: StateChangeKind,
+    },
+    /// Matches transactions signed by the specified accounts. Cannot be nested within composite
+    /// rules, as transactions are not tied to a single receipt
+    TransactionSigner { signer_account_id: String },
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -171,6 +171,45 @@ describe('HasuraClient', () => {
     expect(mockFetch.mock.calls).toMatchSnapshot();
   });
 
+  it('runs sql and retracks tables in a single bulk query', async () => {
+    const mockFetch = jest
+      .fn()
+      .mockResolvedValue({
+        status: 200,
+        text: () => JSON.stringify({})
+      });
+    const client = new HasuraClient({ fetch: mockFetch as unknown as typeof fetch }, config);
+
+    await client.executeSqlAndRetrackTables(
+      'source',
+      'ALTER SCHEMA shadow RENAME TO live;',
+      [{ schema: 'live', name: 'height' }],
+      [{ schema: 'live', name: 'width' }],
+      'role',
+      ['select']
+    );
+
+    expect(mockFetch).toBeCalledTimes(1);
+    expect(mockFetch.mock.calls[0][0]).toBe(`${config.endpoint}/v2/query`);
+    expect(JSON.parse(mockFetch.mock.calls[0][1].body)).toEqual({
+      type: 'bulk',
+      args: [
+        { type: 'pg_untrack_table', args: { source: 'source', table: { schema: 'live', name: 'height' }, cascade: true } },
+        { type: 'run_sql', args: { source: 'source', sql: 'ALTER SCHEMA shadow RENAME TO live;', read_only: false } },
+        { type: 'pg_track_table', args: { source: 'source', table: { schema: 'live', name: 'width' } } },
+        {
+          type: 'pg_create_select_permission',
+          args: {
+            source: 'source',
+            table: { schema: 'live', name: 'width' },
+            role: 'role',
+            permission: { columns: '*', check: {}, computed_fields: [], filter: {}, allow_aggregations: true },
+          },
+        },
+      ],
+    });
+  });
+
   it('adds the specified permissions for the specified roles/table/schema', async () => {
     const mockFetch = jest
       .fn()
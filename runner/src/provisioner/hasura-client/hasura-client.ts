@@ -81,6 +81,8 @@ type MetadataRequestArgs = Record<string, any>;
 
 type MetadataRequests = Record<string, any>;
 
+type QueryRequests = Array<Record<string, any>>;
+
 interface Config {
   adminSecret: string
   endpoint: string
@@ -140,6 +142,32 @@ export default class HasuraClient {
     return JSON.parse(body);
   }
 
+  async executeBulkQueryRequest (
+    queryRequests: QueryRequests
+  ): Promise<any> {
+    const response: Response = await this.deps.fetch(
+      `${this.config.endpoint}/v2/query`,
+      {
+        method: 'POST',
+        headers: {
+          'X-Hasura-Admin-Secret': this.config.adminSecret,
+        },
+        body: JSON.stringify({
+          type: 'bulk',
+          args: queryRequests,
+        }),
+      }
+    );
+
+    const body: string = await response.text();
+
+    if (response.status !== 200) {
+      throw new Error(body);
+    }
+
+    return JSON.parse(body);
+  }
+
   async executeMetadataRequest (
     type: string,
     args: MetadataRequestArgs,
@@ -296,6 +324,46 @@ export default class HasuraClient {
     );
   }
 
+  /**
+   * Runs `sql` between untracking and tracking the specified tables, all within a single
+   * transaction, so tables moved between schemas remain queryable throughout
+   */
+  async executeSqlAndRetrackTables (
+    source: string,
+    sql: string,
+    tablesToUntrack: TableDefinition[],
+    tablesToTrack: TableDefinition[],
+    roleName: string,
+    permissions: string[]
+  ): Promise<any> {
+    return await this.executeBulkQueryRequest([
+      ...tablesToUntrack.map(table => ({
+        type: 'pg_untrack_table',
+        args: {
+          source,
+          table,
+          cascade: true,
+        },
+      })),
+      {
+        type: 'run_sql',
+        args: {
+          source,
+          sql,
+          read_only: false,
+        },
+      },
+      ...tablesToTrack.map(table => ({
+        type: 'pg_track_table',
+        args: {
+          source,
+          table,
+        },
+      })),
+      ...this.createPermissionRequests(source, tablesToTrack, roleName, permissions),
+    ]);
+  }
+
   async getForeignKeys (schemaName: string, source: string): Promise<any[]> {
     const { result } = await this.executeSql(
       `
@@ -406,32 +474,38 @@ export default class HasuraClient {
 
   async addPermissionsToTables (schemaName: string, source: string, tableNames: string[], roleName: string, permissions: string[]): Promise<any> {
     return await this.executeBulkMetadataRequest(
-      tableNames
-        .map((tableName) => (
-          permissions.map((permission) => ({
-            type: `pg_create_${permission}_permission`,
-            args: {
-              source,
-              table: {
-                name: tableName,
-                schema: schemaName,
-              },
-              role: roleName,
-              permission: {
-                columns: '*',
-                check: {},
-                computed_fields: [],
-                filter: {},
-                ...(permission !== 'select' && { backend_only: true }),
-                ...(permission === 'select' && { allow_aggregations: true })
-              },
-            },
-          }))
-        ))
-        .flat()
+      this.createPermissionRequests(
+        source,
+        tableNames.map((name) => ({ name, schema: schemaName })),
+        roleName,
+        permissions
+      )
     );
   }
 
+  private createPermissionRequests (source: string, tables: TableDefinition[], roleName: string, permissions: string[]): Array<Record<string, any>> {
+    return tables
+      .map((table) => (
+        permissions.map((permission) => ({
+          type: `pg_create_${permission}_permission`,
+          args: {
+            source,
+            table,
+            role: roleName,
+            permission: {
+              columns: '*',
+              check: {},
+              computed_fields: [],
+              filter: {},
+              ...(permission !== 'select' && { backend_only: true }),
+              ...(permission === 'select' && { allow_aggregations: true })
+            },
+          },
+        }))
+      ))
+      .flat();
+  }
+
   async addDatasource (userName: string, password: string, databaseName: string): Promise<any> {
     return await this.executeMetadataRequest('pg_add_source', {
       name: databaseName,
@@ -190,6 +190,86 @@ describe('Provisioner', () => {
     });
   });
 
+  describe('migrateSchema', () => {
+    it('applies additive changes and tracks new tables', async () => {
+      indexerConfig = new IndexerConfig('', accountId, functionName, 0, '', `${databaseSchema}; CREATE TABLE receipts (id text)`, LogLevel.INFO);
+      hasuraClient.exportMetadata = jest.fn().mockResolvedValue(hasuraMetadataWithProvisions);
+      hasuraClient.getTableNames = jest.fn().mockResolvedValue([...tableNamesWithSystemTables, 'receipts']);
+
+      await provisioner.migrateSchema(indexerConfig, databaseSchema);
+
+      expect(hasuraClient.executeSqlOnSchema).toHaveBeenCalledTimes(1);
+      expect(hasuraClient.executeSqlOnSchema).toBeCalledWith(indexerConfig.databaseName(), indexerConfig.schemaName(), expect.stringContaining('CREATE TABLE "receipts"'));
+      expect(hasuraClient.trackTables).toBeCalledWith(indexerConfig.schemaName(), ['receipts'], indexerConfig.databaseName());
+      expect(hasuraClient.addPermissionsToTables).toBeCalledWith(
+        indexerConfig.schemaName(),
+        indexerConfig.databaseName(),
+        ['receipts'],
+        indexerConfig.userName(),
+        [
+          'select',
+          'insert',
+          'update',
+          'delete'
+        ]
+      );
+    });
+
+    it('throws when changes are not additive', async () => {
+      indexerConfig = new IndexerConfig('', accountId, functionName, 0, '', 'CREATE TABLE blocks (hash text)', LogLevel.INFO);
+
+      await expect(provisioner.migrateSchema(indexerConfig, databaseSchema)).rejects.toThrow('Failed to migrate schema: Column blocks.height was removed, which requires recreating the data');
+      expect(hasuraClient.executeSqlOnSchema).not.toBeCalled();
+    });
+  });
+
+  describe('swapSchema', () => {
+    const shadowConfig = new IndexerConfig('', accountId, `${functionName}__shadow`, 0, '', databaseSchema, LogLevel.INFO);
+
+    beforeEach(() => {
+      const hasuraMetadataWithShadowProvisions = generateDefaultHasuraMetadata();
+      hasuraMetadataWithShadowProvisions.sources.push(generateSourceWithTables([shadowConfig.schemaName()], tableNamesWithSystemTables, indexerConfig.userName(), indexerConfig.databaseName()));
+
+      hasuraClient.exportMetadata = jest.fn()
+        .mockResolvedValueOnce(hasuraMetadataWithProvisions) // live
+        .mockResolvedValueOnce(hasuraMetadataWithShadowProvisions) // shadow
+        .mockResolvedValue(hasuraMetadataWithEmptySource); // renamed
+      hasuraClient.getTableNames = jest.fn().mockResolvedValue(tableNamesWithSystemTables);
+      hasuraClient.executeSqlAndRetrackTables = jest.fn().mockResolvedValue(null);
+    });
+
+    it('replaces the live schema with the shadow schema', async () => {
+      await provisioner.swapSchema(indexerConfig, shadowConfig);
+
+      expect(hasuraClient.untrackTables).not.toBeCalled();
+      expect(hasuraClient.executeSqlAndRetrackTables).toBeCalledWith(
+        indexerConfig.databaseName(),
+        "DO $$ BEGIN IF EXISTS (SELECT 1 FROM information_schema.schemata WHERE schema_name = 'morgs_near_test_function__shadow') THEN DROP SCHEMA IF EXISTS morgs_near_test_function_retired CASCADE; ALTER SCHEMA morgs_near_test_function RENAME TO morgs_near_test_function_retired; ALTER SCHEMA morgs_near_test_function__shadow RENAME TO morgs_near_test_function; END IF; END $$;",
+        [
+          ...tableNamesWithSystemTables.map((name) => ({ schema: indexerConfig.schemaName(), name })),
+          ...tableNamesWithSystemTables.map((name) => ({ schema: shadowConfig.schemaName(), name })),
+        ],
+        tableNamesWithSystemTables.map((name) => ({ schema: indexerConfig.schemaName(), name })),
+        indexerConfig.hasuraRoleName(),
+        ['select', 'insert', 'update', 'delete']
+      );
+      expect(hasuraClient.executeSqlOnSchema).not.toBeCalled();
+      expect(hasuraClient.dropSchema).toBeCalledWith(indexerConfig.databaseName(), 'morgs_near_test_function_retired');
+      expect(userPgClientQuery.mock.calls).toEqual([
+        ["SELECT cron.unschedule('morgs_near_test_function__shadow_sys_logs_create_partition');"],
+        ["SELECT cron.unschedule('morgs_near_test_function__shadow_sys_logs_delete_partition');"],
+      ]);
+    });
+
+    it('throws when renaming fails', async () => {
+      hasuraClient.executeSqlAndRetrackTables = jest.fn().mockRejectedValue(error);
+
+      await expect(provisioner.swapSchema(indexerConfig, shadowConfig)).rejects.toThrow('Failed to swap schema: Failed to rename schemas: some error');
+      expect(hasuraClient.trackTables).not.toBeCalled();
+      expect(hasuraClient.dropSchema).not.toBeCalled();
+    });
+  });
+
   describe('provisionUserApi', () => {
     it('provisions an API for the user', async () => {
       await provisioner.provisionUserApi(indexerConfig);
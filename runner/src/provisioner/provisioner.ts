@@ -13,6 +13,7 @@ import { type ProvisioningConfig } from '../indexer-config/indexer-config';
 import IndexerMetaClass, { METADATA_TABLE_UPSERT, MetadataFields, IndexerStatus, LogEntry } from '../indexer-meta';
 import logger from '../logger';
 import ProvisioningState from './provisioning-state/provisioning-state';
+import { generateAdditiveMigration } from './schema-migration';
 
 const DEFAULT_PASSWORD_LENGTH = 16;
 
@@ -199,6 +200,39 @@ export default class Provisioner {
     return await wrapError(async () => await this.hasuraClient.trackTables(schemaName, tableNames, databaseName), 'Failed to track tables');
   }
 
+  async untrackTables (schemaName: string, tableNames: string[], databaseName: string): Promise<void> {
+    return await wrapError(async () => await this.hasuraClient.untrackTables(databaseName, schemaName, tableNames), 'Failed to untrack tables');
+  }
+
+  async renameSchemas (
+    databaseName: string,
+    liveSchemaName: string,
+    shadowSchemaName: string,
+    retiredSchemaName: string,
+    liveTableNames: string[],
+    shadowTableNames: string[],
+    roleName: string
+  ): Promise<void> {
+    await wrapError(async () => {
+      await this.hasuraClient.executeSqlAndRetrackTables(
+        databaseName,
+        this.pgFormat(
+          'DO $$ BEGIN IF EXISTS (SELECT 1 FROM information_schema.schemata WHERE schema_name = %1$L) THEN DROP SCHEMA IF EXISTS %2$I CASCADE; ALTER SCHEMA %3$I RENAME TO %2$I; ALTER SCHEMA %1$I RENAME TO %3$I; END IF; END $$;',
+          shadowSchemaName,
+          retiredSchemaName,
+          liveSchemaName
+        ),
+        [
+          ...liveTableNames.map((name) => ({ schema: liveSchemaName, name })),
+          ...shadowTableNames.map((name) => ({ schema: shadowSchemaName, name })),
+        ],
+        shadowTableNames.map((name) => ({ schema: liveSchemaName, name })),
+        roleName,
+        ['select', 'insert', 'update', 'delete']
+      );
+    }, 'Failed to rename schemas');
+  }
+
   async addPermissionsToTables (indexerSchema: ProvisioningConfig, tableNames: string[], permissions: string[]): Promise<void> {
     return await wrapError(async () => await this.hasuraClient.addPermissionsToTables(
       indexerSchema.schemaName(),
@@ -348,6 +382,72 @@ export default class Provisioner {
     }, this.tracer, 'provision indexer resources');
   }
 
+  async migrateSchema (indexerConfig: ProvisioningConfig, previousSchema: string): Promise<void> {
+    const logger = this.logger.child({ accountId: indexerConfig.accountId, functionName: indexerConfig.functionName });
+
+    await wrapSpan(async () => {
+      await wrapError(async () => {
+        const migration = generateAdditiveMigration(previousSchema, indexerConfig.schema);
+
+        if (migration.length > 0) {
+          await this.runIndexerSql(indexerConfig.databaseName(), indexerConfig.schemaName(), migration);
+        } else {
+          logger.debug('Schema has no additive changes');
+        }
+
+        const provisioningState = await ProvisioningState.loadProvisioningState(this.hasuraClient, indexerConfig);
+        await this.provisionUserResources(indexerConfig, provisioningState);
+      }, 'Failed to migrate schema');
+    }, this.tracer, 'migrate indexer schema');
+  }
+
+  async recreate (indexerConfig: ProvisioningConfig): Promise<void> {
+    await wrapError(async () => {
+      await this.deprovision(indexerConfig);
+      await this.provisionUserApi(indexerConfig);
+    }, 'Failed to recreate');
+  }
+
+  /**
+   * Replaces the live Data Layer with the shadow one provisioned and populated by a new version of the Indexer.
+   * Schemas are swapped via rename in a single transaction, after which the previous live schema is dropped.
+   * Steps are safe to retry, the rename is skipped if the shadow schema no longer exists.
+   */
+  async swapSchema (liveConfig: ProvisioningConfig, shadowConfig: ProvisioningConfig): Promise<void> {
+    const userName = liveConfig.userName();
+    const databaseName = liveConfig.databaseName();
+    const liveSchemaName = liveConfig.schemaName();
+    const shadowSchemaName = shadowConfig.schemaName();
+    const retiredSchemaName = `${liveSchemaName}_retired`;
+
+    await wrapSpan(async () => {
+      await wrapError(async () => {
+        // Tables are tracked by schema, so are retracked under the new names within the same
+        // transaction as the rename, ensuring the live tables are never untracked
+        const liveProvisioningState = await ProvisioningState.loadProvisioningState(this.hasuraClient, liveConfig);
+        const shadowProvisioningState = await ProvisioningState.loadProvisioningState(this.hasuraClient, shadowConfig);
+
+        await this.renameSchemas(
+          databaseName,
+          liveSchemaName,
+          shadowSchemaName,
+          retiredSchemaName,
+          liveProvisioningState.getTrackedTables(),
+          shadowProvisioningState.getTrackedTables(),
+          liveConfig.hasuraRoleName()
+        );
+
+        // Provisioning state is keyed by schema name, so now reflects the previous shadow schema
+        const provisioningState = await ProvisioningState.loadProvisioningState(this.hasuraClient, liveConfig);
+        await this.provisionUserResources(liveConfig, provisioningState);
+
+        // Jobs of the live schema are scheduled by name, so continue to work after the rename
+        await this.dropSchemaAndMetadata(databaseName, retiredSchemaName);
+        await this.removeLogPartitionJobs(userName, shadowSchemaName);
+      }, 'Failed to swap schema');
+    }, this.tracer, 'swap indexer schema');
+  }
+
   async writeFailureToUserLogs (indexerConfig: ProvisioningConfig, error: Error): Promise<void> {
     const indexerMeta = new this.IndexerMeta(indexerConfig, await this.getPostgresConnectionParameters(indexerConfig.userName()));
     await indexerMeta.writeLogs([LogEntry.systemError(error.message)]);
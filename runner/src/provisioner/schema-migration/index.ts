@@ -0,0 +1 @@
+export { generateAdditiveMigration } from './schema-migration';
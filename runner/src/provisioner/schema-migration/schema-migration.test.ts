@@ -0,0 +1,51 @@
+import { generateAdditiveMigration } from './schema-migration';
+
+describe('generateAdditiveMigration', () => {
+  const previousSchema = 'CREATE TABLE blocks (height numeric NOT NULL, hash text);';
+
+  it('generates nothing when the schema is unchanged', () => {
+    expect(generateAdditiveMigration(previousSchema, previousSchema)).toBe('');
+  });
+
+  it('creates new tables', () => {
+    const schema = `${previousSchema} CREATE TABLE receipts (id text PRIMARY KEY);`;
+
+    const migration = generateAdditiveMigration(previousSchema, schema);
+
+    expect(migration).toMatch(/^CREATE TABLE "receipts"/);
+    expect(migration).not.toContain('"blocks"');
+  });
+
+  it('adds new columns to existing tables', () => {
+    const schema = 'CREATE TABLE blocks (height numeric NOT NULL, hash text, Timestamp bigint);';
+
+    expect(generateAdditiveMigration(previousSchema, schema)).toMatch(/^ALTER TABLE "blocks" ADD COLUMN "timestamp" bigint;$/i);
+  });
+
+  it('applies new non-table statements', () => {
+    const schema = `${previousSchema} CREATE INDEX blocks_hash_idx ON blocks (hash);`;
+
+    expect(generateAdditiveMigration(previousSchema, schema)).toMatch(/^CREATE INDEX/);
+  });
+
+  it('treats unquoted names case insensitively', () => {
+    const schema = 'CREATE TABLE Blocks (HEIGHT numeric NOT NULL, hash text);';
+
+    expect(generateAdditiveMigration(previousSchema, schema)).toBe('');
+  });
+
+  it('throws when a table is removed', () => {
+    expect(() => generateAdditiveMigration(previousSchema, 'CREATE TABLE receipts (id text);'))
+      .toThrow('Table blocks was removed, which requires recreating the data');
+  });
+
+  it('throws when a column is removed', () => {
+    expect(() => generateAdditiveMigration(previousSchema, 'CREATE TABLE blocks (height numeric NOT NULL);'))
+      .toThrow('Column blocks.hash was removed, which requires recreating the data');
+  });
+
+  it('throws when a column is modified', () => {
+    expect(() => generateAdditiveMigration(previousSchema, 'CREATE TABLE blocks (height text NOT NULL, hash text);'))
+      .toThrow('Column blocks.height was modified, which requires recreating the data');
+  });
+});
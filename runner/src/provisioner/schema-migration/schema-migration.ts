@@ -0,0 +1,130 @@
+import { Parser } from 'node-sql-parser';
+
+const parser = new Parser();
+const parserOptions = { database: 'Postgresql' };
+
+interface TableDefinition {
+  statement: any
+  columns: Map<string, any>
+}
+
+interface ParsedSchema {
+  tables: Map<string, TableDefinition>
+  otherStatements: string[]
+}
+
+function isQuotedTableName (schema: string, tableName: string): boolean {
+  return new RegExp(`\\bcreate\\s+table\\s+(if\\s+not\\s+exists\\s+)?"${tableName}"`, 'i').test(schema);
+}
+
+// Postgres folds unquoted identifiers to lower case, whereas generated SQL quotes all identifiers,
+// so names must be normalized for both comparison and generation
+function normalizeTableName (schema: string, tableName: string): string {
+  return isQuotedTableName(schema, tableName) ? tableName : tableName.toLowerCase();
+}
+
+function normalizeColumnName (columnDef: any): string {
+  const columnNameDef = columnDef.column.column.expr;
+
+  if (columnNameDef.type !== 'double_quote_string') {
+    columnNameDef.value = columnNameDef.value.toLowerCase();
+  }
+
+  return columnNameDef.value;
+}
+
+function parseSchema (schema: string): ParsedSchema {
+  let syntaxTree = parser.astify(schema, parserOptions);
+  syntaxTree = Array.isArray(syntaxTree) ? syntaxTree : [syntaxTree];
+
+  const tables = new Map<string, TableDefinition>();
+  const otherStatements: string[] = [];
+
+  for (const statement of syntaxTree as any[]) {
+    if (statement.type === 'create' && statement.keyword === 'table' && statement.table !== undefined) {
+      const tableName = normalizeTableName(schema, statement.table[0].table);
+      statement.table[0].table = tableName;
+
+      const columns = new Map<string, any>();
+      for (const createDef of statement.create_definitions ?? []) {
+        if (createDef.column?.type === 'column_ref') {
+          columns.set(normalizeColumnName(createDef), createDef);
+        }
+      }
+
+      tables.set(tableName, { statement, columns });
+    } else {
+      otherStatements.push(parser.sqlify(statement, parserOptions));
+    }
+  }
+
+  return { tables, otherStatements };
+}
+
+function sqlifyColumnDefinition (table: TableDefinition, columnDef: any): string {
+  const sql = parser.sqlify({ ...table.statement, table_options: null, create_definitions: [columnDef] }, parserOptions);
+
+  return sql.slice(sql.indexOf('(') + 1, sql.lastIndexOf(')'));
+}
+
+function quoteIdentifier (identifier: string): string {
+  return `"${identifier.replace(/"/g, '""')}"`;
+}
+
+function sqlifyTableName (table: TableDefinition): string {
+  const { db, table: tableName } = table.statement.table[0];
+
+  return [db, tableName].filter(Boolean).map(quoteIdentifier).join('.');
+}
+
+/**
+ * Generates SQL which migrates a Data Layer provisioned with `previousSchema` to `schema`, by only
+ * creating new tables/columns and applying new statements, e.g. indexes. Throws if an existing
+ * table or column has been removed or modified, as that can only be achieved by recreating the data.
+ */
+export function generateAdditiveMigration (previousSchema: string, schema: string): string {
+  const previous = parseSchema(previousSchema);
+  const next = parseSchema(schema);
+
+  const statements: string[] = [];
+
+  for (const [tableName, table] of next.tables) {
+    if (!previous.tables.has(tableName)) {
+      statements.push(parser.sqlify(table.statement, parserOptions));
+    }
+  }
+
+  for (const [tableName, previousTable] of previous.tables) {
+    const table = next.tables.get(tableName);
+
+    if (table === undefined) {
+      throw new Error(`Table ${tableName} was removed, which requires recreating the data`);
+    }
+
+    for (const [columnName, previousColumnDef] of previousTable.columns) {
+      const columnDef = table.columns.get(columnName);
+
+      if (columnDef === undefined) {
+        throw new Error(`Column ${tableName}.${columnName} was removed, which requires recreating the data`);
+      }
+
+      if (JSON.stringify(columnDef) !== JSON.stringify(previousColumnDef)) {
+        throw new Error(`Column ${tableName}.${columnName} was modified, which requires recreating the data`);
+      }
+    }
+
+    for (const [columnName, columnDef] of table.columns) {
+      if (!previousTable.columns.has(columnName)) {
+        statements.push(`ALTER TABLE ${sqlifyTableName(table)} ADD COLUMN ${sqlifyColumnDefinition(table, columnDef)}`);
+      }
+    }
+  }
+
+  for (const statement of next.otherStatements) {
+    if (!previous.otherStatements.includes(statement)) {
+      statements.push(statement);
+    }
+  }
+
+  return statements.map((statement) => `${statement};`).join('\n');
+}
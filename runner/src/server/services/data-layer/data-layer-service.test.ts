@@ -2,6 +2,7 @@ import { type ServerUnaryCall, status } from '@grpc/grpc-js';
 
 import { createDataLayerService, type AsyncTask } from './data-layer-service';
 import { TaskStatus } from '../../../generated/data_layer/TaskStatus';
+import { MigrationStrategy } from '../../../generated/data_layer/MigrationStrategy';
 import type Provisioner from '../../../provisioner';
 
 describe('DataLayerService', () => {
@@ -52,13 +53,14 @@ describe('DataLayerService', () => {
 
     it('should return FAILED if the task has failed', (done) => {
       const tasks = {
-        id: { pending: false, completed: false, failed: true } as unknown as AsyncTask
+        id: { pending: false, completed: false, failed: true, error: 'relation already exists' } as unknown as AsyncTask
       };
       const call = {
         request: { taskId: 'id' }
       } as unknown as ServerUnaryCall<any, any>;
       const callback = (_error: any, response: any): void => {
         expect(response.status).toBe(TaskStatus.FAILED);
+        expect(response.error).toBe('relation already exists');
         done();
       };
 
@@ -104,4 +106,68 @@ describe('DataLayerService', () => {
       createDataLayerService(provisioner, tasks).StartDeprovisioningTask(call, callback);
     });
   });
+
+  describe('MigrateSchema', () => {
+    it('should start an additive migration task', (done) => {
+      const tasks: Record<any, any> = {};
+      const provisioner = {
+        migrateSchema: jest.fn().mockResolvedValue(null),
+        recreate: jest.fn().mockResolvedValue(null)
+      } as unknown as Provisioner;
+      const call = {
+        request: { accountId: 'testAccount', functionName: 'testFunction', previousSchema: 'previousSchema', schema: 'testSchema', strategy: MigrationStrategy.ADDITIVE }
+      } as unknown as ServerUnaryCall<any, any>;
+      const callback = (_error: any, response: any): void => {
+        expect(tasks[response.taskId]).toBeDefined();
+        expect(tasks[response.taskId].pending).toBe(true);
+        expect(provisioner.migrateSchema).toHaveBeenCalledWith(expect.objectContaining({ schema: 'testSchema' }), 'previousSchema');
+        expect(provisioner.recreate).not.toHaveBeenCalled();
+        done();
+      };
+
+      createDataLayerService(provisioner, tasks).MigrateSchema(call, callback);
+    });
+
+    it('should start a recreate migration task', (done) => {
+      const tasks: Record<any, any> = {};
+      const provisioner = {
+        migrateSchema: jest.fn().mockResolvedValue(null),
+        recreate: jest.fn().mockResolvedValue(null)
+      } as unknown as Provisioner;
+      const call = {
+        request: { accountId: 'testAccount', functionName: 'testFunction', previousSchema: 'previousSchema', schema: 'testSchema', strategy: MigrationStrategy.RECREATE }
+      } as unknown as ServerUnaryCall<any, any>;
+      const callback = (_error: any, response: any): void => {
+        expect(tasks[response.taskId]).toBeDefined();
+        expect(provisioner.recreate).toHaveBeenCalledWith(expect.objectContaining({ schema: 'testSchema' }));
+        expect(provisioner.migrateSchema).not.toHaveBeenCalled();
+        done();
+      };
+
+      createDataLayerService(provisioner, tasks).MigrateSchema(call, callback);
+    });
+  });
+
+  describe('SwapSchema', () => {
+    it('should start a new swap task', (done) => {
+      const tasks: Record<any, any> = {};
+      const provisioner = {
+        swapSchema: jest.fn().mockResolvedValue(null)
+      } as unknown as Provisioner;
+      const call = {
+        request: { accountId: 'testAccount', functionName: 'testFunction', shadowFunctionName: 'testFunction__shadow', schema: 'testSchema' }
+      } as unknown as ServerUnaryCall<any, any>;
+      const callback = (_error: any, response: any): void => {
+        expect(tasks[response.taskId]).toBeDefined();
+        expect(tasks[response.taskId].pending).toBe(true);
+        expect(provisioner.swapSchema).toHaveBeenCalledWith(
+          expect.objectContaining({ functionName: 'testFunction', schema: 'testSchema' }),
+          expect.objectContaining({ functionName: 'testFunction__shadow', schema: 'testSchema' })
+        );
+        done();
+      };
+
+      createDataLayerService(provisioner, tasks).SwapSchema(call, callback);
+    });
+  });
 });
@@ -12,12 +12,16 @@ import { type DataLayerHandlers } from '../../../generated/data_layer/DataLayer'
 import { type StartTaskResponse } from '../../../generated/data_layer/StartTaskResponse';
 import { type ProvisionRequest__Output } from '../../../generated/data_layer/ProvisionRequest';
 import { type DeprovisionRequest__Output } from '../../../generated/data_layer/DeprovisionRequest';
+import { type MigrateSchemaRequest__Output } from '../../../generated/data_layer/MigrateSchemaRequest';
+import { MigrationStrategy } from '../../../generated/data_layer/MigrationStrategy';
+import { type SwapSchemaRequest__Output } from '../../../generated/data_layer/SwapSchemaRequest';
 import { TaskStatus } from '../../../generated/data_layer/TaskStatus';
 
 export class AsyncTask {
   public failed: boolean;
   public pending: boolean;
   public completed: boolean;
+  public error?: string;
 
   constructor (
     public readonly promise: Promise<void>
@@ -26,6 +30,7 @@ export class AsyncTask {
       this.completed = true;
     }).catch((error) => {
       this.failed = true;
+      this.error = error instanceof Error ? error.message : String(error);
       return error;
     }).finally(() => {
       this.pending = false;
@@ -73,7 +78,7 @@ export function createDataLayerService (
       }
 
       if (task.failed) {
-        callback(null, { status: TaskStatus.FAILED });
+        callback(null, { status: TaskStatus.FAILED, error: task.error ?? '' });
         return;
       }
 
@@ -125,6 +130,62 @@ export function createDataLayerService (
           })
       );
 
+      callback(null, { taskId });
+    },
+
+    MigrateSchema (call: ServerUnaryCall<MigrateSchemaRequest__Output, StartTaskResponse>, callback: sendUnaryData<StartTaskResponse>): void {
+      const { accountId, functionName, previousSchema, schema, strategy } = call.request;
+
+      const provisioningConfig = new ProvisioningConfig(accountId, functionName, schema);
+
+      const logger = createLogger(provisioningConfig);
+
+      const taskId = crypto.randomUUID();
+
+      logger.info(`Starting ${strategy} migration task: ${taskId}`);
+
+      const migration = strategy === MigrationStrategy.RECREATE
+        ? provisioner.recreate(provisioningConfig)
+        : provisioner.migrateSchema(provisioningConfig, previousSchema);
+
+      tasks[taskId] = new AsyncTask(
+        migration
+          .then(() => {
+            logger.info('Successfully migrated Data Layer');
+          })
+          .catch((err) => {
+            logger.warn('Failed to migrate Data Layer', err);
+            throw err;
+          })
+      );
+
+      callback(null, { taskId });
+    },
+
+    SwapSchema (call: ServerUnaryCall<SwapSchemaRequest__Output, StartTaskResponse>, callback: sendUnaryData<StartTaskResponse>): void {
+      const { accountId, functionName, shadowFunctionName, schema } = call.request;
+
+      const provisioningConfig = new ProvisioningConfig(accountId, functionName, schema);
+      const shadowProvisioningConfig = new ProvisioningConfig(accountId, shadowFunctionName, schema);
+
+      const logger = createLogger(provisioningConfig);
+
+      const taskId = crypto.randomUUID();
+
+      logger.info(`Starting schema swap task: ${taskId}`);
+
+      tasks[taskId] = new AsyncTask(
+        provisioner
+          .swapSchema(provisioningConfig, shadowProvisioningConfig)
+          .then(() => {
+            logger.info('Successfully swapped Data Layer');
+          })
+          .catch((err) => {
+            logger.warn('Failed to swap Data Layer', err);
+            throw err;
+          })
+      );
+
       callback(null, { taskId });
     }
   };
@@ -35,6 +35,10 @@ module "queryapi-coordinator-mainnet-container" {
         name  = "GRPC_PORT",
         value = "9003"
       },
+      {
+        name  = "METRICS_PORT",
+        value = "9180"
+      },
       {
         name  = "GCP_LOGGING_ENABLED",
         value = "true"